@@ -0,0 +1,16 @@
+
+
+// Requiring a task twice tests
+
+#[test]
+fn test_require_same_task_twice() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let hello = Return("Hello");
+  let task = Sequence(vec![hello.clone(), hello.clone()]);
+  // `hello` is required twice by the same task, but only executed once.
+  pie.require_then_assert(&task, |tracker| {
+    assert!(tracker.one_execute_of(&hello));
+  })?;
+  pie.require_then_assert_no_execute(&task)?;
+  Ok(())
+}
@@ -0,0 +1,91 @@
+use std::fs::write;
+use std::io::{self, BufReader, Read};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use dev_shared::{create_temp_dir, write_until_modified};
+use pie::{Context, Pie, Task};
+use pie::tracker::event::EventTracker;
+use pie::tracker::writing::WritingTracker;
+
+/// Serializable testing tasks, required for writing events to an event log.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+enum LogTask {
+  ReadFile(PathBuf),
+  ToLower(Box<LogTask>),
+}
+impl Task for LogTask {
+  type Output = String;
+  fn execute<C: Context<Self>>(&self, context: &mut C) -> Self::Output {
+    match self {
+      LogTask::ReadFile(path) => {
+        let mut string = String::new();
+        if let Some(mut file) = context.require_file(path).expect("failed to require file") {
+          file.read_to_string(&mut string).expect("failed to read file");
+        }
+        string
+      }
+      LogTask::ToLower(string_provider_task) => context.require_task(string_provider_task).to_lowercase(),
+    }
+  }
+}
+
+#[test]
+fn test_write_read_json_lines() -> Result<(), io::Error> {
+  let mut pie = Pie::with_tracker(EventTracker::default());
+  let temp_dir = create_temp_dir()?;
+
+  let path = temp_dir.path().join("in.txt");
+  write(&path, "HELLO")?;
+  let task = LogTask::ToLower(Box::new(LogTask::ReadFile(path.clone())));
+  assert_eq!(pie.new_session().require(&task), "hello");
+
+  let mut log = Vec::new();
+  pie.tracker().write_json_lines(&mut log)?;
+  assert_eq!(String::from_utf8_lossy(&log).lines().count(), pie.tracker().slice().len());
+
+  let read_tracker = EventTracker::read_json_lines(BufReader::new(log.as_slice()))?;
+  assert_eq!(pie.tracker().slice(), read_tracker.slice());
+
+  // Change the file and build again, which should result in a different event log.
+  write_until_modified(&path, "WORLD")?;
+  assert_eq!(pie.new_session().require(&task), "world");
+  let mut new_log = Vec::new();
+  pie.tracker().write_json_lines(&mut new_log)?;
+  assert_ne!(log, new_log);
+
+  Ok(())
+}
+
+#[test]
+fn test_replay() -> Result<(), io::Error> {
+  let mut pie = Pie::with_tracker(EventTracker::default());
+  let temp_dir = create_temp_dir()?;
+
+  let path = temp_dir.path().join("in.txt");
+  write(&path, "HELLO")?;
+  let task = LogTask::ToLower(Box::new(LogTask::ReadFile(path.clone())));
+  pie.new_session().require(&task);
+
+  // Replaying into another event tracker results in the same events.
+  let mut replay_tracker = EventTracker::default();
+  pie.tracker().replay(&mut replay_tracker);
+  assert_eq!(pie.tracker().slice(), replay_tracker.slice());
+
+  // Replaying into a writing tracker results in a build log.
+  let mut writing_tracker = WritingTracker::new(Vec::new());
+  pie.tracker().replay(&mut writing_tracker);
+  let build_log = String::from_utf8_lossy(writing_tracker.writer());
+  assert!(build_log.contains(&format!("r {}", path.display())));
+  assert!(build_log.contains("◀ \"hello\""));
+  assert!(build_log.ends_with("🏁\n"));
+
+  Ok(())
+}
+
+#[test]
+fn test_read_json_lines_error() {
+  let result = EventTracker::<LogTask, String>::read_json_lines(BufReader::new("not an event\n".as_bytes()));
+  assert!(result.is_err());
+}
@@ -0,0 +1,303 @@
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+use crate::dependency::{Dependency, FileDependency, Inconsistency, TaskDependency};
+use crate::stamp::OutputStamper;
+use crate::Task;
+
+pub mod writing;
+pub mod event;
+
+/// Trait for tracking build events. Can be used to implement logging, event tracing, progress tracking, metrics, etc.
+#[allow(unused_variables)]
+pub trait Tracker<T: Task> {
+  /// Start: a new build.
+  fn build_start(&mut self) {}
+  /// End: completed build.
+  fn build_end(&mut self) {}
+
+  /// End: created a require file `dependency`.
+  fn require_file_end(&mut self, dependency: &FileDependency) {}
+  /// End: created a provide file `dependency`.
+  fn provide_file_end(&mut self, dependency: &FileDependency) {}
+  /// Start: require `task` using `stamper`.
+  fn require_task_start(&mut self, task: &T, stamper: &OutputStamper) {}
+  /// End: required a task, resulting in a task `dependency` and `output`, and the task `was_executed`.
+  fn require_task_end(&mut self, dependency: &TaskDependency<T, T::Output>, output: &T::Output, was_executed: bool) {}
+
+  /// Start: check consistency of `dependency`.
+  fn check_dependency_start(&mut self, dependency: &Dependency<T, T::Output>) {}
+  /// End: checked consistency of `dependency`, possibly found `inconsistency`.
+  fn check_dependency_end(
+    &mut self,
+    dependency: &Dependency<T, T::Output>,
+    inconsistency: Result<Option<&Inconsistency<T::Output>>, &io::Error>
+  ) {}
+
+  /// Start: execute `task`.
+  fn execute_start(&mut self, task: &T) {}
+  /// End: executed `task` resulting in `output`.
+  fn execute_end(&mut self, task: &T, output: &T::Output) {}
+}
+
+/// [`Tracker`] that does nothing.
+#[derive(Copy, Clone, Debug)]
+pub struct NoopTracker;
+impl<T: Task> Tracker<T> for NoopTracker {}
+
+/// [`Tracker`] that forwards build events to 2 trackers.
+#[derive(Copy, Clone, Debug)]
+pub struct CompositeTracker<A1, A2>(pub A1, pub A2);
+impl<T: Task, A1: Tracker<T>, A2: Tracker<T>> Tracker<T> for CompositeTracker<A1, A2> {
+  fn build_start(&mut self) {
+    self.0.build_start();
+    self.1.build_start();
+  }
+  fn build_end(&mut self) {
+    self.0.build_end();
+    self.1.build_end();
+  }
+
+  fn provide_file_end(&mut self, dependency: &FileDependency) {
+    self.0.provide_file_end(dependency);
+    self.1.provide_file_end(dependency);
+  }
+  fn require_file_end(&mut self, dependency: &FileDependency) {
+    self.0.require_file_end(dependency);
+    self.1.require_file_end(dependency);
+  }
+  fn require_task_start(&mut self, task: &T, stamper: &OutputStamper) {
+    self.0.require_task_start(task, stamper);
+    self.1.require_task_start(task, stamper);
+  }
+  fn require_task_end(&mut self, dependency: &TaskDependency<T, T::Output>, output: &T::Output, was_executed: bool) {
+    self.0.require_task_end(dependency, output, was_executed);
+    self.1.require_task_end(dependency, output, was_executed);
+  }
+
+  fn check_dependency_start(&mut self, dependency: &Dependency<T, T::Output>) {
+    self.0.check_dependency_start(dependency);
+    self.1.check_dependency_start(dependency);
+  }
+  fn check_dependency_end(
+    &mut self,
+    dependency: &Dependency<T, T::Output>,
+    inconsistency: Result<Option<&Inconsistency<T::Output>>, &io::Error>
+  ) {
+    self.0.check_dependency_end(dependency, inconsistency);
+    self.1.check_dependency_end(dependency, inconsistency);
+  }
+
+  fn execute_start(&mut self, task: &T) {
+    self.0.execute_start(task);
+    self.1.execute_start(task);
+  }
+  fn execute_end(&mut self, task: &T, output: &T::Output) {
+    self.0.execute_end(task, output);
+    self.1.execute_end(task, output);
+  }
+}
+
+/// [`Tracker`] that forwards build events to a dynamic list of trackers. Trackers can be added and removed at runtime,
+/// for example between sessions via [`Pie::tracker_mut`](crate::Pie::tracker_mut).
+pub struct MultiTracker<T: Task> {
+  trackers: Vec<(TrackerId, Box<dyn Tracker<T>>)>,
+  next_id: usize,
+}
+
+/// Identifier of a tracker added to a [`MultiTracker`], used to remove it again.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TrackerId(usize);
+
+impl<T: Task> Default for MultiTracker<T> {
+  fn default() -> Self { Self { trackers: Vec::new(), next_id: 0 } }
+}
+
+impl<T: Task> MultiTracker<T> {
+  /// Adds `tracker` to the end of the list of trackers, returning the identifier of the added tracker.
+  pub fn add(&mut self, tracker: impl Tracker<T> + 'static) -> TrackerId {
+    self.add_boxed(Box::new(tracker))
+  }
+  /// Adds boxed `tracker` to the end of the list of trackers, returning the identifier of the added tracker.
+  pub fn add_boxed(&mut self, tracker: Box<dyn Tracker<T>>) -> TrackerId {
+    let id = TrackerId(self.next_id);
+    self.next_id += 1;
+    self.trackers.push((id, tracker));
+    id
+  }
+  /// Removes the tracker with identifier `id`, returning `Some(tracker)` if it was removed, or `None` if no tracker
+  /// with that identifier exists.
+  pub fn remove(&mut self, id: TrackerId) -> Option<Box<dyn Tracker<T>>> {
+    let index = self.trackers.iter().position(|(i, _)| *i == id)?;
+    Some(self.trackers.remove(index).1)
+  }
+  /// Checks whether a tracker with identifier `id` exists.
+  pub fn contains(&self, id: TrackerId) -> bool {
+    self.trackers.iter().any(|(i, _)| *i == id)
+  }
+  /// Removes all trackers.
+  pub fn clear(&mut self) {
+    self.trackers.clear();
+  }
+
+  /// Returns the number of trackers.
+  pub fn len(&self) -> usize { self.trackers.len() }
+  /// Returns `true` if there are no trackers.
+  pub fn is_empty(&self) -> bool { self.trackers.is_empty() }
+
+  fn iter_mut(&mut self) -> impl Iterator<Item=&mut Box<dyn Tracker<T>>> {
+    self.trackers.iter_mut().map(|(_, t)| t)
+  }
+}
+
+impl<T: Task> Tracker<T> for MultiTracker<T> {
+  fn build_start(&mut self) {
+    self.iter_mut().for_each(|t| t.build_start());
+  }
+  fn build_end(&mut self) {
+    self.iter_mut().for_each(|t| t.build_end());
+  }
+
+  fn provide_file_end(&mut self, dependency: &FileDependency) {
+    self.iter_mut().for_each(|t| t.provide_file_end(dependency));
+  }
+  fn require_file_end(&mut self, dependency: &FileDependency) {
+    self.iter_mut().for_each(|t| t.require_file_end(dependency));
+  }
+  fn require_task_start(&mut self, task: &T, stamper: &OutputStamper) {
+    self.iter_mut().for_each(|t| t.require_task_start(task, stamper));
+  }
+  fn require_task_end(&mut self, dependency: &TaskDependency<T, T::Output>, output: &T::Output, was_executed: bool) {
+    self.iter_mut().for_each(|t| t.require_task_end(dependency, output, was_executed));
+  }
+
+  fn check_dependency_start(&mut self, dependency: &Dependency<T, T::Output>) {
+    self.iter_mut().for_each(|t| t.check_dependency_start(dependency));
+  }
+  fn check_dependency_end(
+    &mut self,
+    dependency: &Dependency<T, T::Output>,
+    inconsistency: Result<Option<&Inconsistency<T::Output>>, &io::Error>
+  ) {
+    self.iter_mut().for_each(|t| t.check_dependency_end(dependency, inconsistency));
+  }
+
+  fn execute_start(&mut self, task: &T) {
+    self.iter_mut().for_each(|t| t.execute_start(task));
+  }
+  fn execute_end(&mut self, task: &T, output: &T::Output) {
+    self.iter_mut().for_each(|t| t.execute_end(task, output));
+  }
+}
+
+/// [`Tracker`] implementation for shared trackers, forwarding build events to the inner tracker. Useful for keeping a
+/// handle to a tracker that is added to a [`MultiTracker`], for example to inspect its state.
+impl<T: Task, A: Tracker<T>> Tracker<T> for Rc<RefCell<A>> {
+  fn build_start(&mut self) {
+    self.borrow_mut().build_start();
+  }
+  fn build_end(&mut self) {
+    self.borrow_mut().build_end();
+  }
+
+  fn provide_file_end(&mut self, dependency: &FileDependency) {
+    self.borrow_mut().provide_file_end(dependency);
+  }
+  fn require_file_end(&mut self, dependency: &FileDependency) {
+    self.borrow_mut().require_file_end(dependency);
+  }
+  fn require_task_start(&mut self, task: &T, stamper: &OutputStamper) {
+    self.borrow_mut().require_task_start(task, stamper);
+  }
+  fn require_task_end(&mut self, dependency: &TaskDependency<T, T::Output>, output: &T::Output, was_executed: bool) {
+    self.borrow_mut().require_task_end(dependency, output, was_executed);
+  }
+
+  fn check_dependency_start(&mut self, dependency: &Dependency<T, T::Output>) {
+    self.borrow_mut().check_dependency_start(dependency);
+  }
+  fn check_dependency_end(
+    &mut self,
+    dependency: &Dependency<T, T::Output>,
+    inconsistency: Result<Option<&Inconsistency<T::Output>>, &io::Error>
+  ) {
+    self.borrow_mut().check_dependency_end(dependency, inconsistency);
+  }
+
+  fn execute_start(&mut self, task: &T) {
+    self.borrow_mut().execute_start(task);
+  }
+  fn execute_end(&mut self, task: &T, output: &T::Output) {
+    self.borrow_mut().execute_end(task, output);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{Context, Pie};
+  use crate::tracker::event::EventTracker;
+
+  use super::*;
+
+  /// Task that returns its owned string.
+  #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+  struct StringConstant(String);
+
+  impl Task for StringConstant {
+    type Output = String;
+    fn execute<C: Context<Self>>(&self, _context: &mut C) -> Self::Output {
+      self.0.clone()
+    }
+  }
+
+  #[test]
+  fn test_multi_tracker_forwards_events() {
+    let tracker_1 = Rc::new(RefCell::new(EventTracker::default()));
+    let tracker_2 = Rc::new(RefCell::new(EventTracker::default()));
+    let mut multi_tracker = MultiTracker::default();
+    multi_tracker.add(tracker_1.clone());
+    multi_tracker.add(tracker_2.clone());
+    assert_eq!(multi_tracker.len(), 2);
+
+    let mut pie = Pie::with_tracker(multi_tracker);
+    let task = StringConstant("Hello, World!".to_string());
+    pie.new_session().require(&task);
+    assert!(tracker_1.borrow().one_execute_of(&task));
+    assert!(tracker_2.borrow().one_execute_of(&task));
+  }
+
+  #[test]
+  fn test_multi_tracker_add_remove_between_sessions() {
+    let mut pie = Pie::with_tracker(MultiTracker::default());
+    let task = StringConstant("Hello, World!".to_string());
+    assert!(pie.tracker().is_empty());
+    pie.new_session().require(&task);
+
+    let event_tracker = Rc::new(RefCell::new(EventTracker::default()));
+    let id = pie.tracker_mut().add(event_tracker.clone());
+    assert!(pie.tracker().contains(id));
+    pie.new_session().require(&task);
+    assert!(!event_tracker.borrow().slice().is_empty());
+    assert!(!event_tracker.borrow().any_execute_of(&task));
+
+    assert!(pie.tracker_mut().remove(id).is_some());
+    assert!(!pie.tracker().contains(id));
+    assert!(pie.tracker_mut().remove(id).is_none());
+    let events_before = event_tracker.borrow().slice().len();
+    pie.new_session().require(&task);
+    assert_eq!(events_before, event_tracker.borrow().slice().len());
+  }
+
+  #[test]
+  fn test_multi_tracker_ids_are_unique() {
+    let mut multi_tracker = MultiTracker::<StringConstant>::default();
+    let id_1 = multi_tracker.add(NoopTracker);
+    multi_tracker.remove(id_1);
+    let id_2 = multi_tracker.add(NoopTracker);
+    assert_ne!(id_1, id_2);
+    assert!(!multi_tracker.contains(id_1));
+    multi_tracker.clear();
+    assert!(multi_tracker.is_empty());
+  }
+}
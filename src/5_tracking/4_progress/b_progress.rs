@@ -0,0 +1,300 @@
+use std::collections::HashSet;
+use std::io::{self, IsTerminal, Stderr, Write};
+use std::time::{Duration, Instant};
+
+use crate::dependency::{Dependency, Inconsistency, TaskDependency};
+use crate::Task;
+use crate::tracker::{Span, Tracker};
+
+/// [`Tracker`] that shows the progress of builds in a single status line, similar to the status line of Cargo. Shows
+/// the number of checked and executed tasks, the currently executing task, and the elapsed time.
+///
+/// Each task is counted as checked once per build, even when it is checked or required multiple times. Checked tasks
+/// are cloned into a set to identify them.
+///
+/// When the writer is not a terminal, writes a plain line for every executed task instead, as terminal control codes
+/// would end up as garbage in files and logs.
+#[derive(Clone, Debug)]
+pub struct ProgressTracker<W, T> {
+  writer: W,
+  is_terminal: bool,
+  max_task_width: usize,
+  redraw_interval: Duration,
+
+  build_start: Option<Instant>,
+  last_redraw: Option<Instant>,
+  checked: HashSet<T>,
+  executed: usize,
+  executing: Vec<String>,
+}
+
+impl<T: Task> ProgressTracker<Stderr, T> {
+  /// Creates a [`ProgressTracker`] that writes to standard error, showing a status line if standard error is a
+  /// terminal, and plain lines otherwise.
+  pub fn with_stderr() -> Self {
+    let stderr = io::stderr();
+    let is_terminal = stderr.is_terminal();
+    Self::new(stderr, is_terminal)
+  }
+}
+impl<W: Write, T: Task> ProgressTracker<W, T> {
+  /// Creates a [`ProgressTracker`] that writes to `writer`, showing a status line if `is_terminal` is `true`, and plain
+  /// lines otherwise.
+  pub fn new(writer: W, is_terminal: bool) -> Self {
+    Self {
+      writer,
+      is_terminal,
+      max_task_width: 60,
+      redraw_interval: Duration::from_millis(50),
+      build_start: None,
+      last_redraw: None,
+      checked: HashSet::new(),
+      executed: 0,
+      executing: Vec::new(),
+    }
+  }
+  /// Sets the maximum number of characters of the `Debug` form of the currently executing task to show. Longer tasks
+  /// are shortened.
+  pub fn with_max_task_width(mut self, max_task_width: usize) -> Self {
+    self.max_task_width = max_task_width;
+    self
+  }
+  /// Sets the minimum interval between redraws of the status line, preventing large builds from being slowed down by
+  /// writing to the terminal.
+  pub fn with_redraw_interval(mut self, redraw_interval: Duration) -> Self {
+    self.redraw_interval = redraw_interval;
+    self
+  }
+
+  /// Gets the writer of this progress tracker.
+  pub fn writer(&self) -> &W { &self.writer }
+  /// Gets the mutable writer of this progress tracker.
+  pub fn writer_mut(&mut self) -> &mut W { &mut self.writer }
+}
+
+impl<W: Write, T: Task> ProgressTracker<W, T> {
+  fn elapsed(&self) -> Duration {
+    self.build_start.map(|s| s.elapsed()).unwrap_or_default()
+  }
+
+  /// Records that `task` was checked, counting it only if it was not checked before in this build.
+  fn check(&mut self, task: &T) {
+    if !self.checked.contains(task) {
+      self.checked.insert(task.clone());
+      self.redraw(false);
+    }
+  }
+
+  /// Redraws the status line if enough time has passed since the last redraw, or if `force` is `true`.
+  fn redraw(&mut self, force: bool) {
+    if !self.is_terminal { return; }
+    let now = Instant::now();
+    if !force && self.last_redraw.is_some_and(|l| now.duration_since(l) < self.redraw_interval) { return; }
+    self.last_redraw = Some(now);
+
+    let elapsed = self.elapsed().as_secs_f64();
+    // `\r` moves the cursor to the start of the line, and `\x1b[2K` clears the line.
+    let _ = write!(&mut self.writer, "\r\x1b[2K{:>12} [{} checked, {} executed, {:.1}s]", "Building",
+      self.checked.len(), self.executed, elapsed);
+    if let Some(task) = self.executing.last() {
+      let _ = write!(&mut self.writer, ": {}", task);
+    }
+    let _ = self.writer.flush();
+  }
+  fn clear(&mut self) {
+    if !self.is_terminal { return; }
+    let _ = write!(&mut self.writer, "\r\x1b[2K");
+  }
+}
+
+/// Shortens `string` to at most `max_width` characters, replacing the end with `…` if it is too long. Returns an empty
+/// string if `max_width` is 0, as there is no room for `…`.
+fn shorten(string: &str, max_width: usize) -> String {
+  if string.chars().count() <= max_width {
+    string.to_string()
+  } else if max_width == 0 {
+    String::new()
+  } else {
+    let mut shortened: String = string.chars().take(max_width - 1).collect();
+    shortened.push('…');
+    shortened
+  }
+}
+
+impl<W: Write, T: Task> Tracker<T> for ProgressTracker<W, T> {
+  fn build_start(&mut self, _span: Span) {
+    self.build_start = Some(Instant::now());
+    self.last_redraw = None;
+    self.checked.clear();
+    self.executed = 0;
+    self.executing.clear();
+    self.redraw(true);
+  }
+  fn build_end(&mut self, _span: Span) {
+    self.clear();
+    let elapsed = self.elapsed().as_secs_f64();
+    let _ = writeln!(&mut self.writer, "{:>12} {} checked, {} executed in {:.2}s", "Finished", self.checked.len(),
+      self.executed, elapsed);
+    let _ = self.writer.flush();
+  }
+
+  fn require_task_end(
+    &mut self,
+    _span: Span,
+    dependency: &TaskDependency<T, T::Output>,
+    _output: &T::Output,
+    _was_executed: bool
+  ) {
+    self.check(dependency.task());
+  }
+  fn check_dependency_end(
+    &mut self,
+    _span: Span,
+    dependency: &Dependency<T, T::Output>,
+    _inconsistency: Result<Option<&Inconsistency<T::Output>>, &io::Error>
+  ) {
+    // Task dependencies are checked by making the task consistent, without requiring it.
+    if let Dependency::RequireTask(dependency) = dependency {
+      self.check(dependency.task());
+    }
+  }
+
+  fn execute_start(&mut self, _span: Span, task: &T) {
+    let task = shorten(&format!("{:?}", task), self.max_task_width);
+    if !self.is_terminal {
+      let _ = writeln!(&mut self.writer, "{:>12} {}", "Executing", task);
+    }
+    self.executing.push(task);
+    self.redraw(false);
+  }
+  fn execute_end(
+    &mut self,
+    _span: Span,
+    _task: &T,
+    _output: &T::Output,
+    _dependencies: &[&Dependency<T, T::Output>]
+  ) {
+    self.executing.pop();
+    self.executed += 1;
+    self.redraw(false);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::hash::{Hash, Hasher};
+
+  use crate::{Context, Pie};
+
+  use super::*;
+
+  /// Task that returns its owned string, requires the task in the box, or requires all tasks and concatenates their
+  /// outputs.
+  #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+  enum StringTask {
+    Constant(String),
+    Require(Box<StringTask>),
+    Concat(Vec<StringTask>),
+  }
+
+  impl Task for StringTask {
+    type Output = String;
+    fn execute<C: Context<Self>>(&self, context: &mut C) -> Self::Output {
+      match self {
+        StringTask::Constant(string) => string.clone(),
+        StringTask::Require(task) => context.require_task(task),
+        StringTask::Concat(tasks) => tasks.iter().map(|t| context.require_task(t)).collect(),
+      }
+    }
+  }
+
+  #[test]
+  fn test_plain_lines() {
+    let mut pie = Pie::with_tracker(ProgressTracker::new(Vec::new(), false));
+    let task = StringTask::Require(Box::new(StringTask::Constant("Hello, World!".to_string())));
+    pie.new_session().require(&task);
+
+    let output = String::from_utf8_lossy(pie.tracker().writer()).into_owned();
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "   Executing Require(Constant(\"Hello, World!\"))");
+    assert_eq!(lines[1], "   Executing Constant(\"Hello, World!\")");
+    assert!(lines[2].starts_with("    Finished 2 checked, 2 executed in "));
+    assert!(!output.contains('\x1b'));
+
+    // Both tasks are checked but not executed in a new session, as they are consistent.
+    pie.tracker_mut().writer_mut().clear();
+    pie.new_session().require(&task);
+    let output = String::from_utf8_lossy(pie.tracker().writer()).into_owned();
+    assert!(output.starts_with("    Finished 2 checked, 0 executed in "));
+  }
+
+  #[test]
+  fn test_count_distinct_checked_tasks() {
+    let mut pie = Pie::with_tracker(ProgressTracker::new(Vec::new(), false));
+    let constant = StringTask::Constant("Hello".to_string());
+    // `constant` is required by two tasks, but is counted as checked once.
+    let task = StringTask::Concat(vec![StringTask::Require(Box::new(constant.clone())), constant]);
+    pie.new_session().require(&task);
+    let output = String::from_utf8_lossy(pie.tracker().writer()).into_owned();
+    assert!(output.lines().last().unwrap().starts_with("    Finished 3 checked, 3 executed in "));
+
+    // Also when tasks are checked as task dependencies instead of required.
+    pie.tracker_mut().writer_mut().clear();
+    pie.new_session().require(&task);
+    let output = String::from_utf8_lossy(pie.tracker().writer()).into_owned();
+    assert!(output.starts_with("    Finished 3 checked, 0 executed in "));
+  }
+
+  /// Task that requires tasks `0` up to its number, of which all instances have the same hash.
+  #[derive(Clone, PartialEq, Eq, Debug)]
+  struct CollidingTask(usize);
+
+  impl Hash for CollidingTask {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+  }
+
+  impl Task for CollidingTask {
+    type Output = ();
+    fn execute<C: Context<Self>>(&self, context: &mut C) -> Self::Output {
+      for i in 0..self.0 {
+        context.require_task(&CollidingTask(i));
+      }
+    }
+  }
+
+  #[test]
+  fn test_count_checked_tasks_with_same_hash() {
+    let mut pie = Pie::with_tracker(ProgressTracker::new(Vec::new(), false));
+    // Distinct tasks are counted separately, even when their hashes are equal.
+    pie.new_session().require(&CollidingTask(3));
+    let output = String::from_utf8_lossy(pie.tracker().writer()).into_owned();
+    assert!(output.lines().last().unwrap().starts_with("    Finished 4 checked, 4 executed in "));
+  }
+
+  #[test]
+  fn test_status_line() {
+    let tracker = ProgressTracker::new(Vec::new(), true).with_redraw_interval(Duration::ZERO);
+    let mut pie = Pie::with_tracker(tracker);
+    let task = StringTask::Require(Box::new(StringTask::Constant("Hello, World!".to_string())));
+    pie.new_session().require(&task);
+
+    let output = String::from_utf8_lossy(pie.tracker().writer()).into_owned();
+    // Everything is drawn on a single line that is repeatedly cleared, followed by the final line.
+    assert_eq!(output.lines().count(), 1);
+    assert!(output.contains("\r\x1b[2K    Building [0 checked, 0 executed, "));
+    assert!(output.contains(": Constant(\"Hello, World!\")"));
+    let (_, last_line) = output.rsplit_once("\r\x1b[2K").unwrap();
+    assert!(last_line.starts_with("    Finished 2 checked, 2 executed in "));
+    assert!(last_line.ends_with("s\n"));
+  }
+
+  #[test]
+  fn test_shorten() {
+    assert_eq!(shorten("Hello", 5), "Hello");
+    assert_eq!(shorten("Hello, World!", 5), "Hell…");
+    assert_eq!(shorten("Hello, World!", 1), "…");
+    assert_eq!(shorten("Hello", 0), "");
+    assert_eq!(shorten("", 0), "");
+  }
+}
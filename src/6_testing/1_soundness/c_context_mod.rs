@@ -0,0 +1,2 @@
+pub mod non_incremental;
+pub(crate) mod top_down;
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::fs::remove_file;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use proptest::prelude::*;
+
+use dev_shared::{create_temp_dir, write_until_modified};
+use pie::Context;
+use pie::context::non_incremental::NonIncrementalContext;
+use pie::dependency::Dependency;
+use pie::stamp::FileStamper;
+use pie::tracker::event::Event;
+
+use crate::common::{test_pie, TestOutput, TestPieExt, TestTask};
+
+mod common;
+
+/// Number of input files that tasks can read, and that are edited between builds.
+const NUM_INPUT_FILES: usize = 3;
+/// Contents that are written to input files and returned by tasks.
+const STRINGS: [&str; 4] = ["", "a", "Hello", "WORLD"];
+
+/// Specification of a task that returns a string, from which a [`TestTask`] is created. We generate specifications
+/// instead of tasks, because specifications can only describe tasks without hidden dependencies and overlapping
+/// provided files.
+#[derive(Clone, Debug)]
+enum Spec {
+  Return(&'static str),
+  ReadInput(usize),
+  /// Write the output of the task to a fresh output file, then read that file, requiring the writing task directly or
+  /// through a [`TestTask::Sequence`].
+  ReadOutput(Box<Spec>, bool),
+  ToLower(Box<Spec>),
+  ToUpper(Box<Spec>),
+  Concat(Vec<Spec>),
+}
+
+impl Spec {
+  /// Creates a task from this specification, with input files in `input_dir` and output files in `output_dir`.
+  /// `num_outputs` is incremented for every output file, ensuring that output files are only written by one task.
+  fn to_task(&self, input_dir: &Path, output_dir: &Path, num_outputs: &mut usize) -> TestTask {
+    match self {
+      Spec::Return(string) => TestTask::Return(string),
+      Spec::ReadInput(index) => {
+        TestTask::ReadFile(input_dir.join(format!("in{}.txt", index)), FileStamper::Modified, None)
+      }
+      Spec::ReadOutput(spec, through_sequence) => {
+        let path = output_dir.join(format!("out{}.txt", num_outputs));
+        *num_outputs += 1;
+        let task = Box::new(spec.to_task(input_dir, output_dir, num_outputs));
+        let mut origin = TestTask::WriteFile(task, path.clone(), FileStamper::Modified);
+        if *through_sequence {
+          origin = TestTask::Sequence(vec![origin]);
+        }
+        TestTask::ReadFile(path, FileStamper::Modified, Some(Box::new(origin)))
+      }
+      Spec::ToLower(spec) => TestTask::ToLower(Box::new(spec.to_task(input_dir, output_dir, num_outputs))),
+      Spec::ToUpper(spec) => TestTask::ToUpper(Box::new(spec.to_task(input_dir, output_dir, num_outputs))),
+      Spec::Concat(specs) => {
+        TestTask::Concat(specs.iter().map(|s| s.to_task(input_dir, output_dir, num_outputs)).collect())
+      }
+    }
+  }
+}
+
+fn spec() -> impl Strategy<Value=Spec> {
+  let leaf = prop_oneof![
+    proptest::sample::select(&STRINGS[..]).prop_map(Spec::Return),
+    (0..NUM_INPUT_FILES).prop_map(Spec::ReadInput),
+  ];
+  leaf.prop_recursive(4, 24, 3, |inner| prop_oneof![
+    (inner.clone(), any::<bool>()).prop_map(|(s, b)| Spec::ReadOutput(Box::new(s), b)),
+    inner.clone().prop_map(|s| Spec::ToLower(Box::new(s))),
+    inner.clone().prop_map(|s| Spec::ToUpper(Box::new(s))),
+    proptest::collection::vec(inner, 1..4).prop_map(Spec::Concat),
+  ])
+}
+
+/// Edit to an input file.
+#[derive(Clone, Debug)]
+enum Edit {
+  Write(usize, &'static str),
+  Remove(usize),
+}
+
+impl Edit {
+  fn apply(&self, input_dir: &Path) -> Result<(), io::Error> {
+    match self {
+      Edit::Write(index, string) => {
+        write_until_modified(input_dir.join(format!("in{}.txt", index)), string)?;
+      }
+      Edit::Remove(index) => {
+        let path = input_dir.join(format!("in{}.txt", index));
+        if path.exists() {
+          remove_file(path)?;
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+fn edit() -> impl Strategy<Value=Edit> {
+  prop_oneof![
+    4 => (0..NUM_INPUT_FILES, proptest::sample::select(&STRINGS[..])).prop_map(|(i, s)| Edit::Write(i, s)),
+    1 => (0..NUM_INPUT_FILES).prop_map(Edit::Remove),
+  ]
+}
+
+/// Returns a copy of `task` where all paths in `from` are replaced with paths in `to`.
+fn rebase(task: &TestTask, from: &Path, to: &Path) -> TestTask {
+  let rebase_path = |path: &PathBuf| to.join(path.strip_prefix(from).expect("path is not in `from` directory"));
+  let rebase_box = |task: &TestTask| Box::new(rebase(task, from, to));
+  match task {
+    TestTask::ReadFile(path, stamper, origin) => {
+      TestTask::ReadFile(rebase_path(path), *stamper, origin.as_deref().map(rebase_box))
+    }
+    TestTask::WriteFile(task, path, stamper) => TestTask::WriteFile(rebase_box(task), rebase_path(path), *stamper),
+    TestTask::ToLower(task) => TestTask::ToLower(rebase_box(task)),
+    TestTask::ToUpper(task) => TestTask::ToUpper(rebase_box(task)),
+    TestTask::Sequence(tasks) => TestTask::Sequence(tasks.iter().map(|t| rebase(t, from, to)).collect()),
+    TestTask::Concat(tasks) => TestTask::Concat(tasks.iter().map(|t| rebase(t, from, to)).collect()),
+    task => task.clone(),
+  }
+}
+
+type TaskDependencies = Vec<Dependency<TestTask, Result<TestOutput, io::ErrorKind>>>;
+
+/// Builds the task specified by `spec` incrementally after each step of `edits`, and asserts that:
+/// - the output is equal to the output of building the task from scratch with the [`NonIncrementalContext`],
+/// - no task is executed while all its dependencies are consistent.
+///
+/// The incremental build and the non-incremental build use separate directories, such that files written by one build
+/// do not affect the other build.
+fn assert_sound(spec: &Spec, edits: &[Vec<Edit>]) -> Result<(), io::Error> {
+  let temp_dir = create_temp_dir()?;
+  let incremental_dir = temp_dir.path().join("incremental");
+  let oracle_dir = temp_dir.path().join("oracle");
+  let mut pie = test_pie();
+  let task = spec.to_task(&incremental_dir, &incremental_dir, &mut 0);
+  let oracle_task = rebase(&task, &incremental_dir, &oracle_dir);
+
+  for dir in [&incremental_dir, &oracle_dir] {
+    std::fs::create_dir(dir)?;
+    for index in 0..NUM_INPUT_FILES {
+      Edit::Write(index, STRINGS[index + 1]).apply(dir)?;
+    }
+  }
+
+  // Dependencies of tasks, as recorded when they were last executed.
+  let mut dependencies: HashMap<TestTask, TaskDependencies> = HashMap::new();
+  for (step, step_edits) in std::iter::once(&Vec::new()).chain(edits).enumerate() {
+    for edit in step_edits {
+      edit.apply(&incremental_dir)?;
+      edit.apply(&oracle_dir)?;
+    }
+
+    let mut executed: Vec<(TestTask, TaskDependencies)> = Vec::new();
+    let output = pie.require_then_assert(&task, |tracker| {
+      for event in tracker.iter() {
+        if let Event::ExecuteEnd(d) = event {
+          executed.push((d.task.clone(), d.dependencies.clone()));
+        }
+      }
+    });
+    let oracle_output = NonIncrementalContext.require_task(&oracle_task);
+    assert_eq!(output, oracle_output, "incremental build output differs from non-incremental build output in step {}",
+      step);
+
+    for (executed_task, new_dependencies) in executed {
+      if let Some(previous_dependencies) = dependencies.get(&executed_task) {
+        let any_inconsistent = previous_dependencies.iter().any(|d| match d {
+          Dependency::RequireFile(d) => d.is_inconsistent().expect("failed to check file dependency").is_some(),
+          // Provided files are only changed by the tasks that provide them, so an inconsistent provide file dependency
+          // is the result of executing the task, not the reason for executing it.
+          Dependency::ProvideFile(_) => false,
+          Dependency::RequireTask(d) => {
+            let output = NonIncrementalContext.require_task(&rebase(d.task(), &incremental_dir, &oracle_dir));
+            d.stamper().stamp(output) != *d.stamp()
+          }
+          Dependency::ReservedRequireTask => panic!("BUG: reserved task dependency after execution"),
+        });
+        assert!(any_inconsistent, "task {:?} was executed in step {}, but all its dependencies were consistent: {:?}",
+          executed_task, step, previous_dependencies);
+      }
+      dependencies.insert(executed_task, new_dependencies);
+    }
+  }
+  Ok(())
+}
+
+proptest! {
+  #![proptest_config(ProptestConfig::with_cases(64))]
+
+  #[test]
+  fn test_sound(spec in spec(), edits in proptest::collection::vec(proptest::collection::vec(edit(), 0..3), 0..5)) {
+    assert_sound(&spec, &edits)?;
+  }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+use proptest::prelude::*;
+
+use pie::Context;
+use pie::context::non_incremental::NonIncrementalContext;
+use pie::dependency::Dependency;
+use pie::determinism::DeterminismCheck;
+use pie::fs::{FileSystem, InMemoryFileSystem};
+use pie::stamp::FileStamper;
+use pie::tracker::event::Event;
+
+use crate::common::{test_pie, TestOutput, TestPieExt, TestTask};
+
+mod common;
+
+/// Number of input files that tasks can read, and that are edited between builds.
+const NUM_INPUT_FILES: usize = 3;
+/// Contents that are written to input files and returned by tasks.
+const STRINGS: [&str; 4] = ["", "a", "Hello", "WORLD"];
+
+/// Specification of a task that returns a string, from which a [`TestTask`] is created. We generate specifications
+/// instead of tasks, because specifications can only describe tasks without hidden dependencies and overlapping
+/// provided files.
+#[derive(Clone, Debug)]
+enum Spec {
+  Return(&'static str),
+  ReadInput(usize),
+  /// Write the output of the task to a fresh output file, then read that file, requiring the writing task directly or
+  /// through a [`TestTask::Sequence`].
+  ReadOutput(Box<Spec>, bool),
+  ToLower(Box<Spec>),
+  ToUpper(Box<Spec>),
+  Concat(Vec<Spec>),
+}
+
+impl Spec {
+  /// Creates a task from this specification. `num_outputs` is incremented for every output file, ensuring that output
+  /// files are only written by one task.
+  fn to_task(&self, num_outputs: &mut usize) -> TestTask {
+    match self {
+      Spec::Return(string) => TestTask::Return(string),
+      Spec::ReadInput(index) => TestTask::ReadFile(input_path(*index), FileStamper::Modified, None),
+      Spec::ReadOutput(spec, through_sequence) => {
+        let path = PathBuf::from(format!("out{}.txt", num_outputs));
+        *num_outputs += 1;
+        let task = Box::new(spec.to_task(num_outputs));
+        let mut origin = TestTask::WriteFile(task, path.clone(), FileStamper::Modified);
+        if *through_sequence {
+          origin = TestTask::Sequence(vec![origin]);
+        }
+        TestTask::ReadFile(path, FileStamper::Modified, Some(Box::new(origin)))
+      }
+      Spec::ToLower(spec) => TestTask::ToLower(Box::new(spec.to_task(num_outputs))),
+      Spec::ToUpper(spec) => TestTask::ToUpper(Box::new(spec.to_task(num_outputs))),
+      Spec::Concat(specs) => TestTask::Concat(specs.iter().map(|s| s.to_task(num_outputs)).collect()),
+    }
+  }
+}
+
+fn input_path(index: usize) -> PathBuf { PathBuf::from(format!("in{}.txt", index)) }
+
+fn spec() -> impl Strategy<Value=Spec> {
+  let leaf = prop_oneof![
+    proptest::sample::select(&STRINGS[..]).prop_map(Spec::Return),
+    (0..NUM_INPUT_FILES).prop_map(Spec::ReadInput),
+  ];
+  leaf.prop_recursive(4, 24, 3, |inner| prop_oneof![
+    (inner.clone(), any::<bool>()).prop_map(|(s, b)| Spec::ReadOutput(Box::new(s), b)),
+    inner.clone().prop_map(|s| Spec::ToLower(Box::new(s))),
+    inner.clone().prop_map(|s| Spec::ToUpper(Box::new(s))),
+    proptest::collection::vec(inner, 1..4).prop_map(Spec::Concat),
+  ])
+}
+
+/// Edit to an input file.
+#[derive(Clone, Debug)]
+enum Edit {
+  Write(usize, &'static str),
+  Remove(usize),
+}
+
+impl Edit {
+  fn apply(&self, file_system: &InMemoryFileSystem) -> Result<(), io::Error> {
+    match self {
+      Edit::Write(index, string) => file_system.write(input_path(*index), string)?,
+      Edit::Remove(index) => if file_system.exists(input_path(*index)) {
+        file_system.remove_file(input_path(*index))?;
+      }
+    }
+    Ok(())
+  }
+}
+
+fn edit() -> impl Strategy<Value=Edit> {
+  prop_oneof![
+    4 => (0..NUM_INPUT_FILES, proptest::sample::select(&STRINGS[..])).prop_map(|(i, s)| Edit::Write(i, s)),
+    1 => (0..NUM_INPUT_FILES).prop_map(Edit::Remove),
+  ]
+}
+
+type TaskDependencies = Vec<Dependency<TestTask, Result<TestOutput, io::ErrorKind>>>;
+
+/// Builds the task specified by `spec` incrementally after each step of `edits`, and asserts that:
+/// - the output is equal to the output of building the task from scratch with the [`NonIncrementalContext`],
+/// - no task is executed while all its dependencies are consistent.
+///
+/// The incremental build and the non-incremental build use separate in-memory file systems, such that files written by
+/// one build do not affect the other build.
+fn assert_sound(spec: &Spec, edits: &[Vec<Edit>]) -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  // Also check that all tasks are deterministic, which is required for soundness.
+  pie.set_determinism_check(DeterminismCheck::All);
+  let file_system = pie.file_system().clone();
+  let oracle_file_system = InMemoryFileSystem::default();
+  let mut oracle = NonIncrementalContext::new(oracle_file_system.clone());
+  let task = spec.to_task(&mut 0);
+
+  for index in 0..NUM_INPUT_FILES {
+    let edit = Edit::Write(index, STRINGS[index + 1]);
+    edit.apply(&file_system)?;
+    edit.apply(&oracle_file_system)?;
+  }
+
+  // Dependencies of tasks, as recorded when they were last executed.
+  let mut dependencies: HashMap<TestTask, TaskDependencies> = HashMap::new();
+  for (step, step_edits) in std::iter::once(&Vec::new()).chain(edits).enumerate() {
+    for edit in step_edits {
+      edit.apply(&file_system)?;
+      edit.apply(&oracle_file_system)?;
+    }
+
+    let mut executed: Vec<(TestTask, TaskDependencies)> = Vec::new();
+    let output = pie.require_then_assert(&task, |tracker| {
+      for event in tracker.iter() {
+        if let Event::ExecuteEnd(d) = event {
+          executed.push((d.task.clone(), d.dependencies.clone()));
+        }
+      }
+    });
+    let oracle_output = oracle.require_task(&task);
+    assert_eq!(output, oracle_output, "incremental build output differs from non-incremental build output in step {}",
+      step);
+
+    for (executed_task, new_dependencies) in executed {
+      if let Some(previous_dependencies) = dependencies.get(&executed_task) {
+        let any_inconsistent = previous_dependencies.iter().any(|d| match d {
+          Dependency::RequireFile(d) => d.is_inconsistent(&file_system).expect("failed to check file dependency").is_some(),
+          // Provided files are only changed by the tasks that provide them, so an inconsistent provide file dependency
+          // is the result of executing the task, not the reason for executing it.
+          Dependency::ProvideFile(_) => false,
+          Dependency::RequireTask(d) => d.stamper().stamp(oracle.require_task(d.task())) != *d.stamp(),
+          Dependency::ReservedRequireTask => panic!("BUG: reserved task dependency after execution"),
+        });
+        assert!(any_inconsistent, "task {:?} was executed in step {}, but all its dependencies were consistent: {:?}",
+          executed_task, step, previous_dependencies);
+      }
+      dependencies.insert(executed_task, new_dependencies);
+    }
+  }
+  Ok(())
+}
+
+proptest! {
+  #![proptest_config(ProptestConfig::with_cases(64))]
+
+  #[test]
+  fn test_sound(spec in spec(), edits in proptest::collection::vec(proptest::collection::vec(edit(), 0..3), 0..5)) {
+    assert_sound(&spec, &edits)?;
+  }
+}
@@ -0,0 +1,147 @@
+use std::cell::Cell;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use assert_matches::assert_matches;
+
+use pie::{Context, Task};
+use pie::determinism::DeterminismCheck;
+use pie::fs::FileSystem;
+use pie::stamp::FileStamper;
+use pie::tracker::event::Event;
+
+use crate::common::{test_pie, TestPie, TestPieExt, TestTask};
+
+mod common;
+
+thread_local! {
+  /// Number of times a non-deterministic task was executed on this thread. Tests run on separate threads, so they do
+  /// not influence each other.
+  static EXECUTIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Non-deterministic tasks that use the number of times they were executed, similar to a task that iterates over a
+/// `HashMap`, whose iteration order can differ between executions.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+enum NonDeterministicTask {
+  /// Returns the number of times it was executed.
+  Counter,
+  /// Requires the files in alternating order.
+  Dependencies(PathBuf, PathBuf),
+}
+
+impl Task for NonDeterministicTask {
+  type Output = Result<String, io::ErrorKind>;
+  fn execute<C: Context<Self>>(&self, context: &mut C) -> Self::Output {
+    let executions = EXECUTIONS.with(|e| e.replace(e.get() + 1));
+    match self {
+      NonDeterministicTask::Counter => Ok(executions.to_string()),
+      NonDeterministicTask::Dependencies(a, b) => {
+        let paths = match executions % 2 { 0 => [a, b], _ => [b, a] };
+        let mut strings = Vec::new();
+        for path in paths {
+          let mut string = String::new();
+          if let Some(mut file) = context.require_file(path).map_err(|e| e.kind())? {
+            file.read_to_string(&mut string).map_err(|e| e.kind())?;
+          }
+          strings.push(string);
+        }
+        // Sort to make the output deterministic, even though the order of dependencies is not.
+        strings.sort();
+        Ok(strings.concat())
+      }
+    }
+  }
+}
+
+#[test]
+fn test_deterministic_tasks() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  pie.set_determinism_check(DeterminismCheck::All);
+
+  let input_file = PathBuf::from("in.txt");
+  pie.file_system().write(&input_file, "Hello, World!")?;
+  let read = TestTask::ReadFile(input_file.clone(), FileStamper::Modified, None);
+  let write = TestTask::WriteFile(Box::new(read.clone()), PathBuf::from("out.txt"), FileStamper::Modified);
+  let task = TestTask::ToLower(Box::new(TestTask::ReadFile(PathBuf::from("out.txt"), FileStamper::Modified,
+    Some(Box::new(write.clone())))));
+
+  // `require_then_assert` asserts that no task is non-deterministic. Provided files are written again when checking
+  // determinism, but that does not make the task non-deterministic.
+  let output = pie.require_then_assert(&task, |tracker| {
+    assert_eq!(tracker.iter().filter(|e| matches!(e, Event::CheckDeterminismStart(_))).count(), 4);
+    // Tasks are still only executed once.
+    assert!(tracker.one_execute_of(&write));
+  })?;
+  assert_eq!(output.as_str(), "hello, world!");
+
+  pie.file_system().write(&input_file, "Hello, World?")?;
+  let output = pie.require(&task)?;
+  assert_eq!(output.as_str(), "hello, world?");
+
+  Ok(())
+}
+
+#[test]
+fn test_non_deterministic_output() {
+  let mut pie = test_pie();
+  pie.set_determinism_check(DeterminismCheck::All);
+
+  let mut session = pie.new_session();
+  let output = session.require(&NonDeterministicTask::Counter);
+  // The output of the second execution is used.
+  assert_eq!(output, Ok("1".to_string()));
+  let non_determinism = assert_matches!(session.non_deterministic_tasks(), [n] => n);
+  assert_eq!(non_determinism.task, NonDeterministicTask::Counter);
+  assert!(non_determinism.output_differs());
+  assert!(!non_determinism.dependencies_differ());
+
+  let non_determinism = assert_matches!(pie.tracker().0.iter().find_map(|e| match e {
+    Event::CheckDeterminismEnd(d) => Some(&d.non_determinism),
+    _ => None,
+  }), Some(Some(n)) => n);
+  assert_eq!(non_determinism.output, Ok("0".to_string()));
+  assert_eq!(non_determinism.reexecuted_output, Ok("1".to_string()));
+}
+
+#[test]
+fn test_non_deterministic_dependencies() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  pie.set_determinism_check(DeterminismCheck::All);
+  pie.file_system().write("a.txt", "a")?;
+  pie.file_system().write("b.txt", "b")?;
+
+  let task = NonDeterministicTask::Dependencies(PathBuf::from("a.txt"), PathBuf::from("b.txt"));
+  let mut session = pie.new_session();
+  // The output is deterministic, but the order of the dependencies is not.
+  assert_eq!(session.require(&task), Ok("ab".to_string()));
+  let non_determinism = assert_matches!(session.non_deterministic_tasks(), [n] => n);
+  assert!(!non_determinism.output_differs());
+  assert!(non_determinism.dependencies_differ());
+
+  Ok(())
+}
+
+#[test]
+fn test_determinism_check_off_and_sample() {
+  let mut pie = test_pie();
+  let count_checks = |pie: &TestPie<NonDeterministicTask>| pie.tracker().0.iter()
+    .filter(|e| matches!(e, Event::CheckDeterminismStart(_))).count();
+
+  // Off by default.
+  assert_eq!(pie.determinism_check(), DeterminismCheck::Off);
+  let mut session = pie.new_session();
+  assert_eq!(session.require(&NonDeterministicTask::Counter), Ok("0".to_string()));
+  assert!(session.non_deterministic_tasks().is_empty());
+  assert_eq!(count_checks(&pie), 0);
+
+  // Sampling with probability 0 checks no tasks, and sampling with probability 1 checks all tasks.
+  pie.set_determinism_check(DeterminismCheck::Sample(0.0));
+  let task = NonDeterministicTask::Dependencies(PathBuf::from("a.txt"), PathBuf::from("a.txt"));
+  assert_eq!(pie.new_session().require(&task), Ok(String::new()));
+  assert_eq!(count_checks(&pie), 0);
+  pie.set_determinism_check(DeterminismCheck::Sample(1.0));
+  let task = NonDeterministicTask::Dependencies(PathBuf::from("b.txt"), PathBuf::from("b.txt"));
+  assert_eq!(pie.new_session().require(&task), Ok(String::new()));
+  assert_eq!(count_checks(&pie), 1);
+}
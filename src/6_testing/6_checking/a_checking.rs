@@ -0,0 +1,162 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::{Context, Task};
+use crate::context::top_down::TopDownContext;
+use crate::dependency::MakeConsistent;
+use crate::fs::{FileSystem, Metadata};
+use crate::stamp::{FileStamper, OutputStamper};
+use crate::tracker::Tracker;
+
+/// Failure found by [differential checking](crate::Pie::set_differential_checking).
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DifferentialCheckFailure<T, O> {
+  /// Running `task` again resulted in `rerun_output`, which differs from its incremental `output`, even though `task`
+  /// was not executed because all its dependencies were consistent. This means that `task` is missing a dependency.
+  OutputMismatch { task: T, output: O, rerun_output: O },
+  /// Running `task` again read file at `path` through the file system of the context, without requiring or providing
+  /// it.
+  UndeclaredRead { task: T, path: PathBuf },
+}
+
+/// Context that runs a task that was not executed by a [`TopDownContext`], to cross-validate its incremental output.
+/// Required tasks are made consistent by the wrapped top-down context, so that only the task itself is run again.
+///
+/// Files are read and written through an [`OverlayFileSystem`], such that running the task does not change files, and
+/// such that reads of files before they are required or provided can be detected.
+///
+/// Only file accesses through the file system of the context go through the overlay. Tasks that access files in other
+/// ways, such as with `std::fs` or by spawning processes, bypass it: their reads are not detected as undeclared reads,
+/// and their writes change the real file system. Comparing file stamps before and after running the task does not
+/// detect such reads either, as reading a file does not change its modification time, and access times are not updated
+/// reliably by file systems. Missing dependencies of such tasks are only detected as output mismatches, once the files
+/// they read change.
+pub(crate) struct CheckingContext<'c, 'p, 's, T: Task, A, F> {
+  context: &'c mut TopDownContext<'p, 's, T, T::Output, A, F>,
+  file_system: OverlayFileSystem<'p, F>,
+}
+
+impl<'c, 'p, 's, T: Task, A: Tracker<T>, F: FileSystem> CheckingContext<'c, 'p, 's, T, A, F> {
+  pub fn new(context: &'c mut TopDownContext<'p, 's, T, T::Output, A, F>, file_system: &'p F) -> Self {
+    Self { context, file_system: OverlayFileSystem::new(file_system) }
+  }
+
+  /// Returns the paths of files that were read through the file system before they were required or provided, in the
+  /// order in which they were first read.
+  pub fn into_undeclared_reads(self) -> Vec<PathBuf> {
+    self.file_system.undeclared_reads.into_inner()
+  }
+}
+
+impl<'c, 'p, 's, T: Task, A: Tracker<T>, F: FileSystem> Context<T> for CheckingContext<'c, 'p, 's, T, A, F> {
+  type FileSystem = OverlayFileSystem<'p, F>;
+  fn file_system(&self) -> &Self::FileSystem { &self.file_system }
+
+  fn require_file_with_stamper<P: AsRef<Path>>(
+    &mut self,
+    path: P,
+    _stamper: FileStamper
+  ) -> Result<Option<OverlayFile<F::File>>, io::Error> {
+    self.file_system.declare(path.as_ref());
+    self.file_system.open_if_file(path)
+  }
+
+  fn provide_file_with_stamper<P: AsRef<Path>>(&mut self, path: P, _stamper: FileStamper) -> Result<(), io::Error> {
+    self.file_system.declare(path.as_ref());
+    Ok(())
+  }
+
+  fn require_task_with_stamper(&mut self, task: &T, _stamper: OutputStamper) -> T::Output {
+    self.context.make_task_consistent(task)
+  }
+}
+
+/// [`FileSystem`] that writes to an in-memory overlay instead of the underlying file system, and records which paths
+/// were read before they were declared.
+pub(crate) struct OverlayFileSystem<'f, F> {
+  file_system: &'f F,
+  /// Written (`Some(contents)`) and removed (`None`) files.
+  overlay: RefCell<HashMap<PathBuf, Option<Vec<u8>>>>,
+  /// Paths of required or provided files.
+  declared: HashSet<PathBuf>,
+  /// Paths that were read while they were not declared, in the order in which they were first read.
+  undeclared_reads: RefCell<Vec<PathBuf>>,
+}
+
+impl<'f, F: FileSystem> OverlayFileSystem<'f, F> {
+  fn new(file_system: &'f F) -> Self {
+    Self {
+      file_system,
+      overlay: RefCell::default(),
+      declared: HashSet::default(),
+      undeclared_reads: RefCell::default(),
+    }
+  }
+  /// Declares `path` as required or provided, so that later reads of `path` are not undeclared.
+  fn declare(&mut self, path: &Path) {
+    self.declared.insert(path.to_path_buf());
+  }
+  /// Records a read of `path`, which is undeclared if `path` was not declared yet at this point.
+  fn touch(&self, path: &Path) {
+    if self.declared.contains(path) {
+      return;
+    }
+    let mut undeclared_reads = self.undeclared_reads.borrow_mut();
+    if !undeclared_reads.iter().any(|p| p == path) {
+      undeclared_reads.push(path.to_path_buf());
+    }
+  }
+}
+
+/// File opened from an [`OverlayFileSystem`]: either from the underlying file system, or from the overlay.
+pub(crate) enum OverlayFile<F> {
+  File(F),
+  Overlay(Cursor<Vec<u8>>),
+}
+
+impl<F: Read> Read for OverlayFile<F> {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    match self {
+      OverlayFile::File(file) => file.read(buf),
+      OverlayFile::Overlay(cursor) => cursor.read(buf),
+    }
+  }
+}
+
+impl<'f, F: FileSystem> FileSystem for OverlayFileSystem<'f, F> {
+  type File = OverlayFile<F::File>;
+
+  fn metadata(&self, path: impl AsRef<Path>) -> Result<Option<Metadata>, io::Error> {
+    let path = path.as_ref();
+    self.touch(path);
+    match self.overlay.borrow().get(path) {
+      Some(Some(_)) => Ok(Some(Metadata { is_file: true, modified: SystemTime::now() })),
+      Some(None) => Ok(None),
+      None => self.file_system.metadata(path),
+    }
+  }
+
+  fn open_if_file(&self, path: impl AsRef<Path>) -> Result<Option<Self::File>, io::Error> {
+    let path = path.as_ref();
+    self.touch(path);
+    match self.overlay.borrow().get(path) {
+      Some(Some(contents)) => Ok(Some(OverlayFile::Overlay(Cursor::new(contents.clone())))),
+      Some(None) => Ok(None),
+      None => Ok(self.file_system.open_if_file(path)?.map(OverlayFile::File)),
+    }
+  }
+
+  fn write(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), io::Error> {
+    self.overlay.borrow_mut().insert(path.as_ref().to_path_buf(), Some(contents.as_ref().to_vec()));
+    Ok(())
+  }
+
+  fn remove_file(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
+    self.overlay.borrow_mut().insert(path.as_ref().to_path_buf(), None);
+    Ok(())
+  }
+}
@@ -0,0 +1,3 @@
+pub mod checking;
+pub mod non_incremental;
+pub(crate) mod top_down;
@@ -0,0 +1,144 @@
+use std::borrow::Borrow;
+use std::path::Path;
+
+use pie::fs::FileSystem;
+use pie::Task;
+use pie::tracker::event::EventTracker;
+use pie::tracker::writing::WritingTracker;
+
+use super::{test_pie, TestOutput, TestPie, TestTask};
+
+/// Creates a new [`Scenario`] with a fresh [`TestPie`].
+pub fn scenario<T: Task>() -> Scenario<T> {
+  Scenario { pie: test_pie(), step: 0, output: None }
+}
+
+/// Declarative builder for integration test scenarios. Edits files, requires tasks, and asserts what happened during the
+/// last build. When an expectation fails, panics with the build log of the last build, rendered from its events.
+///
+/// Example:
+///
+/// ```ignore
+/// scenario()
+///   .write("in.txt", "Hello")
+///   .require(&read)
+///   .expect_executed([&read])
+///   .write("in.txt", "World")
+///   .require(&read)
+///   .expect_executed([&read]);
+/// ```
+pub struct Scenario<T: Task> {
+  pie: TestPie<T>,
+  step: usize,
+  output: Option<T::Output>,
+}
+
+impl<T: Task> Scenario<T> {
+  /// Writes `contents` to the file at `path`.
+  pub fn write(self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Self {
+    let path = path.as_ref();
+    self.pie.file_system().write(path, contents)
+      .unwrap_or_else(|e| panic!("failed to write to file '{}': {}", path.display(), e));
+    self
+  }
+  /// Removes the file at `path`.
+  pub fn remove(self, path: impl AsRef<Path>) -> Self {
+    let path = path.as_ref();
+    self.pie.file_system().remove_file(path)
+      .unwrap_or_else(|e| panic!("failed to remove file '{}': {}", path.display(), e));
+    self
+  }
+
+  /// Requires `task` in a new session, asserting that there are no dependency check errors, no non-deterministic tasks,
+  /// and no differential check failures. Subsequent expectations apply to this build.
+  pub fn require(mut self, task: &T) -> Self {
+    self.step += 1;
+    let mut session = self.pie.new_session();
+    let output = session.require(task);
+    let errors = session.dependency_check_errors();
+    if !errors.is_empty() {
+      let message = format!("expected no dependency checking errors, but there are dependency checking errors: {:?}",
+        errors);
+      self.fail(message);
+    }
+    let non_deterministic_tasks = session.non_deterministic_tasks();
+    if !non_deterministic_tasks.is_empty() {
+      let message = format!("expected no non-deterministic tasks, but there are non-deterministic tasks: {:?}",
+        non_deterministic_tasks);
+      self.fail(message);
+    }
+    let failures = session.differential_check_failures();
+    if !failures.is_empty() {
+      let message = format!("expected no differential check failures, but there are differential check failures: {:?}",
+        failures);
+      self.fail(message);
+    }
+    self.output = Some(output);
+    self
+  }
+
+  /// Expects the output of the last build to equal `output`.
+  pub fn expect_output(self, output: T::Output) -> Self {
+    match &self.output {
+      Some(o) if o == &output => self,
+      o => self.fail(format!("expected output {:?}, but got {:?}", output, o)),
+    }
+  }
+  /// Expects each task in `tasks` to be executed exactly once in the last build.
+  pub fn expect_executed(self, tasks: impl IntoIterator<Item=impl Borrow<T>>) -> Self {
+    for task in tasks {
+      let task = task.borrow();
+      if !self.tracker().one_execute_of(task) {
+        self.fail(format!("expected one execution of task {:?}, but it was not executed, or was executed more than \
+        once", task));
+      }
+    }
+    self
+  }
+  /// Expects no task in `tasks` to be executed in the last build.
+  pub fn expect_not_executed(self, tasks: impl IntoIterator<Item=impl Borrow<T>>) -> Self {
+    for task in tasks {
+      let task = task.borrow();
+      if self.tracker().any_execute_of(task) {
+        self.fail(format!("expected no execution of task {:?}, but it was executed", task));
+      }
+    }
+    self
+  }
+  /// Expects `before` to finish executing before `after` finishes executing in the last build. This holds when `after`
+  /// requires `before`, even though `before` starts executing after `after` does.
+  pub fn expect_order(self, before: &T, after: &T) -> Self {
+    let before_end = self.tracker().first_execute_range(before).map(|r| *r.end());
+    let after_end = self.tracker().first_execute_range(after).map(|r| *r.end());
+    match (before_end, after_end) {
+      (Some(b), Some(a)) if b < a => self,
+      (Some(_), Some(_)) => self.fail(format!("expected task {:?} to finish executing before task {:?}, but it \
+      finished after", before, after)),
+      _ => self.fail(format!("expected tasks {:?} and {:?} to be executed, but at least one was not", before, after)),
+    }
+  }
+
+  /// Gets the output of the last build.
+  pub fn output(&self) -> Option<&T::Output> { self.output.as_ref() }
+  /// Gets the [`TestPie`] instance, for assertions that are not supported by scenarios.
+  pub fn pie(&self) -> &TestPie<T> { &self.pie }
+  /// Gets the [`EventTracker`] of the last build.
+  pub fn tracker(&self) -> &EventTracker<T, T::Output> { &self.pie.tracker().0 }
+
+  /// Renders the build log of the last build by replaying its events into a [`WritingTracker`].
+  pub fn build_log(&self) -> String {
+    let mut writing_tracker = WritingTracker::new(Vec::new());
+    self.tracker().replay(&mut writing_tracker);
+    String::from_utf8_lossy(writing_tracker.writer()).into_owned()
+  }
+  fn fail(&self, message: String) -> ! {
+    panic!("{}\n\nbuild log of step {}:\n{}", message, self.step, self.build_log());
+  }
+}
+
+impl Scenario<TestTask> {
+  /// Expects the output of the last build to be `Ok` with string `string`.
+  pub fn expect_string(self, string: &str) -> Self {
+    self.expect_output(Ok(TestOutput::String(string.to_string())))
+  }
+}
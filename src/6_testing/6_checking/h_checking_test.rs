@@ -0,0 +1,172 @@
+use std::fs::{read_to_string, write};
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use assert_matches::assert_matches;
+
+use dev_shared::{create_temp_dir, write_until_modified};
+use pie::{Context, Pie, Task};
+use pie::context::checking::DifferentialCheckFailure;
+use pie::fs::FileSystem;
+use pie::stamp::FileStamper;
+
+use crate::common::{test_pie, test_tracker, TestPieExt, TestTask};
+
+mod common;
+
+/// Tasks with missing dependencies.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+enum MissingDependencyTask {
+  /// Reads a file through the file system without requiring it.
+  ReadUndeclared(PathBuf),
+  /// Reads a file with `std::fs`, bypassing the file system of the context, without requiring it.
+  ReadStd(PathBuf),
+  /// Reads a file through the file system before requiring it.
+  RequireAfterRead(PathBuf),
+}
+
+impl Task for MissingDependencyTask {
+  type Output = Result<String, io::ErrorKind>;
+  fn execute<C: Context<Self>>(&self, context: &mut C) -> Self::Output {
+    match self {
+      MissingDependencyTask::ReadUndeclared(path) => {
+        let mut string = String::new();
+        if let Some(mut file) = context.file_system().open_if_file(path).map_err(|e| e.kind())? {
+          file.read_to_string(&mut string).map_err(|e| e.kind())?;
+        }
+        Ok(string)
+      }
+      MissingDependencyTask::ReadStd(path) => read_to_string(path).map_err(|e| e.kind()),
+      MissingDependencyTask::RequireAfterRead(path) => {
+        let mut string = String::new();
+        if let Some(mut file) = context.file_system().open_if_file(path).map_err(|e| e.kind())? {
+          file.read_to_string(&mut string).map_err(|e| e.kind())?;
+        }
+        context.require_file(path).map_err(|e| e.kind())?;
+        Ok(string)
+      }
+    }
+  }
+}
+
+#[test]
+fn test_checking_correct_tasks() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  pie.set_differential_checking(true);
+
+  let input_file = PathBuf::from("in.txt");
+  let output_file = PathBuf::from("out.txt");
+  pie.file_system().write(&input_file, "Hello, World!")?;
+  let read = TestTask::ReadFile(input_file.clone(), FileStamper::Modified, None);
+  let write = TestTask::WriteFile(Box::new(read.clone()), output_file.clone(), FileStamper::Modified);
+  let task = TestTask::ToLower(Box::new(TestTask::ReadFile(output_file.clone(), FileStamper::Modified,
+    Some(Box::new(write.clone())))));
+
+  // `require_then_assert` asserts that there are no differential check failures.
+  let output = pie.require_then_assert(&task, |tracker| assert!(tracker.one_execute_of(&write)))?;
+  assert_eq!(output.as_str(), "hello, world!");
+  let modified = pie.file_system().metadata(&output_file)?.map(|m| m.modified);
+
+  // All tasks are consistent and are checked, but the checking context does not execute them, nor write files.
+  let output = pie.require_then_assert(&task, |tracker| assert!(tracker.executed_tasks().is_empty()))?;
+  assert_eq!(output.as_str(), "hello, world!");
+  assert_eq!(pie.file_system().metadata(&output_file)?.map(|m| m.modified), modified);
+
+  Ok(())
+}
+
+#[test]
+fn test_checking_undeclared_read() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  pie.set_differential_checking(true);
+  let path = PathBuf::from("in.txt");
+  pie.file_system().write(&path, "Hello")?;
+  let task = MissingDependencyTask::ReadUndeclared(path.clone());
+
+  // Task is executed, and not checked.
+  let mut session = pie.new_session();
+  assert_eq!(session.require(&task), Ok("Hello".to_string()));
+  assert!(session.differential_check_failures().is_empty());
+
+  // Task has no dependencies, so it is not executed, but checked. It read a file that it did not require.
+  let mut session = pie.new_session();
+  assert_eq!(session.require(&task), Ok("Hello".to_string()));
+  assert_matches!(session.differential_check_failures(), [DifferentialCheckFailure::UndeclaredRead { task: t, path: p }] => {
+    assert_eq!(t, &task);
+    assert_eq!(p, &path);
+  });
+
+  // Change the file: task is still not executed, resulting in an output mismatch.
+  pie.file_system().write(&path, "World")?;
+  let mut session = pie.new_session();
+  assert_eq!(session.require(&task), Ok("Hello".to_string()));
+  assert_matches!(session.differential_check_failures(), [
+    DifferentialCheckFailure::OutputMismatch { output, rerun_output, .. },
+    DifferentialCheckFailure::UndeclaredRead { .. }
+  ] => {
+    assert_eq!(output, &Ok("Hello".to_string()));
+    assert_eq!(rerun_output, &Ok("World".to_string()));
+  });
+
+  Ok(())
+}
+
+#[test]
+fn test_checking_undeclared_std_read() -> Result<(), io::Error> {
+  let temp_dir = create_temp_dir()?;
+  let path = temp_dir.path().join("in.txt");
+  write(&path, "Hello")?;
+  let mut pie = Pie::with_tracker(test_tracker());
+  pie.set_differential_checking(true);
+  let task = MissingDependencyTask::ReadStd(path.clone());
+  assert_eq!(pie.new_session().require(&task), Ok("Hello".to_string()));
+
+  // Reads that bypass the file system of the context are not detected as undeclared reads.
+  let mut session = pie.new_session();
+  assert_eq!(session.require(&task), Ok("Hello".to_string()));
+  assert!(session.differential_check_failures().is_empty());
+
+  // Only once the file changes, the missing dependency is detected as an output mismatch.
+  write_until_modified(&path, "World")?;
+  let mut session = pie.new_session();
+  assert_eq!(session.require(&task), Ok("Hello".to_string()));
+  assert_matches!(session.differential_check_failures(), [DifferentialCheckFailure::OutputMismatch { output, rerun_output, .. }] => {
+    assert_eq!(output, &Ok("Hello".to_string()));
+    assert_eq!(rerun_output, &Ok("World".to_string()));
+  });
+
+  Ok(())
+}
+
+#[test]
+fn test_checking_read_before_require() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  pie.set_differential_checking(true);
+  let path = PathBuf::from("in.txt");
+  pie.file_system().write(&path, "Hello")?;
+  let task = MissingDependencyTask::RequireAfterRead(path.clone());
+  assert_eq!(pie.new_session().require(&task), Ok("Hello".to_string()));
+
+  // The file is required, but only after it was read, so the read is still undeclared.
+  let mut session = pie.new_session();
+  assert_eq!(session.require(&task), Ok("Hello".to_string()));
+  assert_matches!(session.differential_check_failures(), [DifferentialCheckFailure::UndeclaredRead { task: t, path: p }] => {
+    assert_eq!(t, &task);
+    assert_eq!(p, &path);
+  });
+
+  Ok(())
+}
+
+#[test]
+fn test_checking_off_by_default() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  assert!(!pie.differential_checking());
+  pie.file_system().write("in.txt", "Hello")?;
+  let task = MissingDependencyTask::ReadUndeclared(PathBuf::from("in.txt"));
+  assert_eq!(pie.require(&task), Ok("Hello".to_string()));
+  pie.file_system().write("in.txt", "World")?;
+  // Not executed and not checked, so the stale output goes unnoticed.
+  assert_eq!(pie.require(&task), Ok("Hello".to_string()));
+  Ok(())
+}
@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::fs::FileSystem;
+
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FileStamper {
+  Exists,
+  Modified,
+}
+
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FileStamp {
+  Exists(bool),
+  Modified(Option<SystemTime>),
+}
+
+impl FileStamper {
+  pub fn stamp(&self, file_system: &impl FileSystem, path: impl AsRef<Path>) -> Result<FileStamp, io::Error> {
+    match self {
+      FileStamper::Exists => {
+        Ok(FileStamp::Exists(file_system.metadata(path)?.is_some()))
+      }
+      FileStamper::Modified => {
+        let Some(metadata) = file_system.metadata(path)? else {
+          return Ok(FileStamp::Modified(None));
+        };
+        Ok(FileStamp::Modified(Some(metadata.modified)))
+      }
+    }
+  }
+}
+
+/// Cache of file stamps, keyed by path and stamper. Used within a single build of a session to stamp a file only once
+/// when it is required by many tasks, as files only change during a build when they are provided by a task.
+#[derive(Default, Debug)]
+pub(crate) struct FileStampCache {
+  stamps: HashMap<PathBuf, HashMap<FileStamper, FileStamp>>,
+}
+
+impl FileStampCache {
+  /// Stamps file at `path` with `stamper`, returning the cached stamp if the file was stamped with `stamper` before.
+  /// Errors are not cached.
+  pub fn stamp(
+    &mut self,
+    file_system: &impl FileSystem,
+    path: &Path,
+    stamper: FileStamper,
+  ) -> Result<FileStamp, io::Error> {
+    if let Some(stamp) = self.stamps.get(path).and_then(|stamps| stamps.get(&stamper)) {
+      return Ok(*stamp);
+    }
+    let stamp = stamper.stamp(file_system, path)?;
+    self.stamps.entry(path.to_path_buf()).or_default().insert(stamper, stamp);
+    Ok(stamp)
+  }
+
+  /// Invalidates all cached stamps of file at `path`. Must be called when the file at `path` may have changed.
+  pub fn invalidate(&mut self, path: &Path) {
+    self.stamps.remove(path);
+  }
+}
+
+
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutputStamper {
+  Inconsequential,
+  Equals,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutputStamp<O> {
+  Inconsequential,
+  Equals(O),
+}
+
+impl OutputStamper {
+  pub fn stamp<O>(&self, output: O) -> OutputStamp<O> {
+    match self {
+      OutputStamper::Inconsequential => OutputStamp::Inconsequential,
+      OutputStamper::Equals => OutputStamp::Equals(output),
+    }
+  }
+}
+
+
+#[cfg(test)]
+mod test {
+  use std::fs::remove_file;
+  use std::io;
+
+  use dev_shared::{create_temp_file, write_until_modified};
+
+  use crate::fs::{InMemoryFileSystem, RealFileSystem};
+
+  use super::*;
+
+  #[test]
+  fn test_exists_file_stamper() -> Result<(), io::Error> {
+    let stamper = FileStamper::Exists;
+    let temp_file = create_temp_file()?;
+    let stamp = stamper.stamp(&RealFileSystem, &temp_file)?;
+    assert_eq!(stamp, stamper.stamp(&RealFileSystem, &temp_file)?);
+
+    remove_file(&temp_file)?;
+    assert_ne!(stamp, stamper.stamp(&RealFileSystem, &temp_file)?);
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_modified_file_stamper() -> Result<(), io::Error> {
+    let stamper = FileStamper::Modified;
+    let temp_file = create_temp_file()?;
+    let stamp = stamper.stamp(&RealFileSystem, &temp_file)?;
+    assert_eq!(stamp, stamper.stamp(&RealFileSystem, &temp_file)?);
+
+    // Write until file modified time changes. Required on some OSs due to imprecise modified timer causing the modified
+    // stamp to be the same after fast consecutive writes.
+    write_until_modified(&temp_file, format!("{:?}", stamp))?;
+    let new_stamp = stamper.stamp(&RealFileSystem, &temp_file)?;
+    assert_ne!(stamp, new_stamp);
+    let stamp = new_stamp;
+
+    remove_file(&temp_file)?;
+    assert_ne!(stamp, stamper.stamp(&RealFileSystem, &temp_file)?);
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_in_memory_file_stampers() -> Result<(), io::Error> {
+    let fs = InMemoryFileSystem::default();
+    let exists_stamp = FileStamper::Exists.stamp(&fs, "in.txt")?;
+    let modified_stamp = FileStamper::Modified.stamp(&fs, "in.txt")?;
+
+    fs.write("in.txt", "Hello")?;
+    assert_ne!(exists_stamp, FileStamper::Exists.stamp(&fs, "in.txt")?);
+    assert_ne!(modified_stamp, FileStamper::Modified.stamp(&fs, "in.txt")?);
+    let exists_stamp = FileStamper::Exists.stamp(&fs, "in.txt")?;
+    let modified_stamp = FileStamper::Modified.stamp(&fs, "in.txt")?;
+
+    // No need to write until modified, as the logical clock advances on every write.
+    fs.write("in.txt", "World")?;
+    assert_eq!(exists_stamp, FileStamper::Exists.stamp(&fs, "in.txt")?);
+    assert_ne!(modified_stamp, FileStamper::Modified.stamp(&fs, "in.txt")?);
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_file_stamp_cache() -> Result<(), io::Error> {
+    let fs = InMemoryFileSystem::default();
+    let path = Path::new("in.txt");
+    fs.write(path, "Hello")?;
+    let mut cache = FileStampCache::default();
+    let exists_stamp = cache.stamp(&fs, path, FileStamper::Exists)?;
+    let modified_stamp = cache.stamp(&fs, path, FileStamper::Modified)?;
+    assert_eq!(exists_stamp, FileStamper::Exists.stamp(&fs, path)?);
+    assert_eq!(modified_stamp, FileStamper::Modified.stamp(&fs, path)?);
+
+    // Cached stamps are returned, even though the file changed.
+    fs.remove_file(path)?;
+    assert_eq!(exists_stamp, cache.stamp(&fs, path, FileStamper::Exists)?);
+    assert_eq!(modified_stamp, cache.stamp(&fs, path, FileStamper::Modified)?);
+
+    // Until they are invalidated.
+    cache.invalidate(path);
+    assert_eq!(FileStamp::Exists(false), cache.stamp(&fs, path, FileStamper::Exists)?);
+    assert_eq!(FileStamp::Modified(None), cache.stamp(&fs, path, FileStamper::Modified)?);
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_inconsequential_output_stamper() {
+    let stamper = OutputStamper::Inconsequential;
+    let stamp = stamper.stamp(&1);
+    assert_eq!(stamp, stamper.stamp(&1));
+    assert_eq!(stamp, stamper.stamp(&2));
+  }
+
+  #[test]
+  fn test_equals_output_stamper() {
+    let stamper = OutputStamper::Equals;
+    let stamp = stamper.stamp(&1);
+    assert_eq!(stamp, stamper.stamp(&1));
+    assert_ne!(stamp, stamper.stamp(&2));
+  }
+}
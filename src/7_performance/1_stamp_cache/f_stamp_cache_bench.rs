@@ -0,0 +1,100 @@
+use std::cell::Cell;
+use std::fs::write;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use dev_shared::create_temp_dir;
+use pie::{Context, Pie, Task};
+use pie::fs::{FileSystem, Metadata, RealFileSystem};
+use pie::tracker::NoopTracker;
+
+/// [`RealFileSystem`] that counts how many times file metadata is requested, which is the system call that file
+/// stampers make.
+#[derive(Default, Clone)]
+struct CountingFileSystem {
+  metadata_calls: Rc<Cell<usize>>,
+}
+
+impl FileSystem for CountingFileSystem {
+  type File = <RealFileSystem as FileSystem>::File;
+  fn metadata(&self, path: impl AsRef<Path>) -> Result<Option<Metadata>, io::Error> {
+    self.metadata_calls.set(self.metadata_calls.get() + 1);
+    RealFileSystem.metadata(path)
+  }
+  fn open_if_file(&self, path: impl AsRef<Path>) -> Result<Option<Self::File>, io::Error> {
+    RealFileSystem.open_if_file(path)
+  }
+  fn write(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), io::Error> {
+    RealFileSystem.write(path, contents)
+  }
+  fn remove_file(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
+    RealFileSystem.remove_file(path)
+  }
+}
+
+/// Wide graph of tasks: `Build` requires all `Parse` tasks, which all require the same grammar file and their own
+/// source file.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+enum Tasks {
+  Build { num_sources: usize, dir: PathBuf },
+  Parse { source: PathBuf, grammar: PathBuf },
+}
+
+impl Task for Tasks {
+  type Output = ();
+  fn execute<C: Context<Self>>(&self, context: &mut C) {
+    match self {
+      Tasks::Build { num_sources, dir } => {
+        for i in 0..*num_sources {
+          context.require_task(&Tasks::Parse { source: dir.join(format!("{}.txt", i)), grammar: dir.join("grammar.txt") });
+        }
+      }
+      Tasks::Parse { source, grammar } => {
+        context.require_file(grammar).expect("failed to require grammar");
+        context.require_file(source).expect("failed to require source");
+      }
+    }
+  }
+}
+
+const NUM_SOURCES: usize = 500;
+
+pub fn bench_stamp_cache(c: &mut Criterion) {
+  let temp_dir = create_temp_dir().unwrap();
+  let dir = temp_dir.path().to_path_buf();
+  write(dir.join("grammar.txt"), "grammar").unwrap();
+  for i in 0..NUM_SOURCES {
+    write(dir.join(format!("{}.txt", i)), i.to_string()).unwrap();
+  }
+
+  let task = Tasks::Build { num_sources: NUM_SOURCES, dir };
+  let mut group = c.benchmark_group(format!("no-op build of {} tasks sharing a file", NUM_SOURCES));
+  let mut metadata_calls = Vec::new();
+  for file_stamp_caching in [false, true] {
+    let file_system = CountingFileSystem::default();
+    let mut pie = Pie::with_tracker_and_file_system(NoopTracker, file_system.clone());
+    pie.set_file_stamp_caching(file_stamp_caching);
+    pie.new_session().require(&task);
+
+    // Without the stamp cache, a no-op build stamps every file dependency: the grammar file and source file of every
+    // `Parse` task. With the stamp cache, the grammar file is stamped once.
+    file_system.metadata_calls.set(0);
+    pie.new_session().require(&task);
+    let calls = file_system.metadata_calls.get();
+    println!("no-op build {} stamp cache checked {} file dependencies with {} metadata calls",
+      if file_stamp_caching { "with" } else { "without" }, 2 * NUM_SOURCES, calls);
+    metadata_calls.push(calls);
+
+    let name = if file_stamp_caching { "with stamp cache" } else { "without stamp cache" };
+    group.bench_function(name, |b| b.iter(|| pie.new_session().require(&task)));
+  }
+  group.finish();
+  assert!(metadata_calls[1] < metadata_calls[0], "expected the stamp cache to reduce the number of metadata calls, but \
+    it made {} metadata calls, compared to {} without the stamp cache", metadata_calls[1], metadata_calls[0]);
+}
+
+criterion_group!(benches, bench_stamp_cache);
+criterion_main!(benches);
@@ -0,0 +1,182 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use pie_graph::{DAG, Node};
+
+/// Index that speeds up reachability queries on a [`DAG`], which are used to check for hidden dependencies.
+///
+/// For each queried destination node, the index caches which nodes are known to reach it, and which nodes are known to
+/// not reach it. Many queries have the same destination node: the task that provides a file that is required by many
+/// tasks. Those queries can reuse the results of previous searches, instead of searching the graph again.
+///
+/// The index must be kept up-to-date by calling [`edge_added`](Self::edge_added) and
+/// [`outgoing_edges_removed`](Self::outgoing_edges_removed) whenever the graph changes.
+#[derive(Default, Debug)]
+pub(crate) struct ReachabilityIndex {
+  destinations: RefCell<HashMap<Node, Reachability>>,
+}
+
+/// Cached reachability of a single destination node.
+///
+/// Invariants:
+/// - every node in `reaching` reaches the destination through a path of nodes in `reaching`,
+/// - no node in `not_reaching` reaches the destination, and all descendants of a node in `not_reaching` are also in
+///   `not_reaching`.
+#[derive(Default, Debug)]
+struct Reachability {
+  reaching: HashSet<Node>,
+  not_reaching: HashSet<Node>,
+}
+
+impl ReachabilityIndex {
+  /// Checks whether there is a path of one or more edges from `src` to `dst` in `graph`.
+  pub fn contains_path<N, E>(&self, graph: &DAG<N, E>, src: Node, dst: Node) -> bool {
+    if src == dst {
+      return false;
+    }
+    // The graph keeps its nodes in topological order: every node on a path from `src` to `dst` comes before `dst`.
+    if graph.topo_cmp(src, dst) != Ordering::Less {
+      return false;
+    }
+    self.destinations.borrow_mut().entry(dst).or_default().search(graph, src, dst)
+  }
+
+  /// Updates the index after an edge from `src` to `dst` was added to the graph.
+  pub fn edge_added(&mut self, src: Node, dst: Node) {
+    for reachability in self.destinations.get_mut().values_mut() {
+      // Adding an edge does not break paths, so `reaching` stays valid. Nodes in `not_reaching` may now reach the
+      // destination through `src`, unless `dst` does not reach it either.
+      if reachability.not_reaching.contains(&src) && !reachability.not_reaching.contains(&dst) {
+        reachability.not_reaching.clear();
+      }
+    }
+  }
+
+  /// Updates the index after all outgoing edges of `src` were removed from the graph.
+  pub fn outgoing_edges_removed(&mut self, src: Node) {
+    for reachability in self.destinations.get_mut().values_mut() {
+      // Removing edges does not create paths, so `not_reaching` stays valid. Nodes in `reaching` may no longer reach
+      // the destination if their path goes through `src`.
+      if reachability.reaching.contains(&src) {
+        reachability.reaching.clear();
+      }
+    }
+  }
+}
+
+impl Reachability {
+  /// Searches for a path from `src` to `dst` in `graph` with a depth-first search, skipping nodes that are known to
+  /// not reach `dst`, and stopping at nodes that are known to reach `dst`. Caches the results of the search.
+  fn search<N, E>(&mut self, graph: &DAG<N, E>, src: Node, dst: Node) -> bool {
+    if self.reaching.contains(&src) {
+      return true;
+    }
+    if self.not_reaching.contains(&src) {
+      return false;
+    }
+
+    let mut stack = vec![(src, graph.get_outgoing_edge_nodes(src).copied().collect::<Vec<_>>())];
+    while let Some((_, children)) = stack.last_mut() {
+      if let Some(child) = children.pop() {
+        if child == dst || self.reaching.contains(&child) {
+          // All nodes on the stack reach `dst` through `child`.
+          self.reaching.extend(stack.iter().map(|(node, _)| *node));
+          return true;
+        }
+        if self.not_reaching.contains(&child) {
+          continue;
+        }
+        // Not visited yet: nodes that were visited before are in `not_reaching`, as `graph` has no cycles.
+        stack.push((child, graph.get_outgoing_edge_nodes(child).copied().collect()));
+      } else if let Some((node, _)) = stack.pop() {
+        // All descendants of `node` were searched, none reach `dst`.
+        self.not_reaching.insert(node);
+      }
+    }
+    false
+  }
+}
+
+
+#[cfg(test)]
+mod test {
+  use proptest::prelude::*;
+
+  use super::*;
+
+  #[test]
+  fn test_reachability() {
+    let mut graph = DAG::<(), ()>::default();
+    let mut index = ReachabilityIndex::default();
+    let [a, b, c, d] = [(); 4].map(|_| graph.add_node(()));
+    fn add_edge(graph: &mut DAG<(), ()>, index: &mut ReachabilityIndex, src: Node, dst: Node) {
+      graph.add_edge(src, dst, ()).unwrap();
+      index.edge_added(src, dst);
+    }
+
+    add_edge(&mut graph, &mut index, a, b);
+    add_edge(&mut graph, &mut index, b, c);
+    assert!(index.contains_path(&graph, a, c));
+    assert!(index.contains_path(&graph, b, c));
+    assert!(!index.contains_path(&graph, c, a));
+    assert!(!index.contains_path(&graph, a, a));
+    assert!(!index.contains_path(&graph, a, d));
+
+    // Cached: `a` and `b` reach `c`. Removing the edge from `b` invalidates that.
+    graph.remove_outgoing_edges_of_node(b);
+    index.outgoing_edges_removed(b);
+    assert!(!index.contains_path(&graph, a, c));
+    assert!(!index.contains_path(&graph, b, c));
+
+    // Cached: `a` and `b` do not reach `c`. Adding an edge from `b` to `d` which reaches `c` invalidates that.
+    add_edge(&mut graph, &mut index, d, c);
+    add_edge(&mut graph, &mut index, b, d);
+    assert!(index.contains_path(&graph, a, c));
+    assert!(index.contains_path(&graph, b, c));
+    assert!(index.contains_path(&graph, a, d));
+  }
+
+  /// Operation on a graph of [`NUM_NODES`] nodes.
+  #[derive(Clone, Debug)]
+  enum Operation {
+    AddEdge(usize, usize),
+    RemoveOutgoingEdges(usize),
+    Query(usize, usize),
+  }
+
+  const NUM_NODES: usize = 12;
+
+  fn operation() -> impl Strategy<Value=Operation> {
+    prop_oneof![
+      4 => (0..NUM_NODES, 0..NUM_NODES).prop_map(|(s, d)| Operation::AddEdge(s, d)),
+      1 => (0..NUM_NODES).prop_map(Operation::RemoveOutgoingEdges),
+      4 => (0..NUM_NODES, 0..NUM_NODES).prop_map(|(s, d)| Operation::Query(s, d)),
+    ]
+  }
+
+  proptest! {
+    /// Tests that the index gives the same answers as searching the graph without an index.
+    #[test]
+    fn test_reachability_matches_graph_search(operations in prop::collection::vec(operation(), 0..200)) {
+      let mut graph = DAG::<(), ()>::default();
+      let mut index = ReachabilityIndex::default();
+      let nodes: Vec<_> = (0..NUM_NODES).map(|_| graph.add_node(())).collect();
+      for operation in operations {
+        match operation {
+          Operation::AddEdge(src, dst) => if graph.add_edge(nodes[src], nodes[dst], ()).is_ok() {
+            index.edge_added(nodes[src], nodes[dst]);
+          }
+          Operation::RemoveOutgoingEdges(src) => {
+            graph.remove_outgoing_edges_of_node(nodes[src]);
+            index.outgoing_edges_removed(nodes[src]);
+          }
+          Operation::Query(src, dst) => {
+            let expected = graph.contains_transitive_edge(nodes[src], nodes[dst]);
+            prop_assert_eq!(index.contains_path(&graph, nodes[src], nodes[dst]), expected);
+          }
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,109 @@
+use std::io::Read;
+use std::path::PathBuf;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+
+use pie::{Context, Pie, Task};
+use pie::fs::{FileSystem, InMemoryFileSystem};
+use pie::tracker::NoopTracker;
+
+/// Synthetic build where many `Consumer` tasks require a file provided by `Generate`, so that every access to that file
+/// checks for hidden dependencies. Consumers require `Generate` indirectly through a `Wrapper` task, and also require a
+/// large layered graph of `Lib` tasks that does not reach `Generate`, which searches for a path to `Generate` may have
+/// to go through.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+enum Tasks {
+  Build { consumers: usize, lib: Lib },
+  Consumer { index: usize, lib: Lib },
+  Wrapper(usize),
+  Generate,
+  Lib { lib: Lib, layer: usize, index: usize },
+}
+
+/// Size of the layered graph of `Lib` tasks, where each task requires two tasks of the layer below it.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+struct Lib {
+  width: usize,
+  depth: usize,
+}
+
+impl Tasks {
+  fn input_path() -> PathBuf { PathBuf::from("in.txt") }
+  fn shared_path() -> PathBuf { PathBuf::from("shared.txt") }
+}
+
+impl Task for Tasks {
+  /// Outputs are summed with wrapping addition, as sums over the layered `Lib` graph grow exponentially with its depth.
+  type Output = usize;
+  fn execute<C: Context<Self>>(&self, context: &mut C) -> usize {
+    match self {
+      Tasks::Build { consumers, lib } => (0..*consumers)
+        .map(|index| context.require_task(&Tasks::Consumer { index, lib: *lib }))
+        .fold(0, usize::wrapping_add),
+      Tasks::Consumer { index, lib } => {
+        let mut sum = context.require_task(&Tasks::Wrapper(*index));
+        sum = sum.wrapping_add(context.require_task(&Tasks::Lib { lib: *lib, layer: lib.depth - 1, index: *index % lib.width }));
+        // Requiring the provided file checks for a hidden dependency on `Generate`.
+        if let Some(mut file) = context.require_file(Self::shared_path()).unwrap() {
+          sum = sum.wrapping_add(file.read_to_string(&mut String::new()).unwrap());
+        }
+        sum
+      }
+      Tasks::Wrapper(_) => context.require_task(&Tasks::Generate),
+      Tasks::Generate => {
+        let mut string = String::new();
+        if let Some(mut file) = context.require_file(Self::input_path()).unwrap() {
+          file.read_to_string(&mut string).unwrap();
+        }
+        context.file_system().write(Self::shared_path(), &string).unwrap();
+        // Providing the file checks for hidden dependencies from all tasks that require it.
+        context.provide_file(Self::shared_path()).unwrap();
+        string.len()
+      }
+      Tasks::Lib { lib, layer, index } => if *layer == 0 {
+        *index
+      } else {
+        let below = |index| Tasks::Lib { lib: *lib, layer: *layer - 1, index };
+        context.require_task(&below(*index)).wrapping_add(context.require_task(&below((*index + 1) % lib.width)))
+      }
+    }
+  }
+}
+
+type BenchPie = Pie<Tasks, usize, NoopTracker, InMemoryFileSystem>;
+
+fn create_pie() -> BenchPie {
+  let file_system = InMemoryFileSystem::default();
+  file_system.write(Tasks::input_path(), "Hello").unwrap();
+  Pie::with_tracker_and_file_system(NoopTracker, file_system)
+}
+
+pub fn bench_hidden_dependency(c: &mut Criterion) {
+  let mut group = c.benchmark_group("hidden dependency checks");
+  group.sample_size(10);
+  for consumers in [2500, 5000] {
+    let lib = Lib { width: 100, depth: 50 };
+    let task = Tasks::Build { consumers, lib };
+    let num_tasks = 2 * consumers + lib.width * lib.depth + 2;
+
+    // Build from scratch: every consumer requires the provided file once.
+    group.bench_with_input(BenchmarkId::new("build from scratch", num_tasks), &task, |b, task| {
+      b.iter_batched(create_pie, |mut pie| pie.new_session().require(task), BatchSize::PerIteration)
+    });
+
+    // Rebuild after changing the input: `Generate` provides the file again, checking all consumers that require it,
+    // then all consumers are executed again, requiring the provided file again.
+    group.bench_with_input(BenchmarkId::new("rebuild after change", num_tasks), &task, |b, task| {
+      b.iter_batched(|| {
+        let mut pie = create_pie();
+        pie.new_session().require(task);
+        pie.file_system().write(Tasks::input_path(), "Hello, World!").unwrap();
+        pie
+      }, |mut pie| pie.new_session().require(task), BatchSize::PerIteration)
+    });
+  }
+  group.finish();
+}
+
+criterion_group!(benches, bench_hidden_dependency);
+criterion_main!(benches);
@@ -0,0 +1,66 @@
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+
+use pie::{Context, Pie, Task};
+use pie::fs::InMemoryFileSystem;
+use pie::tracker::NoopTracker;
+
+/// Tasks with large keys and large outputs: `Build` requires `Wrap` chains of tasks, where each `Wrap` task boxes the
+/// task it requires, similar to `Tasks::Parse` in the parser development example, and returns its large output.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+enum Tasks {
+  Build { chains: usize, length: usize },
+  Wrap(Box<Tasks>),
+  Load(usize),
+}
+
+/// Size of the outputs of `Load` tasks.
+const OUTPUT_SIZE: usize = 64 * 1024;
+
+impl Task for Tasks {
+  type Output = Vec<u8>;
+  fn execute<C: Context<Self>>(&self, context: &mut C) -> Vec<u8> {
+    match self {
+      Tasks::Build { chains, length } => {
+        let mut sum = 0;
+        for chain in 0..*chains {
+          let mut task = Tasks::Load(chain);
+          for _ in 0..*length {
+            task = Tasks::Wrap(Box::new(task));
+          }
+          // Shares the output with the context instead of cloning it.
+          sum += context.require_task_shared(&task).len();
+        }
+        sum.to_le_bytes().to_vec()
+      }
+      Tasks::Wrap(task) => context.require_task(task),
+      Tasks::Load(index) => vec![*index as u8; OUTPUT_SIZE],
+    }
+  }
+}
+
+type BenchPie = Pie<Tasks, Vec<u8>, NoopTracker, InMemoryFileSystem>;
+
+fn create_pie() -> BenchPie {
+  Pie::with_tracker_and_file_system(NoopTracker, InMemoryFileSystem::default())
+}
+
+pub fn bench_shared_output(c: &mut Criterion) {
+  let mut group = c.benchmark_group("large tasks and outputs");
+  let task = Tasks::Build { chains: 50, length: 20 };
+
+  group.bench_function("build from scratch", |b| {
+    b.iter_batched(create_pie, |mut pie| pie.new_session().require(&task), BatchSize::PerIteration)
+  });
+
+  // No-op rebuild: no task is executed, but all task dependencies are checked, which gets their outputs.
+  let mut pie = create_pie();
+  pie.new_session().require(&task);
+  group.bench_function("no-op rebuild", |b| {
+    b.iter(|| pie.new_session().require(&task))
+  });
+
+  group.finish();
+}
+
+criterion_group!(benches, bench_shared_output);
+criterion_main!(benches);
@@ -0,0 +1,101 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+/// Tracks the size of stored outputs and the order in which they were last used, to find the least recently used
+/// output to evict when the total size exceeds a memory budget.
+#[derive(Debug)]
+pub(crate) struct OutputLru<K> {
+  entries: HashMap<K, Entry>,
+  order: BTreeMap<u64, K>,
+  next_tick: u64,
+  total_size: usize,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Entry {
+  size: usize,
+  tick: u64,
+}
+
+impl<K> Default for OutputLru<K> {
+  fn default() -> Self {
+    Self { entries: HashMap::default(), order: BTreeMap::default(), next_tick: 0, total_size: 0 }
+  }
+}
+
+impl<K: Copy + Eq + Hash> OutputLru<K> {
+  /// Inserts output of `key` with `size`, or replaces it if it was already inserted, making it the most recently used.
+  pub fn insert(&mut self, key: K, size: usize) {
+    self.remove(key);
+    let tick = self.tick();
+    self.entries.insert(key, Entry { size, tick });
+    self.order.insert(tick, key);
+    self.total_size += size;
+  }
+  /// Makes output of `key` the most recently used, if it was inserted.
+  pub fn touch(&mut self, key: K) {
+    let tick = self.tick();
+    if let Some(entry) = self.entries.get_mut(&key) {
+      self.order.remove(&entry.tick);
+      entry.tick = tick;
+      self.order.insert(tick, key);
+    }
+  }
+  /// Removes output of `key`, if it was inserted.
+  pub fn remove(&mut self, key: K) {
+    if let Some(entry) = self.entries.remove(&key) {
+      self.order.remove(&entry.tick);
+      self.total_size -= entry.size;
+    }
+  }
+
+  /// Gets the total size of all inserted outputs.
+  pub fn total_size(&self) -> usize { self.total_size }
+  /// Gets the key of the least recently used output, if any.
+  pub fn least_recently_used(&self) -> Option<K> {
+    self.order.values().next().copied()
+  }
+
+  fn tick(&mut self) -> u64 {
+    let tick = self.next_tick;
+    self.next_tick += 1;
+    tick
+  }
+}
+
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_output_lru() {
+    let mut lru = OutputLru::default();
+    assert_eq!(lru.total_size(), 0);
+    assert_eq!(lru.least_recently_used(), None);
+
+    lru.insert('a', 10);
+    lru.insert('b', 20);
+    lru.insert('c', 30);
+    assert_eq!(lru.total_size(), 60);
+    assert_eq!(lru.least_recently_used(), Some('a'));
+
+    lru.touch('a');
+    assert_eq!(lru.least_recently_used(), Some('b'));
+
+    // Replacing updates the size and makes it the most recently used.
+    lru.insert('b', 5);
+    assert_eq!(lru.total_size(), 45);
+    assert_eq!(lru.least_recently_used(), Some('c'));
+
+    lru.remove('c');
+    assert_eq!(lru.total_size(), 15);
+    assert_eq!(lru.least_recently_used(), Some('a'));
+    lru.remove('c');
+    assert_eq!(lru.total_size(), 15);
+
+    // Touching a removed key does nothing.
+    lru.touch('c');
+    assert_eq!(lru.least_recently_used(), Some('a'));
+  }
+}
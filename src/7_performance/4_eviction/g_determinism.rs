@@ -0,0 +1,86 @@
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+use crate::dependency::Dependency;
+use crate::stamp::OutputStamp;
+use crate::Task;
+
+/// Mode for checking whether tasks are deterministic, by executing tasks a second time right after executing them, and
+/// comparing the outputs and dependencies of both executions.
+///
+/// Incremental builds are only sound if tasks are deterministic: executing a task with the same dependencies must
+/// produce the same output and the same dependencies. Checking determinism is expensive, so it is turned off by default.
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub enum DeterminismCheck {
+  /// Do not check determinism.
+  #[default]
+  Off,
+  /// Check determinism of every executed task.
+  All,
+  /// Check determinism of a random sample of executed tasks, where each executed task is checked with given probability
+  /// between `0.0` and `1.0`. A new sample is taken every session.
+  Sample(f64),
+}
+
+impl DeterminismCheck {
+  /// Returns `true` if `task` should be checked for determinism, using `random_state` for sampling.
+  pub(crate) fn should_check<T: Task>(&self, task: &T, random_state: &RandomState) -> bool {
+    match self {
+      DeterminismCheck::Off => false,
+      DeterminismCheck::All => true,
+      DeterminismCheck::Sample(probability) => {
+        let hash = random_state.hash_one(task);
+        (hash as f64 / u64::MAX as f64) < *probability
+      }
+    }
+  }
+}
+
+/// Non-determinism of a task: executing the task a second time resulted in a different output or different dependencies
+/// than executing it the first time.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NonDeterminism<T, O> {
+  pub task: T,
+  pub output: O,
+  pub reexecuted_output: O,
+  pub dependencies: Vec<Dependency<T, O>>,
+  pub reexecuted_dependencies: Vec<Dependency<T, O>>,
+}
+
+impl<T: Task> NonDeterminism<T, T::Output> {
+  /// Compares the `output` and `dependencies` of executing `task`, with the `reexecuted_output` and
+  /// `reexecuted_dependencies` of executing it a second time, returning `Some(non_determinism)` if they differ, or
+  /// `None` if they are the same.
+  ///
+  /// Provide file dependencies are compared by their path and stamper only, as re-executing a task writes provided
+  /// files again, which changes their modified stamp. Task dependencies with an [evicted stamp](OutputStamp::Evicted)
+  /// are compared by their task and stamper only, as outputs may be evicted while executing a task, replacing the
+  /// stamps of dependencies to it.
+  pub fn compare(
+    task: &T,
+    output: T::Output,
+    dependencies: Vec<Dependency<T, T::Output>>,
+    reexecuted_output: T::Output,
+    reexecuted_dependencies: Vec<Dependency<T, T::Output>>,
+  ) -> Option<Self> {
+    let non_determinism = Self { task: task.clone(), output, reexecuted_output, dependencies, reexecuted_dependencies };
+    (non_determinism.output_differs() || non_determinism.dependencies_differ()).then_some(non_determinism)
+  }
+
+  /// Returns `true` if the output of the second execution differs from the output of the first execution.
+  pub fn output_differs(&self) -> bool {
+    self.output != self.reexecuted_output
+  }
+  /// Returns `true` if the dependencies of the second execution differ from the dependencies of the first execution.
+  pub fn dependencies_differ(&self) -> bool {
+    self.dependencies.len() != self.reexecuted_dependencies.len() ||
+      self.dependencies.iter().zip(&self.reexecuted_dependencies).any(|(d1, d2)| match (d1, d2) {
+        (Dependency::ProvideFile(d1), Dependency::ProvideFile(d2)) => d1.path() != d2.path() || d1.stamper() != d2.stamper(),
+        (Dependency::RequireTask(d1), Dependency::RequireTask(d2))
+        if matches!(d1.stamp(), OutputStamp::Evicted(_)) || matches!(d2.stamp(), OutputStamp::Evicted(_)) =>
+          d1.task() != d2.task() || d1.stamper() != d2.stamper(),
+        (d1, d2) => d1 != d2,
+      })
+  }
+}
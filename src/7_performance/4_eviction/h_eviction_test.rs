@@ -0,0 +1,89 @@
+use std::io;
+use std::path::PathBuf;
+
+use pie::Task;
+use pie::fs::FileSystem;
+use pie::stamp::FileStamper;
+
+use crate::common::{test_pie, TestOutput, TestPieExt, TestTask};
+
+mod common;
+
+#[test]
+fn test_no_eviction_by_default() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  assert_eq!(pie.output_memory_budget(), None);
+  pie.file_system().write("in.txt", "HELLO")?;
+  let read = TestTask::ReadFile(PathBuf::from("in.txt"), FileStamper::Modified, None);
+  let task = TestTask::ToLower(Box::new(read.clone()));
+  pie.require(&task)?;
+  // All outputs are kept: requiring any task does not execute it.
+  pie.require_then_assert(&read, |tracker| assert!(tracker.executed_tasks().is_empty()))?;
+  pie.require_then_assert(&task, |tracker| assert!(tracker.executed_tasks().is_empty()))?;
+  Ok(())
+}
+
+#[test]
+fn test_eviction_recomputes_on_demand() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  // Budget for a single output: setting an output evicts all other outputs.
+  pie.set_output_memory_budget(Some(TestTask::output_size(&Ok(TestOutput::Unit))));
+  pie.file_system().write("in.txt", "HELLO")?;
+  let read = TestTask::ReadFile(PathBuf::from("in.txt"), FileStamper::Modified, None);
+  let task = TestTask::ToLower(Box::new(read.clone()));
+
+  // Both tasks are executed, then the output of `read` is evicted when the output of `task` is set.
+  let output = pie.require_then_assert(&task, |tracker| {
+    assert!(tracker.one_execute_of(&read));
+    assert!(tracker.one_execute_of(&task));
+  })?;
+  assert_eq!(output.as_str(), "hello");
+
+  // The output of `read` is evicted, but `task` does not need it to check its dependency on `read`, as `read` is
+  // consistent. So no task is executed.
+  let output = pie.require_then_assert(&task, |tracker| assert!(tracker.executed_tasks().is_empty()))?;
+  assert_eq!(output.as_str(), "hello");
+
+  // Requiring `read` needs its evicted output, so it is executed to recompute it. That evicts the output of `task`.
+  let output = pie.require_then_assert_one_execute(&read)?;
+  assert_eq!(output.as_str(), "HELLO");
+  // Requiring `task` needs its evicted output, so it is executed to recompute it, but `read` is not executed.
+  let output = pie.require_then_assert(&task, |tracker| {
+    assert!(!tracker.any_execute_of(&read));
+    assert!(tracker.one_execute_of(&task));
+  })?;
+  assert_eq!(output.as_str(), "hello");
+
+  // Changing the file makes `read` inconsistent, which makes `task` inconsistent, even though the stamp of its
+  // dependency on `read` no longer contains the output of `read`.
+  pie.file_system().write("in.txt", "WORLD")?;
+  let output = pie.require_then_assert(&task, |tracker| {
+    assert!(tracker.one_execute_of(&read));
+    assert!(tracker.one_execute_of(&task));
+  })?;
+  assert_eq!(output.as_str(), "world");
+
+  Ok(())
+}
+
+#[test]
+fn test_eviction_keeps_recently_used_outputs() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let a = TestTask::Return("a");
+  let b = TestTask::Return("b");
+  pie.require(&a)?;
+  pie.require(&b)?;
+
+  // Budget for a single output: immediately evicts the least recently used output, which is the output of `a`.
+  pie.set_output_memory_budget(Some(TestTask::output_size(&Ok(TestOutput::Unit))));
+  pie.require_then_assert_no_execute(&b)?;
+  pie.require_then_assert_one_execute(&a)?;
+
+  // Removing the budget keeps all outputs again.
+  pie.set_output_memory_budget(None);
+  pie.require_then_assert_one_execute(&b)?;
+  pie.require_then_assert_no_execute(&a)?;
+  pie.require_then_assert_no_execute(&b)?;
+
+  Ok(())
+}
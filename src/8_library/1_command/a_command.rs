@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::{Context, Task};
+use crate::fs::FileSystem;
+use crate::stamp::FileStamper;
+
+/// Reusable task that runs an external command, such as a compiler, with declared input and output files.
+///
+/// PIE builds with a single task type, so this task is generic over that task type `T`, and is embedded in it as a
+/// variant. Executing a command task with [`execute`](Self::execute) returns a [`CommandOutput`], which the embedding
+/// task type converts into its output. For example:
+///
+/// ```
+/// # use pie::{Context, Task};
+/// # use pie::command::{CommandError, CommandOutput, CommandTask};
+/// #[derive(Clone, Eq, PartialEq, Hash, Debug)]
+/// enum Tasks {
+///   Command(CommandTask<Tasks>),
+///   // Other tasks...
+/// }
+/// impl Task for Tasks {
+///   type Output = Result<CommandOutput, CommandError>;
+///   fn execute<C: Context<Self>>(&self, context: &mut C) -> Self::Output {
+///     match self {
+///       Tasks::Command(task) => task.execute(context),
+///     }
+///   }
+/// }
+/// ```
+///
+/// Before spawning the command, the task requires its origin tasks, which are the tasks that provide its inputs, and
+/// then requires its input files. The outputs of origin tasks are ignored. After the command exits, the task provides
+/// its output files. Therefore, a chain of tasks where each command has the tasks that provide its inputs as origins,
+/// is free of hidden dependencies, whether those tasks are commands or other tasks.
+///
+/// Relative input and output paths are resolved against the working directory of the command if it is set, such that
+/// PIE and the command agree on the files that are read and written. Commands read and write files on the real file
+/// system, even when [differential checking](crate::Pie::set_differential_checking) runs them again, so this task
+/// should only be used with the [real file system](crate::fs::RealFileSystem).
+///
+/// Only the declared input files are dependencies of this task. Changes to the program itself, or to environment
+/// variables that are inherited from the current process, do not cause the command to be executed again.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommandTask<T> {
+  program: String,
+  args: Vec<String>,
+  env: BTreeMap<String, String>,
+  working_dir: Option<PathBuf>,
+  origins: Vec<T>,
+  inputs: Vec<(PathBuf, FileStamper)>,
+  outputs: Vec<(PathBuf, FileStamper)>,
+  require_outputs: bool,
+}
+
+/// Output of a [`CommandTask`] whose command was run: its exit code and captured standard output and error.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommandOutput {
+  /// Exit code of the command, or `None` if it was terminated by a signal.
+  pub exit_code: Option<i32>,
+  pub stdout: Vec<u8>,
+  pub stderr: Vec<u8>,
+}
+
+/// Error of a [`CommandTask`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CommandError {
+  /// Requiring an input file, spawning the command, or providing an output file failed with given error message.
+  Io(String),
+  /// The command exited successfully, but did not create declared output file at given path.
+  MissingOutput(PathBuf),
+}
+
+impl<T> CommandTask<T> {
+  /// Creates a new command task that runs `program` without arguments, inputs, or outputs.
+  pub fn new(program: impl Into<String>) -> Self {
+    Self {
+      program: program.into(),
+      args: Vec::new(),
+      env: BTreeMap::new(),
+      working_dir: None,
+      origins: Vec::new(),
+      inputs: Vec::new(),
+      outputs: Vec::new(),
+      require_outputs: false,
+    }
+  }
+
+  /// Adds `arg` to the arguments of the command.
+  pub fn arg(mut self, arg: impl Into<String>) -> Self {
+    self.args.push(arg.into());
+    self
+  }
+  /// Adds `args` to the arguments of the command.
+  pub fn args(mut self, args: impl IntoIterator<Item=impl Into<String>>) -> Self {
+    self.args.extend(args.into_iter().map(Into::into));
+    self
+  }
+  /// Sets environment variable `key` to `value` for the command, in addition to the environment of the current process.
+  pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+    self.env.insert(key.into(), value.into());
+    self
+  }
+  /// Sets the working directory of the command to `working_dir`.
+  pub fn working_dir(mut self, working_dir: impl Into<PathBuf>) -> Self {
+    self.working_dir = Some(working_dir.into());
+    self
+  }
+  /// Adds `origin` as an origin task of the command, which is required before requiring input files. Add the tasks that
+  /// provide input files of this command as origins, to prevent hidden dependencies.
+  pub fn origin(mut self, origin: T) -> Self {
+    self.origins.push(origin);
+    self
+  }
+  /// Adds input file at `path`, required with the [modified stamper](FileStamper::Modified).
+  pub fn input(self, path: impl Into<PathBuf>) -> Self {
+    self.input_with_stamper(path, FileStamper::Modified)
+  }
+  /// Adds input file at `path`, required with `stamper`.
+  pub fn input_with_stamper(mut self, path: impl Into<PathBuf>, stamper: FileStamper) -> Self {
+    self.inputs.push((path.into(), stamper));
+    self
+  }
+  /// Adds output file at `path`, provided with the [modified stamper](FileStamper::Modified).
+  pub fn output(self, path: impl Into<PathBuf>) -> Self {
+    self.output_with_stamper(path, FileStamper::Modified)
+  }
+  /// Adds output file at `path`, provided with `stamper`.
+  pub fn output_with_stamper(mut self, path: impl Into<PathBuf>, stamper: FileStamper) -> Self {
+    self.outputs.push((path.into(), stamper));
+    self
+  }
+  /// Sets whether to fail with [`CommandError::MissingOutput`] when the command exits successfully, but did not create
+  /// all its declared output files.
+  pub fn require_outputs(mut self, require_outputs: bool) -> Self {
+    self.require_outputs = require_outputs;
+    self
+  }
+
+  /// Resolves `path` against the working directory if it is set and `path` is relative.
+  fn resolve(&self, path: &Path) -> PathBuf {
+    match &self.working_dir {
+      Some(working_dir) => working_dir.join(path),
+      None => path.to_path_buf(),
+    }
+  }
+}
+
+impl CommandOutput {
+  /// Returns `true` if the command exited with exit code 0.
+  pub fn success(&self) -> bool { self.exit_code == Some(0) }
+}
+
+impl From<io::Error> for CommandError {
+  fn from(value: io::Error) -> Self { Self::Io(value.to_string()) }
+}
+
+impl<T: Task> CommandTask<T> {
+  /// Executes this command task in `context`.
+  pub fn execute<C: Context<T>>(&self, context: &mut C) -> Result<CommandOutput, CommandError> {
+    for origin in &self.origins {
+      context.require_task(origin);
+    }
+    for (path, stamper) in &self.inputs {
+      context.require_file_with_stamper(self.resolve(path), *stamper)?;
+    }
+
+    let mut command = Command::new(&self.program);
+    command.args(&self.args).envs(&self.env).stdin(Stdio::null());
+    if let Some(working_dir) = &self.working_dir {
+      command.current_dir(working_dir);
+    }
+    let output = command.output()?;
+
+    // Provide output files even if the command failed, as it may have written to them.
+    for (path, stamper) in &self.outputs {
+      context.provide_file_with_stamper(self.resolve(path), *stamper)?;
+    }
+    if self.require_outputs && output.status.success() {
+      for (path, _) in &self.outputs {
+        let metadata = context.file_system().metadata(self.resolve(path))?;
+        if !metadata.is_some_and(|m| m.is_file) {
+          return Err(CommandError::MissingOutput(path.clone()));
+        }
+      }
+    }
+
+    Ok(CommandOutput { exit_code: output.status.code(), stdout: output.stdout, stderr: output.stderr })
+  }
+}
@@ -0,0 +1,166 @@
+#![cfg(unix)]
+
+use std::fs::{read_to_string, write};
+use std::io;
+use std::path::PathBuf;
+
+use assert_matches::assert_matches;
+
+use dev_shared::{create_temp_dir, write_until_modified};
+use pie::{Context, Pie, Task};
+use pie::command::{CommandError, CommandOutput, CommandTask};
+use pie::fs::FileSystem;
+
+use crate::common::test_tracker;
+
+mod common;
+
+/// Tasks of a build that runs commands, along with other tasks.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+enum Tasks {
+  Command(CommandTask<Tasks>),
+  /// Writes string to file at path, and provides that file.
+  WriteFile(PathBuf, &'static str),
+}
+
+impl Task for Tasks {
+  /// Output of a command, or `None` for tasks that are not commands.
+  type Output = Result<Option<CommandOutput>, CommandError>;
+  fn execute<C: Context<Self>>(&self, context: &mut C) -> Self::Output {
+    match self {
+      Tasks::Command(task) => task.execute(context).map(Some),
+      Tasks::WriteFile(path, string) => {
+        context.file_system().write(path, string)?;
+        context.provide_file(path)?;
+        Ok(None)
+      }
+    }
+  }
+}
+
+impl From<CommandTask<Tasks>> for Tasks {
+  fn from(value: CommandTask<Tasks>) -> Self { Self::Command(value) }
+}
+
+/// Creates a command task that runs `script` with `sh`.
+fn sh(script: &str) -> CommandTask<Tasks> {
+  CommandTask::new("sh").arg("-c").arg(script)
+}
+
+#[test]
+fn test_command_output() {
+  let mut pie = Pie::with_tracker(test_tracker());
+
+  let output = pie.new_session().require(&Tasks::Command(sh("echo Hello; echo World >&2; exit 3"))).unwrap().unwrap();
+  assert_eq!(output.exit_code, Some(3));
+  assert!(!output.success());
+  assert_eq!(output.stdout, b"Hello\n");
+  assert_eq!(output.stderr, b"World\n");
+
+  let output = pie.new_session().require(&Tasks::Command(sh("echo $GREETING").env("GREETING", "Hi")))
+    .unwrap().unwrap();
+  assert!(output.success());
+  assert_eq!(output.stdout, b"Hi\n");
+
+  let output = pie.new_session().require(&Tasks::Command(CommandTask::new("non-existent-program")));
+  assert_matches!(output, Err(CommandError::Io(_)));
+}
+
+#[test]
+fn test_command_chain() -> Result<(), io::Error> {
+  let temp_dir = create_temp_dir()?;
+  let dir = temp_dir.path();
+  write(dir.join("in.txt"), "hello")?;
+  let upper: Tasks = sh("tr a-z A-Z < in.txt > upper.txt")
+    .working_dir(dir)
+    .input("in.txt")
+    .output("upper.txt")
+    .into();
+  let twice: Tasks = sh("cat upper.txt upper.txt > twice.txt")
+    .working_dir(dir)
+    .origin(upper.clone())
+    .input("upper.txt")
+    .output("twice.txt")
+    .into();
+
+  let mut pie = Pie::with_tracker(test_tracker());
+  pie.new_session().require(&twice).unwrap();
+  assert_eq!(read_to_string(dir.join("twice.txt"))?, "HELLOHELLO");
+  let tracker = &pie.tracker().0;
+  assert!(tracker.one_execute_of(&upper));
+  assert!(tracker.one_execute_of(&twice));
+
+  // No changes: no commands are run.
+  pie.new_session().require(&twice).unwrap();
+  assert!(pie.tracker().0.executed_tasks().is_empty());
+
+  // Change the input: both commands are run again.
+  write_until_modified(dir.join("in.txt"), "world")?;
+  pie.new_session().require(&twice).unwrap();
+  assert_eq!(read_to_string(dir.join("twice.txt"))?, "WORLDWORLD");
+  let tracker = &pie.tracker().0;
+  assert!(tracker.one_execute_of(&upper));
+  assert!(tracker.one_execute_of(&twice));
+
+  Ok(())
+}
+
+#[test]
+fn test_command_with_task_origin() -> Result<(), io::Error> {
+  let temp_dir = create_temp_dir()?;
+  let dir = temp_dir.path();
+  // A command that reads a file provided by a task of the user, which is not a command.
+  let write = Tasks::WriteFile(dir.join("in.txt"), "hello");
+  let upper: Tasks = sh("tr a-z A-Z < in.txt")
+    .working_dir(dir)
+    .origin(write.clone())
+    .input("in.txt")
+    .into();
+
+  let mut pie = Pie::with_tracker(test_tracker());
+  let output = pie.new_session().require(&upper).unwrap().unwrap();
+  assert_eq!(output.stdout, b"HELLO");
+  let tracker = &pie.tracker().0;
+  assert!(tracker.one_execute_of(&write));
+  assert!(tracker.one_execute_of(&upper));
+
+  // No changes: nothing is executed.
+  pie.new_session().require(&upper).unwrap();
+  assert!(pie.tracker().0.executed_tasks().is_empty());
+
+  Ok(())
+}
+
+#[test]
+fn test_command_require_outputs() -> Result<(), io::Error> {
+  let temp_dir = create_temp_dir()?;
+  let mut pie = Pie::with_tracker(test_tracker());
+
+  // Declared output is not created, which is only an error when outputs are required.
+  let command = Tasks::Command(sh("true").working_dir(temp_dir.path()).output("a.txt"));
+  assert!(pie.new_session().require(&command).is_ok());
+  let command = Tasks::Command(sh("true").working_dir(temp_dir.path()).output("b.txt").require_outputs(true));
+  let output = pie.new_session().require(&command);
+  assert_matches!(output, Err(CommandError::MissingOutput(path)) => assert_eq!(path.as_os_str(), "b.txt"));
+
+  // Outputs are not required to exist when the command fails.
+  let command = Tasks::Command(sh("exit 1").working_dir(temp_dir.path()).output("c.txt").require_outputs(true));
+  assert_matches!(pie.new_session().require(&command), Ok(Some(output)) => assert_eq!(output.exit_code, Some(1)));
+
+  Ok(())
+}
+
+#[test]
+#[should_panic(expected = "Hidden dependency")]
+fn test_command_without_origin_panics() {
+  let temp_dir = create_temp_dir().unwrap();
+  let dir = temp_dir.path();
+  let provide = Tasks::WriteFile(dir.join("out.txt"), "Hello");
+  let require = sh("cat out.txt").working_dir(dir).input("out.txt").into();
+
+  let mut pie = Pie::with_tracker(test_tracker());
+  let mut session = pie.new_session();
+  session.require(&provide).unwrap();
+  // Requires the file provided by `provide` without having it as an origin.
+  session.require(&require).unwrap();
+}
@@ -0,0 +1,346 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+
+/// File system that tasks read from and write to, and that file stampers stamp files with.
+pub trait FileSystem {
+  /// Type of opened files, which can be read from.
+  type File: Read;
+
+  /// Gets the metadata for given `path`, returning:
+  /// - `Ok(Some(metadata))` if a file or directory exists at given path,
+  /// - `Ok(None)` if no file or directory exists at given path,
+  /// - `Err(e)` if there was an error getting the metadata for given path.
+  fn metadata(&self, path: impl AsRef<Path>) -> Result<Option<Metadata>, io::Error>;
+
+  /// Attempt to open file at given `path`, returning:
+  /// - `Ok(Some(file))` if the file exists at given path,
+  /// - `Ok(None)` if no file exists at given path (but a directory could exist at given path),
+  /// - `Err(e)` if there was an error getting the metadata for given path, or if there was an error opening the file.
+  fn open_if_file(&self, path: impl AsRef<Path>) -> Result<Option<Self::File>, io::Error>;
+
+  /// Writes `contents` to the file at given `path`, creating the file if it does not exist, and replacing its contents
+  /// if it does.
+  fn write(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), io::Error>;
+
+  /// Removes the file at given `path`.
+  fn remove_file(&self, path: impl AsRef<Path>) -> Result<(), io::Error>;
+
+  /// Reads the directory at given `path`, returning the paths of its entries in no particular order. Returns an error if
+  /// no directory exists at given `path`.
+  fn read_dir(&self, path: impl AsRef<Path>) -> Result<Vec<PathBuf>, io::Error>;
+}
+
+/// Metadata of a file or directory.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Metadata {
+  /// Whether this is the metadata of a file. If not, it is the metadata of a directory.
+  pub is_file: bool,
+  /// Last modification time.
+  pub modified: SystemTime,
+}
+
+
+/// [`FileSystem`] implementation that uses the file system of the operating system, through [`std::fs`].
+#[derive(Copy, Clone, Default, Debug)]
+pub struct RealFileSystem;
+
+impl FileSystem for RealFileSystem {
+  type File = File;
+
+  fn metadata(&self, path: impl AsRef<Path>) -> Result<Option<Metadata>, io::Error> {
+    match fs::metadata(path) {
+      Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+      Err(e) => Err(e),
+      Ok(m) => Ok(Some(Metadata { is_file: m.is_file(), modified: m.modified()? }))
+    }
+  }
+
+  /// This function is necessary due to Windows returning an error when attempting to open a directory.
+  fn open_if_file(&self, path: impl AsRef<Path>) -> Result<Option<File>, io::Error> {
+    let file = match self.metadata(&path)? {
+      Some(metadata) if metadata.is_file => Some(File::open(&path)?),
+      _ => None,
+    };
+    Ok(file)
+  }
+
+  fn write(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), io::Error> {
+    fs::write(path, contents)
+  }
+
+  fn remove_file(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
+    fs::remove_file(path)
+  }
+
+  fn read_dir(&self, path: impl AsRef<Path>) -> Result<Vec<PathBuf>, io::Error> {
+    fs::read_dir(path)?.map(|entry| Ok(entry?.path())).collect()
+  }
+}
+
+
+/// [`FileSystem`] implementation that stores files in memory, with a logical clock for modification times. Every
+/// write sets the modification time of the file to the current time of the logical clock, and then advances the clock.
+/// Therefore, consecutive writes always result in different modification times, making tests that depend on
+/// modification times deterministic. Like on most real file systems, creating or removing an entry in a directory also
+/// sets the modification time of the directory.
+///
+/// Cloning an in-memory file system creates a new handle to the same files.
+#[derive(Clone, Default, Debug)]
+pub struct InMemoryFileSystem {
+  state: Rc<RefCell<InMemoryState>>,
+}
+
+#[derive(Default, Debug)]
+struct InMemoryState {
+  entries: HashMap<PathBuf, InMemoryEntry>,
+  clock: u64,
+}
+
+#[derive(Debug)]
+enum InMemoryEntry {
+  File { contents: Vec<u8>, modified: u64 },
+  Directory { modified: u64 },
+}
+
+impl InMemoryFileSystem {
+  /// Gets the current time of the logical clock.
+  pub fn clock(&self) -> u64 { self.state.borrow().clock }
+  /// Sets the logical clock to `clock`. Setting the clock to an earlier time simulates file systems with imprecise
+  /// modification times, where consecutive writes can result in the same modification time.
+  pub fn set_clock(&self, clock: u64) { self.state.borrow_mut().clock = clock; }
+
+  /// Creates a directory at given `path`.
+  pub fn create_dir(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
+    let path = path.as_ref();
+    let mut state = self.state.borrow_mut();
+    let modified = state.tick();
+    if state.entries.insert(path.to_path_buf(), InMemoryEntry::Directory { modified }).is_none() {
+      state.set_parent_modified(path, modified);
+    }
+    Ok(())
+  }
+
+  /// Returns whether a file or directory exists at given `path`.
+  pub fn exists(&self, path: impl AsRef<Path>) -> bool {
+    self.state.borrow().entries.contains_key(path.as_ref())
+  }
+
+  /// Reads the file at given `path` into a string.
+  pub fn read_to_string(&self, path: impl AsRef<Path>) -> Result<String, io::Error> {
+    let Some(mut file) = self.open_if_file(&path)? else {
+      return Err(not_found(path));
+    };
+    let mut string = String::new();
+    file.read_to_string(&mut string)?;
+    Ok(string)
+  }
+}
+
+impl InMemoryState {
+  /// Returns the current time of the logical clock, then advances the clock.
+  fn tick(&mut self) -> u64 {
+    let time = self.clock;
+    self.clock += 1;
+    time
+  }
+  /// Sets the modification time of the parent directory of `path` to `modified`, if it exists.
+  fn set_parent_modified(&mut self, path: &Path, modified: u64) {
+    if let Some(InMemoryEntry::Directory { modified: parent_modified }) = path.parent()
+      .and_then(|parent| self.entries.get_mut(parent)) {
+      *parent_modified = modified;
+    }
+  }
+}
+
+impl FileSystem for InMemoryFileSystem {
+  type File = Cursor<Vec<u8>>;
+
+  fn metadata(&self, path: impl AsRef<Path>) -> Result<Option<Metadata>, io::Error> {
+    let metadata = self.state.borrow().entries.get(path.as_ref()).map(|entry| {
+      let (is_file, modified) = match entry {
+        InMemoryEntry::File { modified, .. } => (true, *modified),
+        InMemoryEntry::Directory { modified } => (false, *modified),
+      };
+      Metadata { is_file, modified: SystemTime::UNIX_EPOCH + Duration::from_secs(modified) }
+    });
+    Ok(metadata)
+  }
+
+  fn open_if_file(&self, path: impl AsRef<Path>) -> Result<Option<Self::File>, io::Error> {
+    let file = match self.state.borrow().entries.get(path.as_ref()) {
+      Some(InMemoryEntry::File { contents, .. }) => Some(Cursor::new(contents.clone())),
+      _ => None,
+    };
+    Ok(file)
+  }
+
+  fn write(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), io::Error> {
+    let path = path.as_ref();
+    let mut state = self.state.borrow_mut();
+    if let Some(InMemoryEntry::Directory { .. }) = state.entries.get(path) {
+      return Err(io::Error::other(format!("cannot write to directory '{}'", path.display())));
+    }
+    let modified = state.tick();
+    let entry = InMemoryEntry::File { contents: contents.as_ref().to_vec(), modified };
+    if state.entries.insert(path.to_path_buf(), entry).is_none() {
+      state.set_parent_modified(path, modified);
+    }
+    Ok(())
+  }
+
+  fn remove_file(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
+    let mut state = self.state.borrow_mut();
+    match state.entries.get(path.as_ref()) {
+      Some(InMemoryEntry::File { .. }) => {
+        state.entries.remove(path.as_ref());
+        let modified = state.tick();
+        state.set_parent_modified(path.as_ref(), modified);
+        Ok(())
+      }
+      _ => Err(not_found(path)),
+    }
+  }
+
+  fn read_dir(&self, path: impl AsRef<Path>) -> Result<Vec<PathBuf>, io::Error> {
+    let path = path.as_ref();
+    let state = self.state.borrow();
+    let Some(InMemoryEntry::Directory { .. }) = state.entries.get(path) else {
+      return Err(io::Error::new(io::ErrorKind::NotFound, format!("directory '{}' not found", path.display())));
+    };
+    let entries = state.entries.keys()
+      .filter(|entry| entry.parent() == Some(path))
+      .cloned()
+      .collect();
+    Ok(entries)
+  }
+}
+
+fn not_found(path: impl AsRef<Path>) -> io::Error {
+  io::Error::new(io::ErrorKind::NotFound, format!("file '{}' not found", path.as_ref().display()))
+}
+
+#[cfg(test)]
+mod test {
+  use std::fs::remove_file;
+  use std::io;
+
+  use assert_matches::assert_matches;
+
+  use dev_shared::{create_temp_dir, create_temp_file};
+
+  use super::*;
+
+  #[test]
+  fn test_metadata_ok() -> Result<(), io::Error> {
+    let temp_file = create_temp_file()?;
+    let metadata = RealFileSystem.metadata(temp_file)?;
+    assert_matches!(metadata, Some(metadata) => {
+      assert!(metadata.is_file);
+    });
+    Ok(())
+  }
+
+  #[test]
+  fn test_metadata_none() -> Result<(), io::Error> {
+    let temp_file = create_temp_file()?;
+    remove_file(&temp_file)?;
+    let metadata = RealFileSystem.metadata(&temp_file)?;
+    assert!(metadata.is_none());
+    Ok(())
+  }
+
+  #[test]
+  fn test_open_if_file() -> Result<(), io::Error> {
+    let temp_file = create_temp_file()?;
+    let file = RealFileSystem.open_if_file(&temp_file)?;
+    assert!(file.is_some());
+    Ok(())
+  }
+
+  #[test]
+  fn test_open_if_file_non_existent() -> Result<(), io::Error> {
+    let temp_file = create_temp_file()?;
+    remove_file(&temp_file)?;
+    let file = RealFileSystem.open_if_file(&temp_file)?;
+    assert!(file.is_none());
+    Ok(())
+  }
+
+  #[test]
+  fn test_open_if_file_on_directory() -> Result<(), io::Error> {
+    let temp_dir = create_temp_dir()?;
+    let file = RealFileSystem.open_if_file(temp_dir)?;
+    assert!(file.is_none());
+    Ok(())
+  }
+
+  #[test]
+  fn test_read_dir() -> Result<(), io::Error> {
+    let temp_dir = create_temp_dir()?;
+    fs::write(temp_dir.path().join("a.txt"), "Hello")?;
+    fs::create_dir(temp_dir.path().join("b"))?;
+    let mut entries = RealFileSystem.read_dir(&temp_dir)?;
+    entries.sort();
+    assert_eq!(entries, vec![temp_dir.path().join("a.txt"), temp_dir.path().join("b")]);
+    assert!(RealFileSystem.read_dir(temp_dir.path().join("a.txt")).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_in_memory() -> Result<(), io::Error> {
+    let fs = InMemoryFileSystem::default();
+    assert!(fs.metadata("in.txt")?.is_none());
+    assert!(fs.open_if_file("in.txt")?.is_none());
+
+    fs.write("in.txt", "Hello")?;
+    let metadata = assert_matches!(fs.metadata("in.txt")?, Some(m) => m);
+    assert!(metadata.is_file);
+    assert_eq!(fs.read_to_string("in.txt")?, "Hello");
+
+    // Consecutive writes result in different modification times.
+    fs.write("in.txt", "World")?;
+    let new_metadata = assert_matches!(fs.metadata("in.txt")?, Some(m) => m);
+    assert!(new_metadata.modified > metadata.modified);
+    assert_eq!(fs.read_to_string("in.txt")?, "World");
+
+    // Unless the clock is reset.
+    fs.set_clock(fs.clock() - 1);
+    fs.write("in.txt", "Hello")?;
+    assert_eq!(fs.metadata("in.txt")?, Some(new_metadata));
+
+    fs.remove_file("in.txt")?;
+    assert!(!fs.exists("in.txt"));
+    assert!(fs.remove_file("in.txt").is_err());
+    assert!(fs.read_to_string("in.txt").is_err());
+
+    fs.create_dir("dir")?;
+    assert_matches!(fs.metadata("dir")?, Some(m) if !m.is_file);
+    assert!(fs.open_if_file("dir")?.is_none());
+    assert!(fs.write("dir", "Hello").is_err());
+    assert_eq!(fs.read_dir("dir")?, Vec::<PathBuf>::new());
+    assert!(fs.read_dir("in.txt").is_err());
+
+    // Creating and removing entries in a directory changes the modification time of the directory.
+    let dir_modified = assert_matches!(fs.metadata("dir")?, Some(m) => m.modified);
+    fs.write("dir/a.txt", "Hello")?;
+    let write_modified = assert_matches!(fs.metadata("dir")?, Some(m) => m.modified);
+    assert!(write_modified > dir_modified);
+    assert_eq!(fs.read_dir("dir")?, vec![PathBuf::from("dir/a.txt")]);
+    fs.write("dir/a.txt", "World")?; // Overwriting is not creating an entry.
+    assert_eq!(fs.metadata("dir")?.map(|m| m.modified), Some(write_modified));
+    fs.remove_file("dir/a.txt")?;
+    assert!(fs.metadata("dir")?.is_some_and(|m| m.modified > write_modified));
+    assert_eq!(fs.read_dir("dir")?, Vec::<PathBuf>::new());
+
+    // Clones share files.
+    fs.clone().write("in.txt", "Hello")?;
+    assert!(fs.exists("in.txt"));
+
+    Ok(())
+  }
+}
@@ -0,0 +1,136 @@
+use std::io;
+
+use assert_matches::assert_matches;
+
+use pie::{Context, Task};
+use pie::fs::FileSystem;
+use pie::stamp::FileStamper;
+use pie::tasks::{FileError, FileOutput, FileTask};
+
+use crate::common::{test_pie, TestPieExt};
+
+mod common;
+
+/// Task type embedding [`FileTask`]s, with a task that converts the string output of a task to uppercase.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+enum Tasks {
+  File(FileTask<Tasks>),
+  ToUpper(Box<Tasks>),
+}
+
+impl Task for Tasks {
+  type Output = Result<FileOutput, FileError>;
+  fn execute<C: Context<Self>>(&self, context: &mut C) -> Self::Output {
+    match self {
+      Tasks::File(task) => task.execute(context),
+      Tasks::ToUpper(task) => match context.require_task(task)? {
+        FileOutput::String(string) => Ok(FileOutput::String(string.to_uppercase())),
+        output => Err(FileError::Other(format!("expected string output, got {:?}", output))),
+      },
+    }
+  }
+}
+
+impl From<FileTask<Tasks>> for Tasks {
+  fn from(value: FileTask<Tasks>) -> Self { Self::File(value) }
+}
+
+#[test]
+fn test_read() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  pie.set_differential_checking(true);
+  pie.file_system().write("in.txt", "Hello")?;
+  let read_string = Tasks::from(FileTask::read_string("in.txt"));
+  let read_bytes = Tasks::from(FileTask::read_bytes("in.txt"));
+  assert_eq!(pie.require(&read_string), Ok(FileOutput::String("Hello".to_string())));
+  assert_eq!(pie.require(&read_bytes), Ok(FileOutput::Bytes(b"Hello".to_vec())));
+  assert_eq!(pie.require_then_assert_no_execute(&read_string), Ok(FileOutput::String("Hello".to_string())));
+
+  pie.file_system().write("in.txt", [0xff])?;
+  assert_matches!(pie.require_then_assert_one_execute(&read_string), Err(FileError::InvalidUtf8(p)) if p.as_os_str() == "in.txt");
+  assert_eq!(pie.require_then_assert_one_execute(&read_bytes), Ok(FileOutput::Bytes(vec![0xff])));
+
+  pie.file_system().remove_file("in.txt")?;
+  assert_matches!(pie.require_then_assert_one_execute(&read_string), Err(FileError::NotFound(p)) if p.as_os_str() == "in.txt");
+
+  // With the exists stamper, only creating or removing the file executes the task again. The output then becomes
+  // stale when the file is changed, which differential checking would report.
+  pie.set_differential_checking(false);
+  let read_exists = Tasks::from(FileTask::read_string("in.txt").with_stamper(FileStamper::Exists));
+  pie.file_system().write("in.txt", "Hello")?;
+  assert_eq!(pie.require_then_assert_one_execute(&read_exists), Ok(FileOutput::String("Hello".to_string())));
+  pie.file_system().write("in.txt", "World")?;
+  assert_eq!(pie.require_then_assert_no_execute(&read_exists), Ok(FileOutput::String("Hello".to_string())));
+
+  Ok(())
+}
+
+#[test]
+fn test_write_and_copy() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  pie.set_differential_checking(true);
+  pie.file_system().write("in.txt", "Hello")?;
+  let upper = Tasks::ToUpper(Box::new(FileTask::read_string("in.txt").into()));
+  let write = Tasks::from(FileTask::write(upper.clone(), "upper.txt"));
+  // Copying the written file requires the writing task as origin, preventing a hidden dependency.
+  let copy = Tasks::from(FileTask::copy("upper.txt", "copy.txt").with_origin(write.clone()));
+  let read = Tasks::from(FileTask::read_string("copy.txt").with_origin(copy.clone()));
+
+  assert_eq!(pie.require_then_assert(&read, |tracker| {
+    assert!(tracker.one_execute_of(&write));
+    assert!(tracker.one_execute_of(&copy));
+  }), Ok(FileOutput::String("HELLO".to_string())));
+  assert_eq!(pie.file_system().read_to_string("upper.txt")?, "HELLO");
+  assert!(pie.require_then_assert(&read, |tracker| assert!(tracker.executed_tasks().is_empty())).is_ok());
+
+  pie.file_system().write("in.txt", "World")?;
+  assert_eq!(pie.require_then_assert(&read, |tracker| {
+    assert!(tracker.one_execute_of(&write));
+    assert!(tracker.one_execute_of(&copy));
+  }), Ok(FileOutput::String("WORLD".to_string())));
+
+  // Only outputs with file contents can be written.
+  let write_unit = Tasks::from(FileTask::write(write.clone(), "unit.txt"));
+  assert_eq!(pie.require(&write_unit), Err(FileError::NoContents));
+
+  Ok(())
+}
+
+#[test]
+fn test_list_directory() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  pie.set_differential_checking(true);
+  let list = Tasks::from(FileTask::list_directory("dir"));
+  assert_matches!(pie.require(&list), Err(FileError::NotFound(p)) if p.as_os_str() == "dir");
+
+  pie.file_system().create_dir("dir")?;
+  pie.file_system().write("dir/b.txt", "")?;
+  pie.file_system().write("dir/a.txt", "")?;
+  assert_eq!(pie.require_then_assert_one_execute(&list), Ok(FileOutput::Paths(vec!["dir/a.txt".into(), "dir/b.txt".into()])));
+
+  // Changing a file in the directory does not change the listing.
+  pie.file_system().write("dir/a.txt", "Hello")?;
+  assert!(pie.require_then_assert_no_execute(&list).is_ok());
+
+  pie.file_system().remove_file("dir/b.txt")?;
+  assert_eq!(pie.require_then_assert_one_execute(&list), Ok(FileOutput::Paths(vec!["dir/a.txt".into()])));
+
+  Ok(())
+}
+
+#[test]
+fn test_template() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  pie.set_differential_checking(true);
+  pie.file_system().write("template.txt", "{{greeting}}, {{name}}!")?;
+  let template = Tasks::from(FileTask::template("template.txt", [("greeting", "Hello"), ("name", "World")]));
+  let write = Tasks::from(FileTask::write(template.clone(), "out.txt"));
+  assert_eq!(pie.require(&write), Ok(FileOutput::Unit));
+  assert_eq!(pie.file_system().read_to_string("out.txt")?, "Hello, World!");
+  assert!(pie.require_then_assert_no_execute(&template).is_ok());
+
+  pie.file_system().write("template.txt", "{{greeting}}, {{planet}}!")?;
+  assert_eq!(pie.require(&write), Err(FileError::UndefinedVariable("planet".to_string())));
+
+  Ok(())
+}
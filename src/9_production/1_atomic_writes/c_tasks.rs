@@ -0,0 +1,264 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::{Context, Task};
+use crate::fs::FileSystem;
+use crate::stamp::FileStamper;
+
+/// Reusable tasks that read, write, copy, and list files, creating the correct require and provide dependencies.
+///
+/// PIE builds with a single task type, so this enum is generic over that task type `T`, and is embedded in it as a
+/// variant. Executing a file task with [`execute`](Self::execute) returns a [`FileOutput`], which the embedding task type
+/// converts into its output. For example:
+///
+/// ```
+/// # use pie::{Context, Task};
+/// # use pie::tasks::{FileError, FileOutput, FileTask};
+/// #[derive(Clone, Eq, PartialEq, Hash, Debug)]
+/// enum Tasks {
+///   File(FileTask<Tasks>),
+///   // Other tasks...
+/// }
+/// impl Task for Tasks {
+///   type Output = Result<FileOutput, FileError>;
+///   fn execute<C: Context<Self>>(&self, context: &mut C) -> Self::Output {
+///     match self {
+///       Tasks::File(task) => task.execute(context),
+///     }
+///   }
+/// }
+/// ```
+///
+/// Each file task first requires its origin task, if any, and ignores the output of the origin task. Set the task that
+/// provides the file that a file task requires as its origin, to prevent hidden dependencies.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FileTask<T> {
+  /// Requires file at `path` and reads it into a string.
+  ReadString { path: PathBuf, stamper: FileStamper, origin: Option<Box<T>> },
+  /// Requires file at `path` and reads its bytes.
+  ReadBytes { path: PathBuf, stamper: FileStamper, origin: Option<Box<T>> },
+  /// Requires `task` and atomically writes its output to file at `path`, then provides that file.
+  Write { task: Box<T>, path: PathBuf, stamper: FileStamper, origin: Option<Box<T>> },
+  /// Requires file at `src` and atomically copies it to file at `dst`, then provides that file.
+  Copy { src: PathBuf, dst: PathBuf, stamper: FileStamper, origin: Option<Box<T>> },
+  /// Requires directory at `path` and lists the paths of its entries, sorted.
+  ListDirectory { path: PathBuf, stamper: FileStamper, origin: Option<Box<T>> },
+  /// Requires template file at `path`, reads it into a string, and substitutes `{{name}}` placeholders with the value of
+  /// variable `name` in `variables`.
+  Template { path: PathBuf, variables: BTreeMap<String, String>, stamper: FileStamper, origin: Option<Box<T>> },
+}
+
+/// Output of a [`FileTask`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FileOutput {
+  String(String),
+  Bytes(Vec<u8>),
+  Paths(Vec<PathBuf>),
+  Unit,
+}
+
+/// Error of a [`FileTask`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FileError {
+  /// A file system operation failed with given error message.
+  Io(String),
+  /// No file or directory exists at given path.
+  NotFound(PathBuf),
+  /// File at given path is not valid UTF-8.
+  InvalidUtf8(PathBuf),
+  /// The output of the task to write has no file contents.
+  NoContents,
+  /// The template uses undefined variable with given name.
+  UndefinedVariable(String),
+  /// A task failed with given error message.
+  Other(String),
+}
+
+impl From<io::Error> for FileError {
+  fn from(value: io::Error) -> Self { Self::Io(value.to_string()) }
+}
+impl From<String> for FileError {
+  fn from(value: String) -> Self { Self::Other(value) }
+}
+
+/// Task outputs that can be written to a file with [`FileTask::Write`].
+pub trait ToFileContents {
+  /// Gets the file contents of this output, or an error if this output has no file contents.
+  fn to_file_contents(&self) -> Result<Cow<'_, [u8]>, FileError>;
+}
+impl ToFileContents for String {
+  fn to_file_contents(&self) -> Result<Cow<'_, [u8]>, FileError> { Ok(Cow::Borrowed(self.as_bytes())) }
+}
+impl ToFileContents for Vec<u8> {
+  fn to_file_contents(&self) -> Result<Cow<'_, [u8]>, FileError> { Ok(Cow::Borrowed(self)) }
+}
+impl ToFileContents for FileOutput {
+  fn to_file_contents(&self) -> Result<Cow<'_, [u8]>, FileError> {
+    match self {
+      FileOutput::String(string) => string.to_file_contents(),
+      FileOutput::Bytes(bytes) => bytes.to_file_contents(),
+      _ => Err(FileError::NoContents),
+    }
+  }
+}
+impl<O: ToFileContents, E: Clone + Into<FileError>> ToFileContents for Result<O, E> {
+  fn to_file_contents(&self) -> Result<Cow<'_, [u8]>, FileError> {
+    match self {
+      Ok(output) => output.to_file_contents(),
+      Err(e) => Err(e.clone().into()),
+    }
+  }
+}
+
+impl<T> FileTask<T> {
+  /// Creates a [`Self::ReadString`] task that reads file at `path` into a string.
+  pub fn read_string(path: impl Into<PathBuf>) -> Self {
+    Self::ReadString { path: path.into(), stamper: FileStamper::Modified, origin: None }
+  }
+  /// Creates a [`Self::ReadBytes`] task that reads the bytes of file at `path`.
+  pub fn read_bytes(path: impl Into<PathBuf>) -> Self {
+    Self::ReadBytes { path: path.into(), stamper: FileStamper::Modified, origin: None }
+  }
+  /// Creates a [`Self::Write`] task that writes the output of `task` to file at `path`.
+  pub fn write(task: T, path: impl Into<PathBuf>) -> Self {
+    Self::Write { task: Box::new(task), path: path.into(), stamper: FileStamper::Modified, origin: None }
+  }
+  /// Creates a [`Self::Copy`] task that copies file at `src` to file at `dst`.
+  pub fn copy(src: impl Into<PathBuf>, dst: impl Into<PathBuf>) -> Self {
+    Self::Copy { src: src.into(), dst: dst.into(), stamper: FileStamper::Modified, origin: None }
+  }
+  /// Creates a [`Self::ListDirectory`] task that lists the entries of directory at `path`.
+  pub fn list_directory(path: impl Into<PathBuf>) -> Self {
+    Self::ListDirectory { path: path.into(), stamper: FileStamper::Modified, origin: None }
+  }
+  /// Creates a [`Self::Template`] task that substitutes `variables` in template file at `path`.
+  pub fn template(
+    path: impl Into<PathBuf>,
+    variables: impl IntoIterator<Item=(impl Into<String>, impl Into<String>)>,
+  ) -> Self {
+    let variables = variables.into_iter().map(|(name, value)| (name.into(), value.into())).collect();
+    Self::Template { path: path.into(), variables, stamper: FileStamper::Modified, origin: None }
+  }
+
+  /// Sets the stamper of this task to `stamper`, used for requiring and providing files. Defaults to the
+  /// [modified stamper](FileStamper::Modified).
+  pub fn with_stamper(mut self, stamper: FileStamper) -> Self {
+    *self.parts_mut().0 = stamper;
+    self
+  }
+  /// Sets the origin task of this task to `origin`, which is required before requiring files.
+  pub fn with_origin(mut self, origin: T) -> Self {
+    *self.parts_mut().1 = Some(Box::new(origin));
+    self
+  }
+
+  fn parts_mut(&mut self) -> (&mut FileStamper, &mut Option<Box<T>>) {
+    match self {
+      Self::ReadString { stamper, origin, .. } => (stamper, origin),
+      Self::ReadBytes { stamper, origin, .. } => (stamper, origin),
+      Self::Write { stamper, origin, .. } => (stamper, origin),
+      Self::Copy { stamper, origin, .. } => (stamper, origin),
+      Self::ListDirectory { stamper, origin, .. } => (stamper, origin),
+      Self::Template { stamper, origin, .. } => (stamper, origin),
+    }
+  }
+  fn origin(&self) -> Option<&T> {
+    match self {
+      Self::ReadString { origin, .. } | Self::ReadBytes { origin, .. } | Self::Write { origin, .. } |
+      Self::Copy { origin, .. } | Self::ListDirectory { origin, .. } | Self::Template { origin, .. } => origin.as_deref(),
+    }
+  }
+}
+
+impl<T: Task> FileTask<T> where T::Output: ToFileContents {
+  /// Executes this file task in `context`.
+  pub fn execute<C: Context<T>>(&self, context: &mut C) -> Result<FileOutput, FileError> {
+    if let Some(origin) = self.origin() {
+      context.require_task(origin);
+    }
+    let output = match self {
+      Self::ReadString { path, stamper, .. } => FileOutput::String(require_string(context, path, *stamper)?),
+      Self::ReadBytes { path, stamper, .. } => FileOutput::Bytes(require_bytes(context, path, *stamper)?),
+      Self::Write { task, path, stamper, .. } => {
+        let output = context.require_task_shared(task);
+        let contents = output.to_file_contents()?;
+        context.write_provided_file_with_stamper(path, *stamper, |w| w.write_all(&contents))?;
+        FileOutput::Unit
+      }
+      Self::Copy { src, dst, stamper, .. } => {
+        let bytes = require_bytes(context, src, *stamper)?;
+        context.write_provided_file_with_stamper(dst, *stamper, |w| w.write_all(&bytes))?;
+        FileOutput::Unit
+      }
+      Self::ListDirectory { path, stamper, .. } => {
+        context.require_file_with_stamper(path, *stamper)?;
+        if context.file_system().metadata(path)?.is_none() {
+          return Err(FileError::NotFound(path.clone()));
+        }
+        let mut entries = context.file_system().read_dir(path)?;
+        entries.sort();
+        FileOutput::Paths(entries)
+      }
+      Self::Template { path, variables, stamper, .. } => {
+        let template = require_string(context, path, *stamper)?;
+        FileOutput::String(substitute(&template, variables)?)
+      }
+    };
+    Ok(output)
+  }
+}
+
+/// Requires file at `path` with `stamper` and reads its bytes.
+fn require_bytes<T: Task, C: Context<T>>(context: &mut C, path: &Path, stamper: FileStamper) -> Result<Vec<u8>, FileError> {
+  let Some(mut file) = context.require_file_with_stamper(path, stamper)? else {
+    return Err(FileError::NotFound(path.to_path_buf()));
+  };
+  let mut bytes = Vec::new();
+  file.read_to_end(&mut bytes)?;
+  Ok(bytes)
+}
+
+/// Requires file at `path` with `stamper` and reads it into a string.
+fn require_string<T: Task, C: Context<T>>(context: &mut C, path: &Path, stamper: FileStamper) -> Result<String, FileError> {
+  let bytes = require_bytes(context, path, stamper)?;
+  String::from_utf8(bytes).map_err(|_| FileError::InvalidUtf8(path.to_path_buf()))
+}
+
+/// Substitutes `{{name}}` placeholders in `template` with the value of variable `name` in `variables`. Placeholders
+/// without a closing `}}` are kept as is.
+fn substitute(template: &str, variables: &BTreeMap<String, String>) -> Result<String, FileError> {
+  let mut output = String::with_capacity(template.len());
+  let mut rest = template;
+  while let Some(start) = rest.find("{{") {
+    let Some(end) = rest[start + 2..].find("}}") else { break; };
+    let name = rest[start + 2..start + 2 + end].trim();
+    let value = variables.get(name).ok_or_else(|| FileError::UndefinedVariable(name.to_string()))?;
+    output.push_str(&rest[..start]);
+    output.push_str(value);
+    rest = &rest[start + 2 + end + 2..];
+  }
+  output.push_str(rest);
+  Ok(output)
+}
+
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_substitute() {
+    let variables = BTreeMap::from([("name".to_string(), "World".to_string()), ("greeting".to_string(), "Hello".to_string())]);
+    assert_eq!(substitute("", &variables), Ok(String::new()));
+    assert_eq!(substitute("No placeholders", &variables), Ok("No placeholders".to_string()));
+    assert_eq!(substitute("{{greeting}}, {{ name }}!", &variables), Ok("Hello, World!".to_string()));
+    assert_eq!(substitute("{{name}}{{name}}", &variables), Ok("WorldWorld".to_string()));
+    assert_eq!(substitute("{{name}} {{unclosed", &variables), Ok("World {{unclosed".to_string()));
+    assert_eq!(substitute("{{missing}}", &variables), Err(FileError::UndefinedVariable("missing".to_string())));
+  }
+}
@@ -0,0 +1,83 @@
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use assert_matches::assert_matches;
+
+use pie::{Context, Task};
+use pie::fs::FileSystem;
+use pie::stamp::FileStamper;
+use pie::tracker::event::{Event, VerifyFilesEnd};
+
+use crate::common::{test_pie, TestPieExt};
+
+mod common;
+
+/// Task that requires and reads file at `path` using `stamper`, and then overwrites it with `contents` if its contents
+/// differ, simulating a user that saves the file in an editor during the build.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+struct ReadThenOverwrite {
+  path: PathBuf,
+  stamper: FileStamper,
+  contents: &'static str,
+}
+
+impl Task for ReadThenOverwrite {
+  type Output = Result<String, io::ErrorKind>;
+  fn execute<C: Context<Self>>(&self, context: &mut C) -> Self::Output {
+    let mut string = String::new();
+    if let Some(mut file) = context.require_file_with_stamper(&self.path, self.stamper).map_err(|e| e.kind())? {
+      file.read_to_string(&mut string).map_err(|e| e.kind())?;
+    }
+    if string != self.contents {
+      context.file_system().write(&self.path, self.contents).map_err(|e| e.kind())?;
+    }
+    Ok(string)
+  }
+}
+
+#[test]
+fn test_modified_files() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let path = PathBuf::from("in.txt");
+  pie.file_system().write(&path, "Hello")?;
+  let task = ReadThenOverwrite { path: path.clone(), stamper: FileStamper::Modified, contents: "World" };
+
+  // The file is modified after it was required, which is reported to the tracker and stored in the session.
+  let mut session = pie.new_session();
+  assert_eq!(session.require(&task), Ok("Hello".to_string()));
+  assert_matches!(session.modified_files(), [m] if m.task == task && m.dependency.path() == &path &&
+    &m.new_stamp != m.dependency.stamp());
+  drop(session);
+  let modified_files_events: Vec<_> = pie.tracker().0.iter().filter_map(|e| match e {
+    Event::VerifyFilesEnd(VerifyFilesEnd { modified_files, .. }) => Some(modified_files.as_slice()),
+    _ => None,
+  }).collect();
+  assert_matches!(modified_files_events.as_slice(), [[m]] if m.task == task);
+
+  // The task was marked for execution, so it is executed again in the next session. It now reads the modified file and
+  // does not modify it, so no modified files are reported.
+  assert_eq!(pie.require_then_assert_one_execute(&task), Ok("World".to_string()));
+  assert_eq!(pie.require_then_assert_no_execute(&task), Ok("World".to_string()));
+
+  Ok(())
+}
+
+#[test]
+fn test_no_modified_files() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let path = PathBuf::from("in.txt");
+  pie.file_system().write(&path, "World")?;
+  let task = ReadThenOverwrite { path, stamper: FileStamper::Modified, contents: "World" };
+
+  // Files are verified at the end of each build, without reporting unmodified files.
+  assert_eq!(pie.require_then_assert(&task, |tracker| {
+    assert!(tracker.iter().any(|e| matches!(e, Event::VerifyFilesEnd(VerifyFilesEnd { modified_files, .. })
+      if modified_files.is_empty())));
+  }), Ok("World".to_string()));
+  // Files required by tasks that were not executed are not verified.
+  assert_eq!(pie.require_then_assert(&task, |tracker| {
+    assert!(!tracker.iter().any(|e| matches!(e, Event::VerifyFilesStart(_))));
+  }), Ok("World".to_string()));
+
+  Ok(())
+}
@@ -0,0 +1,90 @@
+use std::cell::Cell;
+use std::io;
+
+use pie::{Context, OutputPolicy, Task};
+
+use crate::common::{test_pie, TestPieExt};
+
+mod common;
+
+thread_local! {
+  /// Number of times that a failing task still fails on this thread. Tests run on separate threads, so they do not
+  /// influence each other.
+  static FAILURES: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Task that fails with an I/O error of `kind` while there are failures left, and succeeds otherwise.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+struct Failing(io::ErrorKind);
+
+impl Task for Failing {
+  type Output = Result<&'static str, io::ErrorKind>;
+  fn execute<C: Context<Self>>(&self, _context: &mut C) -> Self::Output {
+    let failures = FAILURES.with(|f| f.get());
+    if failures > 0 {
+      FAILURES.with(|f| f.set(failures - 1));
+      Err(self.0)
+    } else {
+      Ok("Hello, World!")
+    }
+  }
+  fn output_policy(output: &Self::Output) -> OutputPolicy {
+    match output {
+      Err(io::ErrorKind::PermissionDenied) => OutputPolicy::Transient,
+      Err(kind) => OutputPolicy::from_io_error_kind(*kind),
+      Ok(_) => OutputPolicy::Cache,
+    }
+  }
+}
+
+#[test]
+fn test_transient() {
+  let mut pie = test_pie();
+  let task = Failing(io::ErrorKind::PermissionDenied);
+
+  // Transient outputs are returned, and the task is executed again in the next session, even though it has no
+  // dependencies.
+  FAILURES.with(|f| f.set(1));
+  assert_eq!(pie.require_then_assert_one_execute(&task), Err(io::ErrorKind::PermissionDenied));
+  assert_eq!(pie.require_then_assert_one_execute(&task), Ok("Hello, World!"));
+  // Successful outputs are cached.
+  assert_eq!(pie.require_then_assert_no_execute(&task), Ok("Hello, World!"));
+}
+
+#[test]
+fn test_cache() {
+  let mut pie = test_pie();
+  let task = Failing(io::ErrorKind::NotFound);
+
+  // Errors that are not transient are cached like successful outputs.
+  FAILURES.with(|f| f.set(1));
+  assert_eq!(pie.require_then_assert_one_execute(&task), Err(io::ErrorKind::NotFound));
+  assert_eq!(pie.require_then_assert_no_execute(&task), Err(io::ErrorKind::NotFound));
+}
+
+#[test]
+fn test_retry() {
+  let mut pie = test_pie();
+  let task = Failing(io::ErrorKind::Interrupted);
+
+  // Tasks are retried in the same execution, until they succeed.
+  pie.set_max_retries(2);
+  FAILURES.with(|f| f.set(2));
+  assert_eq!(pie.require_then_assert_one_execute(&task), Ok("Hello, World!"));
+  assert_eq!(FAILURES.with(|f| f.get()), 0);
+  assert_eq!(pie.require_then_assert_no_execute(&task), Ok("Hello, World!"));
+
+  // When no retries are left, the output is transient.
+  let task = Failing(io::ErrorKind::TimedOut);
+  FAILURES.with(|f| f.set(3));
+  assert_eq!(pie.require_then_assert_one_execute(&task), Err(io::ErrorKind::TimedOut));
+  assert_eq!(FAILURES.with(|f| f.get()), 0);
+  assert_eq!(pie.require_then_assert_one_execute(&task), Ok("Hello, World!"));
+
+  // Without retries, the output is transient right away.
+  pie.set_max_retries(0);
+  let task = Failing(io::ErrorKind::WouldBlock);
+  FAILURES.with(|f| f.set(1));
+  assert_eq!(pie.require_then_assert_one_execute(&task), Err(io::ErrorKind::WouldBlock));
+  assert_eq!(pie.require_then_assert_one_execute(&task), Ok("Hello, World!"));
+}
@@ -0,0 +1,90 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// Severity of a [`Diagnostic`].
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+  Info,
+  Warning,
+  Error,
+}
+
+/// Location in a file that a [`Diagnostic`] is about: the file at `path`, optionally at a 1-based `line` and `column`.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Location {
+  pub path: PathBuf,
+  pub line: Option<usize>,
+  pub column: Option<usize>,
+}
+
+/// Diagnostic emitted by a task while executing, such as a warning about its input. Diagnostics are stored with the
+/// output of the task, and are reported again when the task is not executed because it is consistent, such that they
+/// do not disappear on incremental builds.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+  pub severity: Severity,
+  pub message: String,
+  pub location: Option<Location>,
+}
+
+impl Location {
+  /// Creates a location for the entire file at `path`.
+  pub fn file(path: impl Into<PathBuf>) -> Self {
+    Self { path: path.into(), line: None, column: None }
+  }
+  /// Creates a location for `line` and `column` in the file at `path`.
+  pub fn at(path: impl Into<PathBuf>, line: usize, column: usize) -> Self {
+    Self { path: path.into(), line: Some(line), column: Some(column) }
+  }
+}
+
+impl Display for Severity {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Severity::Info => f.write_str("info"),
+      Severity::Warning => f.write_str("warning"),
+      Severity::Error => f.write_str("error"),
+    }
+  }
+}
+
+impl Display for Location {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.path.display())?;
+    if let Some(line) = self.line {
+      write!(f, ":{}", line)?;
+      if let Some(column) = self.column {
+        write!(f, ":{}", column)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+impl Display for Diagnostic {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    if let Some(location) = &self.location {
+      write!(f, "{}: ", location)?;
+    }
+    write!(f, "{}: {}", self.severity, self.message)
+  }
+}
+
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_display() {
+    let diagnostic = Diagnostic { severity: Severity::Warning, message: "unused rule".to_string(), location: None };
+    assert_eq!(diagnostic.to_string(), "warning: unused rule");
+    let diagnostic = Diagnostic { location: Some(Location::file("grammar.pest")), ..diagnostic };
+    assert_eq!(diagnostic.to_string(), "grammar.pest: warning: unused rule");
+    let diagnostic = Diagnostic { location: Some(Location::at("grammar.pest", 3, 14)), ..diagnostic };
+    assert_eq!(diagnostic.to_string(), "grammar.pest:3:14: warning: unused rule");
+  }
+}
@@ -0,0 +1,98 @@
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::{Context, Task};
+use crate::dependency::MakeConsistent;
+use crate::diagnostic::{Location, Severity};
+use crate::fs::{FileSystem, RealFileSystem};
+use crate::stamp::{FileStamper, OutputStamper};
+
+/// Context that does not do incremental building: it executes every required task, and does not create dependencies.
+/// Useful as an oracle for testing that incremental contexts are sound.
+pub struct NonIncrementalContext<F = RealFileSystem> {
+  file_system: F,
+}
+
+impl<F: FileSystem> NonIncrementalContext<F> {
+  /// Creates a new non-incremental context that reads files from `file_system`.
+  pub fn new(file_system: F) -> Self { Self { file_system } }
+}
+
+impl Default for NonIncrementalContext {
+  fn default() -> Self { Self::new(RealFileSystem) }
+}
+
+impl<T: Task, F: FileSystem> Context<T> for NonIncrementalContext<F> {
+  type FileSystem = F;
+  fn file_system(&self) -> &F { &self.file_system }
+
+  fn require_file_with_stamper<P: AsRef<Path>>(&mut self, path: P, _stamper: FileStamper) -> Result<Option<F::File>, io::Error> {
+    self.file_system.open_if_file(&path)
+  }
+
+  fn provide_file_with_stamper<P: AsRef<Path>>(&mut self, _path: P, _stamper: FileStamper) -> Result<(), io::Error> {
+    Ok(())
+  }
+
+  fn require_task_with_stamper(&mut self, task: &T, _stamper: OutputStamper) -> T::Output {
+    task.execute(self)
+  }
+  fn require_task_shared_with_stamper(&mut self, task: &T, _stamper: OutputStamper) -> Arc<T::Output> {
+    Arc::new(task.execute(self))
+  }
+
+  fn emit_diagnostic<M: Into<String>>(&mut self, _severity: Severity, _message: M, _location: Option<Location>) {
+    // Diagnostics are discarded, as this context does not store outputs.
+  }
+}
+
+impl<T: Task, F: FileSystem> MakeConsistent<T> for NonIncrementalContext<F> {
+  fn make_task_consistent(&mut self, task: &T) -> Arc<T::Output> {
+    Arc::new(task.execute(self))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_require_task_direct() {
+    #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+    struct ReturnHelloWorld;
+
+    impl Task for ReturnHelloWorld {
+      type Output = String;
+      fn execute<C: Context<Self>>(&self, _context: &mut C) -> Self::Output {
+        "Hello World!".to_string()
+      }
+    }
+
+    let mut context = NonIncrementalContext::default();
+    assert_eq!("Hello World!", context.require_task(&ReturnHelloWorld));
+  }
+
+  #[test]
+  fn test_require_task() {
+    #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+    enum Test {
+      ReturnHelloWorld,
+      ToLowerCase,
+    }
+
+    impl Task for Test {
+      type Output = String;
+      fn execute<C: Context<Self>>(&self, context: &mut C) -> Self::Output {
+        match self {
+          Self::ReturnHelloWorld => "Hello World!".to_string(),
+          Self::ToLowerCase => context.require_task(&Self::ReturnHelloWorld).to_lowercase(),
+        }
+      }
+    }
+
+    let mut context = NonIncrementalContext::default();
+    assert_eq!("Hello World!", context.require_task(&Test::ReturnHelloWorld));
+    assert_eq!("hello world!", context.require_task(&Test::ToLowerCase));
+  }
+}
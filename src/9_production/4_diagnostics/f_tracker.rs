@@ -0,0 +1,522 @@
+use std::cell::RefCell;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::dependency::{Dependency, FileDependency, Inconsistency, ModifiedFile, TaskDependency};
+use crate::determinism::NonDeterminism;
+use crate::diagnostic::Diagnostic;
+use crate::stamp::{FileStamper, OutputStamper};
+use crate::Task;
+
+pub mod writing;
+pub mod event;
+pub mod progress;
+
+/// Identifier of a build operation, such as requiring a task or executing a task. The start and end event of an
+/// operation have the same identifier.
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpanId(pub u64);
+
+/// Span of a build operation: its identifier and the identifier of the operation it is nested in, if any. Only the
+/// build operation has no parent. The spans of all events in a build form a tree, which mirrors the call tree of the
+/// build.
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+  pub id: SpanId,
+  pub parent: Option<SpanId>,
+}
+
+/// Trait for tracking build events. Can be used to implement logging, event tracing, progress tracking, metrics, etc.
+///
+/// Every operation has a start and an end event, which receive the same [`Span`]. Every start event is followed by
+/// exactly one end event with the same span, with the events of nested operations in between. Reporting a diagnostic is
+/// not an operation, and has a single event that receives the span of the operation it is reported in.
+#[allow(unused_variables)]
+pub trait Tracker<T: Task> {
+  /// Start: a new build.
+  fn build_start(&mut self, span: Span) {}
+  /// End: completed build.
+  fn build_end(&mut self, span: Span) {}
+
+  /// Start: require file at `path` using `stamper`.
+  fn require_file_start(&mut self, span: Span, path: &Path, stamper: &FileStamper) {}
+  /// End: required file at `path` using `stamper`, resulting in a require file `dependency`, or an error if creating
+  /// the dependency failed.
+  fn require_file_end(
+    &mut self,
+    span: Span,
+    path: &Path,
+    stamper: &FileStamper,
+    dependency: Result<&FileDependency, &io::Error>
+  ) {}
+  /// Start: provide file at `path` using `stamper`.
+  fn provide_file_start(&mut self, span: Span, path: &Path, stamper: &FileStamper) {}
+  /// End: provided file at `path` using `stamper`, resulting in a provide file `dependency`, or an error if creating
+  /// the dependency failed.
+  fn provide_file_end(
+    &mut self,
+    span: Span,
+    path: &Path,
+    stamper: &FileStamper,
+    dependency: Result<&FileDependency, &io::Error>
+  ) {}
+  /// Start: require `task` using `stamper`.
+  fn require_task_start(&mut self, span: Span, task: &T, stamper: &OutputStamper) {}
+  /// End: required a task, resulting in a task `dependency` and `output`, and the task `was_executed`.
+  fn require_task_end(
+    &mut self,
+    span: Span,
+    dependency: &TaskDependency<T, T::Output>,
+    output: &T::Output,
+    was_executed: bool
+  ) {}
+
+  /// Start: check consistency of `dependency`.
+  fn check_dependency_start(&mut self, span: Span, dependency: &Dependency<T, T::Output>) {}
+  /// End: checked consistency of `dependency`, possibly found `inconsistency`.
+  fn check_dependency_end(
+    &mut self,
+    span: Span,
+    dependency: &Dependency<T, T::Output>,
+    inconsistency: Result<Option<&Inconsistency<T::Output>>, &io::Error>
+  ) {}
+
+  /// Start: execute `task`.
+  fn execute_start(&mut self, span: Span, task: &T) {}
+  /// End: executed `task` resulting in `output`, with the `dependencies` that `task` made while executing.
+  fn execute_end(&mut self, span: Span, task: &T, output: &T::Output, dependencies: &[&Dependency<T, T::Output>]) {}
+
+  /// Start: check determinism of `task` by executing it again.
+  fn check_determinism_start(&mut self, span: Span, task: &T) {}
+  /// End: checked determinism of `task`, possibly found `non_determinism`.
+  fn check_determinism_end(
+    &mut self,
+    span: Span,
+    task: &T,
+    non_determinism: Option<&NonDeterminism<T, T::Output>>
+  ) {}
+
+  /// Start: verify that files required during the build were not modified after they were required.
+  fn verify_files_start(&mut self, span: Span) {}
+  /// End: verified files required during the build, finding `modified_files`.
+  fn verify_files_end(&mut self, span: Span, modified_files: &[ModifiedFile<T>]) {}
+
+  /// Report `diagnostic` emitted by `task`, which is `replayed` from the stored output of `task` if it was not
+  /// executed.
+  fn report_diagnostic(&mut self, span: Span, task: &T, diagnostic: &Diagnostic, replayed: bool) {}
+}
+
+/// [`Tracker`] that does nothing.
+#[derive(Copy, Clone, Debug)]
+pub struct NoopTracker;
+impl<T: Task> Tracker<T> for NoopTracker {}
+
+/// [`Tracker`] that forwards build events to 2 trackers.
+#[derive(Copy, Clone, Debug)]
+pub struct CompositeTracker<A1, A2>(pub A1, pub A2);
+impl<T: Task, A1: Tracker<T>, A2: Tracker<T>> Tracker<T> for CompositeTracker<A1, A2> {
+  fn build_start(&mut self, span: Span) {
+    self.0.build_start(span);
+    self.1.build_start(span);
+  }
+  fn build_end(&mut self, span: Span) {
+    self.0.build_end(span);
+    self.1.build_end(span);
+  }
+
+  fn require_file_start(&mut self, span: Span, path: &Path, stamper: &FileStamper) {
+    self.0.require_file_start(span, path, stamper);
+    self.1.require_file_start(span, path, stamper);
+  }
+  fn require_file_end(
+    &mut self,
+    span: Span,
+    path: &Path,
+    stamper: &FileStamper,
+    dependency: Result<&FileDependency, &io::Error>
+  ) {
+    self.0.require_file_end(span, path, stamper, dependency);
+    self.1.require_file_end(span, path, stamper, dependency);
+  }
+  fn provide_file_start(&mut self, span: Span, path: &Path, stamper: &FileStamper) {
+    self.0.provide_file_start(span, path, stamper);
+    self.1.provide_file_start(span, path, stamper);
+  }
+  fn provide_file_end(
+    &mut self,
+    span: Span,
+    path: &Path,
+    stamper: &FileStamper,
+    dependency: Result<&FileDependency, &io::Error>
+  ) {
+    self.0.provide_file_end(span, path, stamper, dependency);
+    self.1.provide_file_end(span, path, stamper, dependency);
+  }
+  fn require_task_start(&mut self, span: Span, task: &T, stamper: &OutputStamper) {
+    self.0.require_task_start(span, task, stamper);
+    self.1.require_task_start(span, task, stamper);
+  }
+  fn require_task_end(
+    &mut self,
+    span: Span,
+    dependency: &TaskDependency<T, T::Output>,
+    output: &T::Output,
+    was_executed: bool
+  ) {
+    self.0.require_task_end(span, dependency, output, was_executed);
+    self.1.require_task_end(span, dependency, output, was_executed);
+  }
+
+  fn check_dependency_start(&mut self, span: Span, dependency: &Dependency<T, T::Output>) {
+    self.0.check_dependency_start(span, dependency);
+    self.1.check_dependency_start(span, dependency);
+  }
+  fn check_dependency_end(
+    &mut self,
+    span: Span,
+    dependency: &Dependency<T, T::Output>,
+    inconsistency: Result<Option<&Inconsistency<T::Output>>, &io::Error>
+  ) {
+    self.0.check_dependency_end(span, dependency, inconsistency);
+    self.1.check_dependency_end(span, dependency, inconsistency);
+  }
+
+  fn execute_start(&mut self, span: Span, task: &T) {
+    self.0.execute_start(span, task);
+    self.1.execute_start(span, task);
+  }
+  fn execute_end(&mut self, span: Span, task: &T, output: &T::Output, dependencies: &[&Dependency<T, T::Output>]) {
+    self.0.execute_end(span, task, output, dependencies);
+    self.1.execute_end(span, task, output, dependencies);
+  }
+
+  fn check_determinism_start(&mut self, span: Span, task: &T) {
+    self.0.check_determinism_start(span, task);
+    self.1.check_determinism_start(span, task);
+  }
+  fn check_determinism_end(
+    &mut self,
+    span: Span,
+    task: &T,
+    non_determinism: Option<&NonDeterminism<T, T::Output>>
+  ) {
+    self.0.check_determinism_end(span, task, non_determinism);
+    self.1.check_determinism_end(span, task, non_determinism);
+  }
+
+  fn verify_files_start(&mut self, span: Span) {
+    self.0.verify_files_start(span);
+    self.1.verify_files_start(span);
+  }
+  fn verify_files_end(&mut self, span: Span, modified_files: &[ModifiedFile<T>]) {
+    self.0.verify_files_end(span, modified_files);
+    self.1.verify_files_end(span, modified_files);
+  }
+
+  fn report_diagnostic(&mut self, span: Span, task: &T, diagnostic: &Diagnostic, replayed: bool) {
+    self.0.report_diagnostic(span, task, diagnostic, replayed);
+    self.1.report_diagnostic(span, task, diagnostic, replayed);
+  }
+}
+
+/// [`Tracker`] that forwards build events to a dynamic list of trackers. Trackers can be added and removed at runtime,
+/// for example between sessions via [`Pie::tracker_mut`](crate::Pie::tracker_mut).
+pub struct MultiTracker<T: Task> {
+  trackers: Vec<(TrackerId, Box<dyn Tracker<T>>)>,
+  next_id: usize,
+}
+
+/// Identifier of a tracker added to a [`MultiTracker`], used to remove it again.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TrackerId(usize);
+
+impl<T: Task> Default for MultiTracker<T> {
+  fn default() -> Self { Self { trackers: Vec::new(), next_id: 0 } }
+}
+
+impl<T: Task> MultiTracker<T> {
+  /// Adds `tracker` to the end of the list of trackers, returning the identifier of the added tracker.
+  pub fn add(&mut self, tracker: impl Tracker<T> + 'static) -> TrackerId {
+    self.add_boxed(Box::new(tracker))
+  }
+  /// Adds boxed `tracker` to the end of the list of trackers, returning the identifier of the added tracker.
+  pub fn add_boxed(&mut self, tracker: Box<dyn Tracker<T>>) -> TrackerId {
+    let id = TrackerId(self.next_id);
+    self.next_id += 1;
+    self.trackers.push((id, tracker));
+    id
+  }
+  /// Removes the tracker with identifier `id`, returning `Some(tracker)` if it was removed, or `None` if no tracker
+  /// with that identifier exists.
+  pub fn remove(&mut self, id: TrackerId) -> Option<Box<dyn Tracker<T>>> {
+    let index = self.trackers.iter().position(|(i, _)| *i == id)?;
+    Some(self.trackers.remove(index).1)
+  }
+  /// Checks whether a tracker with identifier `id` exists.
+  pub fn contains(&self, id: TrackerId) -> bool {
+    self.trackers.iter().any(|(i, _)| *i == id)
+  }
+  /// Removes all trackers.
+  pub fn clear(&mut self) {
+    self.trackers.clear();
+  }
+
+  /// Returns the number of trackers.
+  pub fn len(&self) -> usize { self.trackers.len() }
+  /// Returns `true` if there are no trackers.
+  pub fn is_empty(&self) -> bool { self.trackers.is_empty() }
+
+  fn iter_mut(&mut self) -> impl Iterator<Item=&mut Box<dyn Tracker<T>>> {
+    self.trackers.iter_mut().map(|(_, t)| t)
+  }
+}
+
+impl<T: Task> Tracker<T> for MultiTracker<T> {
+  fn build_start(&mut self, span: Span) {
+    self.iter_mut().for_each(|t| t.build_start(span));
+  }
+  fn build_end(&mut self, span: Span) {
+    self.iter_mut().for_each(|t| t.build_end(span));
+  }
+
+  fn require_file_start(&mut self, span: Span, path: &Path, stamper: &FileStamper) {
+    self.iter_mut().for_each(|t| t.require_file_start(span, path, stamper));
+  }
+  fn require_file_end(
+    &mut self,
+    span: Span,
+    path: &Path,
+    stamper: &FileStamper,
+    dependency: Result<&FileDependency, &io::Error>
+  ) {
+    self.iter_mut().for_each(|t| t.require_file_end(span, path, stamper, dependency));
+  }
+  fn provide_file_start(&mut self, span: Span, path: &Path, stamper: &FileStamper) {
+    self.iter_mut().for_each(|t| t.provide_file_start(span, path, stamper));
+  }
+  fn provide_file_end(
+    &mut self,
+    span: Span,
+    path: &Path,
+    stamper: &FileStamper,
+    dependency: Result<&FileDependency, &io::Error>
+  ) {
+    self.iter_mut().for_each(|t| t.provide_file_end(span, path, stamper, dependency));
+  }
+  fn require_task_start(&mut self, span: Span, task: &T, stamper: &OutputStamper) {
+    self.iter_mut().for_each(|t| t.require_task_start(span, task, stamper));
+  }
+  fn require_task_end(
+    &mut self,
+    span: Span,
+    dependency: &TaskDependency<T, T::Output>,
+    output: &T::Output,
+    was_executed: bool
+  ) {
+    self.iter_mut().for_each(|t| t.require_task_end(span, dependency, output, was_executed));
+  }
+
+  fn check_dependency_start(&mut self, span: Span, dependency: &Dependency<T, T::Output>) {
+    self.iter_mut().for_each(|t| t.check_dependency_start(span, dependency));
+  }
+  fn check_dependency_end(
+    &mut self,
+    span: Span,
+    dependency: &Dependency<T, T::Output>,
+    inconsistency: Result<Option<&Inconsistency<T::Output>>, &io::Error>
+  ) {
+    self.iter_mut().for_each(|t| t.check_dependency_end(span, dependency, inconsistency));
+  }
+
+  fn execute_start(&mut self, span: Span, task: &T) {
+    self.iter_mut().for_each(|t| t.execute_start(span, task));
+  }
+  fn execute_end(&mut self, span: Span, task: &T, output: &T::Output, dependencies: &[&Dependency<T, T::Output>]) {
+    self.iter_mut().for_each(|t| t.execute_end(span, task, output, dependencies));
+  }
+
+  fn check_determinism_start(&mut self, span: Span, task: &T) {
+    self.iter_mut().for_each(|t| t.check_determinism_start(span, task));
+  }
+  fn check_determinism_end(
+    &mut self,
+    span: Span,
+    task: &T,
+    non_determinism: Option<&NonDeterminism<T, T::Output>>
+  ) {
+    self.iter_mut().for_each(|t| t.check_determinism_end(span, task, non_determinism));
+  }
+
+  fn verify_files_start(&mut self, span: Span) {
+    self.iter_mut().for_each(|t| t.verify_files_start(span));
+  }
+  fn verify_files_end(&mut self, span: Span, modified_files: &[ModifiedFile<T>]) {
+    self.iter_mut().for_each(|t| t.verify_files_end(span, modified_files));
+  }
+
+  fn report_diagnostic(&mut self, span: Span, task: &T, diagnostic: &Diagnostic, replayed: bool) {
+    self.iter_mut().for_each(|t| t.report_diagnostic(span, task, diagnostic, replayed));
+  }
+}
+
+/// [`Tracker`] implementation for shared trackers, forwarding build events to the inner tracker. Useful for keeping a
+/// handle to a tracker that is added to a [`MultiTracker`], for example to inspect its state.
+impl<T: Task, A: Tracker<T>> Tracker<T> for Rc<RefCell<A>> {
+  fn build_start(&mut self, span: Span) {
+    self.borrow_mut().build_start(span);
+  }
+  fn build_end(&mut self, span: Span) {
+    self.borrow_mut().build_end(span);
+  }
+
+  fn require_file_start(&mut self, span: Span, path: &Path, stamper: &FileStamper) {
+    self.borrow_mut().require_file_start(span, path, stamper);
+  }
+  fn require_file_end(
+    &mut self,
+    span: Span,
+    path: &Path,
+    stamper: &FileStamper,
+    dependency: Result<&FileDependency, &io::Error>
+  ) {
+    self.borrow_mut().require_file_end(span, path, stamper, dependency);
+  }
+  fn provide_file_start(&mut self, span: Span, path: &Path, stamper: &FileStamper) {
+    self.borrow_mut().provide_file_start(span, path, stamper);
+  }
+  fn provide_file_end(
+    &mut self,
+    span: Span,
+    path: &Path,
+    stamper: &FileStamper,
+    dependency: Result<&FileDependency, &io::Error>
+  ) {
+    self.borrow_mut().provide_file_end(span, path, stamper, dependency);
+  }
+  fn require_task_start(&mut self, span: Span, task: &T, stamper: &OutputStamper) {
+    self.borrow_mut().require_task_start(span, task, stamper);
+  }
+  fn require_task_end(
+    &mut self,
+    span: Span,
+    dependency: &TaskDependency<T, T::Output>,
+    output: &T::Output,
+    was_executed: bool
+  ) {
+    self.borrow_mut().require_task_end(span, dependency, output, was_executed);
+  }
+
+  fn check_dependency_start(&mut self, span: Span, dependency: &Dependency<T, T::Output>) {
+    self.borrow_mut().check_dependency_start(span, dependency);
+  }
+  fn check_dependency_end(
+    &mut self,
+    span: Span,
+    dependency: &Dependency<T, T::Output>,
+    inconsistency: Result<Option<&Inconsistency<T::Output>>, &io::Error>
+  ) {
+    self.borrow_mut().check_dependency_end(span, dependency, inconsistency);
+  }
+
+  fn execute_start(&mut self, span: Span, task: &T) {
+    self.borrow_mut().execute_start(span, task);
+  }
+  fn execute_end(&mut self, span: Span, task: &T, output: &T::Output, dependencies: &[&Dependency<T, T::Output>]) {
+    self.borrow_mut().execute_end(span, task, output, dependencies);
+  }
+
+  fn check_determinism_start(&mut self, span: Span, task: &T) {
+    self.borrow_mut().check_determinism_start(span, task);
+  }
+  fn check_determinism_end(
+    &mut self,
+    span: Span,
+    task: &T,
+    non_determinism: Option<&NonDeterminism<T, T::Output>>
+  ) {
+    self.borrow_mut().check_determinism_end(span, task, non_determinism);
+  }
+
+  fn verify_files_start(&mut self, span: Span) {
+    self.borrow_mut().verify_files_start(span);
+  }
+  fn verify_files_end(&mut self, span: Span, modified_files: &[ModifiedFile<T>]) {
+    self.borrow_mut().verify_files_end(span, modified_files);
+  }
+
+  fn report_diagnostic(&mut self, span: Span, task: &T, diagnostic: &Diagnostic, replayed: bool) {
+    self.borrow_mut().report_diagnostic(span, task, diagnostic, replayed);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{Context, Pie};
+  use crate::tracker::event::EventTracker;
+
+  use super::*;
+
+  /// Task that returns its owned string.
+  #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+  struct StringConstant(String);
+
+  impl Task for StringConstant {
+    type Output = String;
+    fn execute<C: Context<Self>>(&self, _context: &mut C) -> Self::Output {
+      self.0.clone()
+    }
+  }
+
+  #[test]
+  fn test_multi_tracker_forwards_events() {
+    let tracker_1 = Rc::new(RefCell::new(EventTracker::default()));
+    let tracker_2 = Rc::new(RefCell::new(EventTracker::default()));
+    let mut multi_tracker = MultiTracker::default();
+    multi_tracker.add(tracker_1.clone());
+    multi_tracker.add(tracker_2.clone());
+    assert_eq!(multi_tracker.len(), 2);
+
+    let mut pie = Pie::with_tracker(multi_tracker);
+    let task = StringConstant("Hello, World!".to_string());
+    pie.new_session().require(&task);
+    assert!(tracker_1.borrow().one_execute_of(&task));
+    assert!(tracker_2.borrow().one_execute_of(&task));
+  }
+
+  #[test]
+  fn test_multi_tracker_add_remove_between_sessions() {
+    let mut pie = Pie::with_tracker(MultiTracker::default());
+    let task = StringConstant("Hello, World!".to_string());
+    assert!(pie.tracker().is_empty());
+    pie.new_session().require(&task);
+
+    let event_tracker = Rc::new(RefCell::new(EventTracker::default()));
+    let id = pie.tracker_mut().add(event_tracker.clone());
+    assert!(pie.tracker().contains(id));
+    pie.new_session().require(&task);
+    assert!(!event_tracker.borrow().slice().is_empty());
+    assert!(!event_tracker.borrow().any_execute_of(&task));
+
+    assert!(pie.tracker_mut().remove(id).is_some());
+    assert!(!pie.tracker().contains(id));
+    assert!(pie.tracker_mut().remove(id).is_none());
+    let events_before = event_tracker.borrow().slice().len();
+    pie.new_session().require(&task);
+    assert_eq!(events_before, event_tracker.borrow().slice().len());
+  }
+
+  #[test]
+  fn test_multi_tracker_ids_are_unique() {
+    let mut multi_tracker = MultiTracker::<StringConstant>::default();
+    let id_1 = multi_tracker.add(NoopTracker);
+    multi_tracker.remove(id_1);
+    let id_2 = multi_tracker.add(NoopTracker);
+    assert_ne!(id_1, id_2);
+    assert!(!multi_tracker.contains(id_1));
+    multi_tracker.clear();
+    assert!(multi_tracker.is_empty());
+  }
+}
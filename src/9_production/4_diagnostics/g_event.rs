@@ -0,0 +1,735 @@
+use std::io;
+#[cfg(feature = "serde")]
+use std::io::{BufRead, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use crate::dependency::{Dependency, FileDependency, Inconsistency, ModifiedFile, TaskDependency};
+use crate::determinism::NonDeterminism;
+use crate::diagnostic::Diagnostic;
+use crate::stamp::{FileStamp, FileStamper, OutputStamp, OutputStamper};
+use crate::Task;
+use crate::tracker::{Span, Tracker};
+
+/// [`Tracker`] that stores [events](Event) in a [`Vec`], useful in testing to assert that a context implementation is
+/// incremental and sound.
+#[derive(Clone, Debug)]
+pub struct EventTracker<T, O> {
+  events: Vec<Event<T, O>>,
+}
+
+impl<T: Task> Default for EventTracker<T, T::Output> {
+  fn default() -> Self { Self { events: Vec::new() } }
+}
+
+/// Enumeration of important build events.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event<T, O> {
+  BuildStart(BuildStart),
+  BuildEnd(BuildEnd),
+
+  RequireFileStart(FileDependencyStart),
+  RequireFileEnd(FileDependencyEnd),
+  ProvideFileStart(FileDependencyStart),
+  ProvideFileEnd(FileDependencyEnd),
+
+  RequireTaskStart(RequireTaskStart<T>),
+  RequireTaskEnd(RequireTaskEnd<T, O>),
+
+  CheckDependencyStart(CheckDependencyStart<T, O>),
+  CheckDependencyEnd(CheckDependencyEnd<T, O>),
+
+  ExecuteStart(ExecuteStart<T>),
+  ExecuteEnd(ExecuteEnd<T, O>),
+
+  CheckDeterminismStart(CheckDeterminismStart<T>),
+  CheckDeterminismEnd(CheckDeterminismEnd<T, O>),
+
+  VerifyFilesStart(VerifyFilesStart),
+  VerifyFilesEnd(VerifyFilesEnd<T>),
+
+  ReportDiagnostic(ReportDiagnostic<T>),
+}
+
+/// Start: a new build.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BuildStart {
+  pub span: Span,
+  pub index: usize,
+}
+/// End: completed build.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BuildEnd {
+  pub span: Span,
+  pub index: usize,
+}
+/// Start: require/provide file at `path` using `stamper`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileDependencyStart {
+  pub span: Span,
+  pub path: PathBuf,
+  pub stamper: FileStamper,
+  pub index: usize,
+}
+/// End: required/provided file at `path` using `stamper` to create `stamp`, or `Err(message)` if creating the
+/// dependency failed.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileDependencyEnd {
+  pub span: Span,
+  pub path: PathBuf,
+  pub stamper: FileStamper,
+  pub stamp: Result<FileStamp, String>,
+  pub index: usize,
+}
+/// Start: require `task` using `stamper`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RequireTaskStart<T> {
+  pub span: Span,
+  pub task: T,
+  pub stamper: OutputStamper,
+  pub index: usize,
+}
+/// End: required `task` resulting in `output`, using `stamper` to create `stamp`, and the task `was_executed`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RequireTaskEnd<T, O> {
+  pub span: Span,
+  pub task: T,
+  pub stamper: OutputStamper,
+  pub stamp: OutputStamp<O>,
+  pub output: O,
+  pub was_executed: bool,
+  pub index: usize,
+}
+/// Start: check consistency of `dependency`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CheckDependencyStart<T, O> {
+  pub span: Span,
+  pub dependency: Dependency<T, O>,
+  pub index: usize,
+}
+/// End: checked consistency of `dependency`, possibly finding `inconsistency`, or `Err(message)` if checking failed.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CheckDependencyEnd<T, O> {
+  pub span: Span,
+  pub dependency: Dependency<T, O>,
+  pub inconsistency: Result<Option<Inconsistency<O>>, String>,
+  pub index: usize,
+}
+/// Start: execute `task`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecuteStart<T> {
+  pub span: Span,
+  pub task: T,
+  pub index: usize,
+}
+/// End: executed `task`, producing `output`, with the `dependencies` that `task` made while executing.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecuteEnd<T, O> {
+  pub span: Span,
+  pub task: T,
+  pub output: O,
+  pub dependencies: Vec<Dependency<T, O>>,
+  pub index: usize,
+}
+/// Start: check determinism of `task` by executing it again.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CheckDeterminismStart<T> {
+  pub span: Span,
+  pub task: T,
+  pub index: usize,
+}
+/// End: checked determinism of `task`, possibly finding `non_determinism`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CheckDeterminismEnd<T, O> {
+  pub span: Span,
+  pub task: T,
+  pub non_determinism: Option<NonDeterminism<T, O>>,
+  pub index: usize,
+}
+/// Start: verify that files required during the build were not modified after they were required.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VerifyFilesStart {
+  pub span: Span,
+  pub index: usize,
+}
+/// End: verified files required during the build, finding `modified_files`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VerifyFilesEnd<T> {
+  pub span: Span,
+  pub modified_files: Vec<ModifiedFile<T>>,
+  pub index: usize,
+}
+/// Report `diagnostic` emitted by `task`, which is `replayed` from the stored output of `task` if it was not executed.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReportDiagnostic<T> {
+  pub span: Span,
+  pub task: T,
+  pub diagnostic: Diagnostic,
+  pub replayed: bool,
+  pub index: usize,
+}
+
+impl<T: Task> Tracker<T> for EventTracker<T, T::Output> {
+  fn build_start(&mut self, span: Span) {
+    self.events.clear();
+    let data = BuildStart { span, index: self.events.len() };
+    self.events.push(Event::BuildStart(data));
+  }
+  fn build_end(&mut self, span: Span) {
+    let data = BuildEnd { span, index: self.events.len() };
+    self.events.push(Event::BuildEnd(data));
+  }
+
+  fn require_file_start(&mut self, span: Span, path: &Path, stamper: &FileStamper) {
+    let data = FileDependencyStart { span, path: path.into(), stamper: *stamper, index: self.events.len() };
+    self.events.push(Event::RequireFileStart(data));
+  }
+  fn require_file_end(
+    &mut self,
+    span: Span,
+    path: &Path,
+    stamper: &FileStamper,
+    dependency: Result<&FileDependency, &io::Error>
+  ) {
+    let data = FileDependencyEnd {
+      span,
+      path: path.into(),
+      stamper: *stamper,
+      stamp: dependency.map(|d| *d.stamp()).map_err(|e| e.to_string()),
+      index: self.events.len()
+    };
+    self.events.push(Event::RequireFileEnd(data));
+  }
+  fn provide_file_start(&mut self, span: Span, path: &Path, stamper: &FileStamper) {
+    let data = FileDependencyStart { span, path: path.into(), stamper: *stamper, index: self.events.len() };
+    self.events.push(Event::ProvideFileStart(data));
+  }
+  fn provide_file_end(
+    &mut self,
+    span: Span,
+    path: &Path,
+    stamper: &FileStamper,
+    dependency: Result<&FileDependency, &io::Error>
+  ) {
+    let data = FileDependencyEnd {
+      span,
+      path: path.into(),
+      stamper: *stamper,
+      stamp: dependency.map(|d| *d.stamp()).map_err(|e| e.to_string()),
+      index: self.events.len()
+    };
+    self.events.push(Event::ProvideFileEnd(data));
+  }
+  fn require_task_start(&mut self, span: Span, task: &T, stamper: &OutputStamper) {
+    let data = RequireTaskStart { span, task: task.clone(), stamper: *stamper, index: self.events.len() };
+    self.events.push(Event::RequireTaskStart(data));
+  }
+  fn require_task_end(
+    &mut self,
+    span: Span,
+    dependency: &TaskDependency<T, T::Output>,
+    output: &T::Output,
+    was_executed: bool
+  ) {
+    let data = RequireTaskEnd {
+      span,
+      task: dependency.task().clone(),
+      stamper: *dependency.stamper(),
+      stamp: dependency.stamp().clone(),
+      output: output.clone(),
+      was_executed,
+      index: self.events.len()
+    };
+    self.events.push(Event::RequireTaskEnd(data));
+  }
+
+  fn check_dependency_start(&mut self, span: Span, dependency: &Dependency<T, T::Output>) {
+    let data = CheckDependencyStart { span, dependency: dependency.clone(), index: self.events.len() };
+    self.events.push(Event::CheckDependencyStart(data));
+  }
+  fn check_dependency_end(
+    &mut self,
+    span: Span,
+    dependency: &Dependency<T, T::Output>,
+    inconsistency: Result<Option<&Inconsistency<T::Output>>, &io::Error>
+  ) {
+    let data = CheckDependencyEnd {
+      span,
+      dependency: dependency.clone(),
+      inconsistency: inconsistency.map(|i| i.cloned()).map_err(|e| e.to_string()),
+      index: self.events.len()
+    };
+    self.events.push(Event::CheckDependencyEnd(data));
+  }
+
+  fn execute_start(&mut self, span: Span, task: &T) {
+    let data = ExecuteStart { span, task: task.clone(), index: self.events.len() };
+    self.events.push(Event::ExecuteStart(data));
+  }
+  fn execute_end(&mut self, span: Span, task: &T, output: &T::Output, dependencies: &[&Dependency<T, T::Output>]) {
+    let data = ExecuteEnd {
+      span,
+      task: task.clone(),
+      output: output.clone(),
+      dependencies: dependencies.iter().map(|d| (*d).clone()).collect(),
+      index: self.events.len()
+    };
+    self.events.push(Event::ExecuteEnd(data));
+  }
+
+  fn check_determinism_start(&mut self, span: Span, task: &T) {
+    let data = CheckDeterminismStart { span, task: task.clone(), index: self.events.len() };
+    self.events.push(Event::CheckDeterminismStart(data));
+  }
+  fn check_determinism_end(
+    &mut self,
+    span: Span,
+    task: &T,
+    non_determinism: Option<&NonDeterminism<T, T::Output>>
+  ) {
+    let data = CheckDeterminismEnd {
+      span,
+      task: task.clone(),
+      non_determinism: non_determinism.cloned(),
+      index: self.events.len()
+    };
+    self.events.push(Event::CheckDeterminismEnd(data));
+  }
+
+  fn verify_files_start(&mut self, span: Span) {
+    let data = VerifyFilesStart { span, index: self.events.len() };
+    self.events.push(Event::VerifyFilesStart(data));
+  }
+  fn verify_files_end(&mut self, span: Span, modified_files: &[ModifiedFile<T>]) {
+    let data = VerifyFilesEnd { span, modified_files: modified_files.to_vec(), index: self.events.len() };
+    self.events.push(Event::VerifyFilesEnd(data));
+  }
+
+  fn report_diagnostic(&mut self, span: Span, task: &T, diagnostic: &Diagnostic, replayed: bool) {
+    let data = ReportDiagnostic {
+      span,
+      task: task.clone(),
+      diagnostic: diagnostic.clone(),
+      replayed,
+      index: self.events.len()
+    };
+    self.events.push(Event::ReportDiagnostic(data));
+  }
+}
+
+impl<T, O> Event<T, O> {
+  /// Returns the [`Span`] of this event.
+  pub fn span(&self) -> Span {
+    match self {
+      Event::BuildStart(d) => d.span,
+      Event::BuildEnd(d) => d.span,
+      Event::RequireFileStart(d) | Event::ProvideFileStart(d) => d.span,
+      Event::RequireFileEnd(d) | Event::ProvideFileEnd(d) => d.span,
+      Event::RequireTaskStart(d) => d.span,
+      Event::RequireTaskEnd(d) => d.span,
+      Event::CheckDependencyStart(d) => d.span,
+      Event::CheckDependencyEnd(d) => d.span,
+      Event::ExecuteStart(d) => d.span,
+      Event::ExecuteEnd(d) => d.span,
+      Event::CheckDeterminismStart(d) => d.span,
+      Event::CheckDeterminismEnd(d) => d.span,
+      Event::VerifyFilesStart(d) => d.span,
+      Event::VerifyFilesEnd(d) => d.span,
+      Event::ReportDiagnostic(d) => d.span,
+    }
+  }
+  /// Returns the index of this event.
+  pub fn index(&self) -> usize {
+    match self {
+      Event::BuildStart(d) => d.index,
+      Event::BuildEnd(d) => d.index,
+      Event::RequireFileStart(d) | Event::ProvideFileStart(d) => d.index,
+      Event::RequireFileEnd(d) | Event::ProvideFileEnd(d) => d.index,
+      Event::RequireTaskStart(d) => d.index,
+      Event::RequireTaskEnd(d) => d.index,
+      Event::CheckDependencyStart(d) => d.index,
+      Event::CheckDependencyEnd(d) => d.index,
+      Event::ExecuteStart(d) => d.index,
+      Event::ExecuteEnd(d) => d.index,
+      Event::CheckDeterminismStart(d) => d.index,
+      Event::CheckDeterminismEnd(d) => d.index,
+      Event::VerifyFilesStart(d) => d.index,
+      Event::VerifyFilesEnd(d) => d.index,
+      Event::ReportDiagnostic(d) => d.index,
+    }
+  }
+  /// Returns `true` if this is a start event, or `false` if this is an end event or a
+  /// [report diagnostic event](Event::ReportDiagnostic).
+  pub fn is_start(&self) -> bool {
+    matches!(self, Event::BuildStart(_) | Event::RequireFileStart(_) | Event::ProvideFileStart(_) |
+      Event::RequireTaskStart(_) | Event::CheckDependencyStart(_) | Event::ExecuteStart(_) |
+      Event::CheckDeterminismStart(_) | Event::VerifyFilesStart(_))
+  }
+}
+
+impl<T: Task> Event<T, T::Output> {
+  /// Returns `Some(&data)` if this is a [require file end event](Event::RequireFileEnd) for file at `path`, or `None`
+  /// otherwise.
+  pub fn match_require_file_end(&self, path: impl AsRef<Path>) -> Option<&FileDependencyEnd> {
+    let path = path.as_ref();
+    match self {
+      Event::RequireFileEnd(data) if data.path == path => Some(data),
+      _ => None,
+    }
+  }
+  /// Returns `Some(&data)` if this is a [provide file end event](Event::ProvideFileEnd) for file at `path`, or `None`
+  /// otherwise.
+  pub fn match_provide_file_end(&self, path: impl AsRef<Path>) -> Option<&FileDependencyEnd> {
+    let path = path.as_ref();
+    match self {
+      Event::ProvideFileEnd(data) if data.path == path => Some(data),
+      _ => None,
+    }
+  }
+
+  /// Returns `Some(&data)` if this is a [require task start event](Event::RequireTaskStart) for `task`, or `None`
+  /// otherwise.
+  pub fn match_require_task_start(&self, task: &T) -> Option<&RequireTaskStart<T>> {
+    match self {
+      Event::RequireTaskStart(data) if data.task == *task => Some(data),
+      _ => None,
+    }
+  }
+  /// Returns `Some(&data)` if this is a [require task start event](Event::RequireTaskStart) for `task`, or `None`
+  /// otherwise.
+  pub fn match_require_task_end(&self, task: &T) -> Option<&RequireTaskEnd<T, T::Output>> {
+    match self {
+      Event::RequireTaskEnd(data) if data.task == *task => Some(data),
+      _ => None,
+    }
+  }
+
+  /// Returns `true` if this is a task execute [start](Event::ExecuteStart) or [end](Event::ExecuteEnd) event.
+  pub fn is_execute(&self) -> bool {
+    match self {
+      Event::ExecuteStart(_) | Event::ExecuteEnd(_) => true,
+      _ => false,
+    }
+  }
+  /// Returns `true` if this is an execute [start](Event::ExecuteStart) or [end](Event::ExecuteEnd) event for `task`.
+  pub fn is_execute_of(&self, task: &T) -> bool {
+    match self {
+      Event::ExecuteStart(ExecuteStart { task: t, .. }) |
+      Event::ExecuteEnd(ExecuteEnd { task: t, .. }) if t == task => true,
+      _ => false,
+    }
+  }
+  /// Returns `Some(&data)` if this is a [task execute start event](Event::ExecuteStart) for `task`, or `None`
+  /// otherwise.
+  pub fn match_execute_start(&self, task: &T) -> Option<&ExecuteStart<T>> {
+    match self {
+      Event::ExecuteStart(data) if data.task == *task => Some(data),
+      _ => None,
+    }
+  }
+  /// Returns `Some(&data)` if this is a [task execute end event](Event::ExecuteStart) for `task`, or `None` otherwise.
+  pub fn match_execute_end(&self, task: &T) -> Option<&ExecuteEnd<T, T::Output>> {
+    match self {
+      Event::ExecuteEnd(data) if data.task == *task => Some(data),
+      _ => None,
+    }
+  }
+}
+
+impl<T: Task> EventTracker<T, T::Output> {
+  /// Returns a slice over all events.
+  pub fn slice(&self) -> &[Event<T, T::Output>] {
+    &self.events
+  }
+  /// Returns an iterator over all events.
+  pub fn iter(&self) -> impl Iterator<Item=&Event<T, T::Output>> {
+    self.events.iter()
+  }
+
+  /// Returns `true` if `predicate` returns `true` for any event.
+  pub fn any(&self, predicate: impl FnMut(&Event<T, T::Output>) -> bool) -> bool {
+    self.iter().any(predicate)
+  }
+  /// Returns `true` if `predicate` returns `true` for exactly one event.
+  pub fn one(&self, predicate: impl FnMut(&&Event<T, T::Output>) -> bool) -> bool {
+    self.iter().filter(predicate).count() == 1
+  }
+
+  /// Returns `Some(v)` for the first event `e` where `f(e)` returns `Some(v)`, or `None` otherwise.
+  pub fn find_map<R>(&self, f: impl FnMut(&Event<T, T::Output>) -> Option<&R>) -> Option<&R> {
+    self.iter().find_map(f)
+  }
+
+
+  /// Finds the first [require file end event](Event::RequireFileEnd) for `path` and returns `Some(&data)`, or `None`
+  /// otherwise.
+  pub fn first_require_file(&self, path: &PathBuf) -> Option<&FileDependencyEnd> {
+    self.find_map(|e| e.match_require_file_end(path))
+  }
+  /// Finds the first [require file end event](Event::RequireFileEnd) for `path` and returns `Some(&index)`, or `None`
+  /// otherwise.
+  pub fn first_require_file_index(&self, path: &PathBuf) -> Option<&usize> {
+    self.first_require_file(path).map(|d| &d.index)
+  }
+  /// Finds the first [provide file end event](Event::ProvideFileEnd) for `path` and returns `Some(&data)`, or `None`
+  /// otherwise.
+  pub fn first_provide_file(&self, path: &PathBuf) -> Option<&FileDependencyEnd> {
+    self.find_map(|e| e.match_provide_file_end(path))
+  }
+  /// Finds the first [provide file end event](Event::ProvideFileEnd) for `path` and returns `Some(&index)`, or `None`
+  /// otherwise.
+  pub fn first_provide_file_index(&self, path: &PathBuf) -> Option<&usize> {
+    self.first_provide_file(path).map(|d| &d.index)
+  }
+
+  /// Finds the first require [start](Event::RequireTaskStart) and [end](Event::RequireTaskEnd) event for `task` and
+  /// returns `Some((&start_data, &end_data))`, or `None` otherwise.
+  pub fn first_require_task(&self, task: &T) -> Option<(&RequireTaskStart<T>, &RequireTaskEnd<T, T::Output>)> {
+    let start_data = self.find_map(|e| e.match_require_task_start(task));
+    let end_data = self.find_map(|e| e.match_require_task_end(task));
+    start_data.zip(end_data)
+  }
+  /// Finds the first require [start](Event::RequireTaskStart) and [end](Event::RequireTaskEnd) event for `task` and
+  /// returns `Some(start_data.index..=end_data.index)`, or `None` otherwise.
+  pub fn first_require_task_range(&self, task: &T) -> Option<RangeInclusive<usize>> {
+    self.first_require_task(task).map(|(s, e)| s.index..=e.index)
+  }
+
+  /// Returns `true` if any task was executed.
+  pub fn any_execute(&self) -> bool {
+    self.any(|e| e.is_execute())
+  }
+  /// Returns `true` if `task` was executed.
+  pub fn any_execute_of(&self, task: &T) -> bool {
+    self.any(|e| e.is_execute_of(task))
+  }
+  /// Returns `true` if `task` was executed exactly once.
+  pub fn one_execute_of(&self, task: &T) -> bool {
+    self.one(|e| e.match_execute_start(task).is_some())
+  }
+
+  /// Finds the first execute [start](Event::ExecuteStart) and [end](Event::ExecuteEnd) event for `task` and returns
+  /// `Some((&start_data, &end_data))`, or `None` otherwise.
+  pub fn first_execute(&self, task: &T) -> Option<(&ExecuteStart<T>, &ExecuteEnd<T, T::Output>)> {
+    let start_data = self.find_map(|e| e.match_execute_start(task));
+    let end_data = self.find_map(|e| e.match_execute_end(task));
+    start_data.zip(end_data)
+  }
+  /// Finds the first execute [start](Event::ExecuteStart) and [end](Event::ExecuteEnd) event for `task` and returns
+  /// `Some(start_data.index..=end_data.index)`, or `None` otherwise.
+  pub fn first_execute_range(&self, task: &T) -> Option<RangeInclusive<usize>> {
+    self.first_execute(task).map(|(s, e)| s.index..=e.index)
+  }
+
+  /// Returns all executed tasks, in the order in which they started executing. A task that was executed multiple times
+  /// is returned multiple times.
+  pub fn executed_tasks(&self) -> Vec<&T> {
+    self.iter().filter_map(|e| match e {
+      Event::ExecuteStart(data) => Some(&data.task),
+      _ => None,
+    }).collect()
+  }
+  /// Returns the number of times `task` was executed.
+  pub fn count_executes_of(&self, task: &T) -> usize {
+    self.iter().filter(|e| e.match_execute_start(task).is_some()).count()
+  }
+  /// Returns `true` if the first execution of `inner` happened during the first execution of `outer`, or `false` if
+  /// not or if either task was not executed.
+  pub fn execution_nested_in(&self, inner: &T, outer: &T) -> bool {
+    let (Some(inner), Some(outer)) = (self.first_execute_range(inner), self.first_execute_range(outer)) else {
+      return false;
+    };
+    inner.start() > outer.start() && outer.end() > inner.end()
+  }
+  /// Returns `true` if file at `path` was first required before `task` first started executing, or `false` if not or if
+  /// the file was not required or the task was not executed.
+  pub fn required_before(&self, path: &PathBuf, task: &T) -> bool {
+    let (Some(file_require), Some(execute)) = (self.first_require_file_index(path), self.first_execute_range(task)) else {
+      return false;
+    };
+    execute.start() > file_require
+  }
+
+  /// Asserts that exactly `tasks` were executed, each exactly once, in any order. On failure, panics with the events of
+  /// the offending executions, or all events if an expected task was not executed.
+  pub fn assert_executed_exactly(&self, tasks: &[&T]) {
+    for task in tasks {
+      match self.count_executes_of(task) {
+        1 => {}
+        0 => panic!("expected one execution of task {:?}, but it was not executed\n{}", task,
+          self.format_all()),
+        n => panic!("expected one execution of task {:?}, but it was executed {} times\n{}", task, n,
+          self.format_execute_windows(task)),
+      }
+    }
+    for task in self.executed_tasks() {
+      if !tasks.contains(&task) {
+        panic!("expected no execution of task {:?}, but it was executed\n{}", task, self.format_execute_windows(task));
+      }
+    }
+  }
+  /// Asserts that the first execution of `inner` happened during the first execution of `outer`. On failure, panics
+  /// with the events from the start of the earliest execution to the end of the latest execution.
+  pub fn assert_execution_nested_in(&self, inner: &T, outer: &T) {
+    if self.execution_nested_in(inner, outer) { return; }
+    let message = format!("expected execution of task {:?} to be nested in execution of task {:?}, but it was not",
+      inner, outer);
+    match (self.first_execute_range(inner), self.first_execute_range(outer)) {
+      (Some(i), Some(o)) => {
+        let window = *i.start().min(o.start())..=*i.end().max(o.end());
+        panic!("{}\n{}", message, self.format_window(window))
+      }
+      _ => panic!("{}, because at least one of the tasks was not executed\n{}", message,
+        self.format_all()),
+    }
+  }
+  /// Asserts that file at `path` was first required before `task` first started executing. On failure, panics with the
+  /// events from the first require of the file to the start of the execution of the task.
+  pub fn assert_required_before(&self, path: &PathBuf, task: &T) {
+    if self.required_before(path, task) { return; }
+    let message = format!("expected file {} to be required before execution of task {:?}, but it was not",
+      path.display(), task);
+    match (self.first_require_file_index(path), self.first_execute_range(task)) {
+      (Some(r), Some(e)) => {
+        let window = *r.min(e.start())..=*r.max(e.start());
+        panic!("{}\n{}", message, self.format_window(window))
+      }
+      _ => panic!("{}, because the file was not required or the task was not executed\n{}", message,
+        self.format_all()),
+    }
+  }
+
+  /// Formats the events of all executions of `task`.
+  fn format_execute_windows(&self, task: &T) -> String {
+    let mut string = String::new();
+    let mut start = None;
+    for event in self.iter() {
+      if let Some(data) = event.match_execute_start(task) {
+        start = Some(data.index);
+      } else if let (Some(data), Some(s)) = (event.match_execute_end(task), start) {
+        string.push_str(&self.format_window(s..=data.index));
+        start = None;
+      }
+    }
+    string
+  }
+  /// Formats all events, one event per line.
+  fn format_all(&self) -> String {
+    self.format_window(0..=self.events.len().saturating_sub(1))
+  }
+  /// Formats the events in `window`, along with a couple of surrounding events for context, one event per line.
+  fn format_window(&self, window: RangeInclusive<usize>) -> String {
+    const CONTEXT: usize = 2;
+    let start = window.start().saturating_sub(CONTEXT);
+    let end = (window.end() + CONTEXT).min(self.events.len().saturating_sub(1));
+    let mut string = String::new();
+    for event in self.events.get(start..=end).unwrap_or_default() {
+      let marker = if window.contains(&event.index()) { ">" } else { " " };
+      string.push_str(&format!("{} {:>4}: {:?}\n", marker, event.index(), event));
+    }
+    string
+  }
+}
+
+impl<T: Task> EventTracker<T, T::Output> {
+  /// Replays all events to `tracker`.
+  pub fn replay(&self, tracker: &mut impl Tracker<T>) {
+    for event in self.iter() {
+      event.replay(tracker);
+    }
+  }
+}
+
+impl<T: Task> Event<T, T::Output> {
+  /// Replays this event to `tracker` by calling the corresponding tracker method.
+  pub fn replay(&self, tracker: &mut impl Tracker<T>) {
+    let to_error = |message: &String| io::Error::other(message.as_str());
+    match self {
+      Event::BuildStart(d) => tracker.build_start(d.span),
+      Event::BuildEnd(d) => tracker.build_end(d.span),
+      Event::RequireFileStart(d) => tracker.require_file_start(d.span, &d.path, &d.stamper),
+      Event::RequireFileEnd(d) => {
+        let dependency = d.stamp.as_ref().map(|s| FileDependency::with_stamp(&d.path, d.stamper, *s)).map_err(to_error);
+        tracker.require_file_end(d.span, &d.path, &d.stamper, dependency.as_ref());
+      }
+      Event::ProvideFileStart(d) => tracker.provide_file_start(d.span, &d.path, &d.stamper),
+      Event::ProvideFileEnd(d) => {
+        let dependency = d.stamp.as_ref().map(|s| FileDependency::with_stamp(&d.path, d.stamper, *s)).map_err(to_error);
+        tracker.provide_file_end(d.span, &d.path, &d.stamper, dependency.as_ref());
+      }
+      Event::RequireTaskStart(d) => tracker.require_task_start(d.span, &d.task, &d.stamper),
+      Event::RequireTaskEnd(d) => {
+        let dependency = TaskDependency::new(d.task.clone(), d.stamper, d.output.clone());
+        tracker.require_task_end(d.span, &dependency, &d.output, d.was_executed);
+      }
+      Event::CheckDependencyStart(d) => tracker.check_dependency_start(d.span, &d.dependency),
+      Event::CheckDependencyEnd(d) => {
+        let inconsistency = d.inconsistency.as_ref().map_err(to_error);
+        tracker.check_dependency_end(d.span, &d.dependency, inconsistency.as_ref().map(|i| i.as_ref()));
+      }
+      Event::ExecuteStart(d) => tracker.execute_start(d.span, &d.task),
+      Event::ExecuteEnd(d) => {
+        let dependencies: Vec<_> = d.dependencies.iter().collect();
+        tracker.execute_end(d.span, &d.task, &d.output, &dependencies);
+      }
+      Event::CheckDeterminismStart(d) => tracker.check_determinism_start(d.span, &d.task),
+      Event::CheckDeterminismEnd(d) => tracker.check_determinism_end(d.span, &d.task, d.non_determinism.as_ref()),
+      Event::VerifyFilesStart(d) => tracker.verify_files_start(d.span),
+      Event::VerifyFilesEnd(d) => tracker.verify_files_end(d.span, &d.modified_files),
+      Event::ReportDiagnostic(d) => tracker.report_diagnostic(d.span, &d.task, &d.diagnostic, d.replayed),
+    }
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Task + serde::Serialize, O: serde::Serialize> EventTracker<T, O> {
+  /// Writes all events to `writer` in the [JSON lines](https://jsonlines.org/) format: one JSON object per event, each
+  /// on a separate line.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if serializing an event fails, or if writing to `writer` fails.
+  pub fn write_json_lines(&self, mut writer: impl Write) -> Result<(), io::Error> {
+    for event in &self.events {
+      serde_json::to_writer(&mut writer, event)?;
+      writeln!(writer)?;
+    }
+    writer.flush()
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Task + serde::de::DeserializeOwned, O: serde::de::DeserializeOwned> EventTracker<T, O> {
+  /// Reads events from `reader` in the [JSON lines](https://jsonlines.org/) format, as written by
+  /// [`write_json_lines`](Self::write_json_lines), and creates an [`EventTracker`] with those events. Empty lines are
+  /// skipped.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if reading from `reader` fails, or if deserializing an event fails.
+  pub fn read_json_lines(reader: impl BufRead) -> Result<Self, io::Error> {
+    let mut events = Vec::new();
+    for line in reader.lines() {
+      let line = line?;
+      if line.trim().is_empty() { continue; }
+      events.push(serde_json::from_str(&line)?);
+    }
+    Ok(Self { events })
+  }
+}
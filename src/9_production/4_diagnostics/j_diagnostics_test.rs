@@ -0,0 +1,108 @@
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use assert_matches::assert_matches;
+
+use pie::{Context, Task};
+use pie::diagnostic::{Diagnostic, Location, Severity};
+use pie::fs::FileSystem;
+use pie::tracker::event::{Event, ReportDiagnostic};
+
+use crate::common::{test_pie, TestPie, TestPieExt};
+
+mod common;
+
+/// Tasks that lint files, emitting a warning for every line that contains `TODO`.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+enum Lint {
+  /// Lints file at given path, returning its number of lines.
+  File(PathBuf),
+  /// Lints all files, returning the total number of lines.
+  All(Vec<Lint>),
+}
+
+impl Task for Lint {
+  type Output = Result<usize, io::ErrorKind>;
+  fn execute<C: Context<Self>>(&self, context: &mut C) -> Self::Output {
+    match self {
+      Lint::File(path) => {
+        let mut string = String::new();
+        if let Some(mut file) = context.require_file(path).map_err(|e| e.kind())? {
+          file.read_to_string(&mut string).map_err(|e| e.kind())?;
+        }
+        for (line, text) in string.lines().enumerate() {
+          if let Some(column) = text.find("TODO") {
+            let location = Location::at(path, line + 1, column + 1);
+            context.emit_diagnostic(Severity::Warning, "unresolved TODO", Some(location));
+          }
+        }
+        Ok(string.lines().count())
+      }
+      Lint::All(tasks) => tasks.iter().map(|task| context.require_task(task)).sum(),
+    }
+  }
+}
+
+/// Requires `task` in a new session, returning its output and the diagnostics of the session.
+fn require_diagnostics(pie: &mut TestPie<Lint>, task: &Lint) -> (Result<usize, io::ErrorKind>, Vec<(Lint, Diagnostic)>) {
+  let mut session = pie.new_session();
+  let output = session.require(task);
+  (output, session.diagnostics().to_vec())
+}
+
+/// Gets whether the report diagnostic events of the last build were replayed.
+fn replayed(pie: &TestPie<Lint>) -> Vec<bool> {
+  pie.tracker().0.iter().filter_map(|e| match e {
+    Event::ReportDiagnostic(ReportDiagnostic { replayed, .. }) => Some(*replayed),
+    _ => None,
+  }).collect()
+}
+
+#[test]
+fn test_diagnostics() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let path_a = PathBuf::from("a.txt");
+  pie.file_system().write(&path_a, "Hello\n  TODO: World")?;
+  let path_b = PathBuf::from("b.txt");
+  pie.file_system().write(&path_b, "TODO")?;
+  let lint_a = Lint::File(path_a.clone());
+  let lint_b = Lint::File(path_b.clone());
+  let task = Lint::All(vec![lint_a.clone(), lint_b.clone()]);
+
+  // Diagnostics are reported when tasks are executed.
+  let (output, diagnostics) = require_diagnostics(&mut pie, &task);
+  assert_eq!(output, Ok(3));
+  assert_matches!(diagnostics.as_slice(), [(a, d_a), (b, d_b)] if a == &lint_a && b == &lint_b &&
+    d_a.severity == Severity::Warning && d_a.location == Some(Location::at(&path_a, 2, 3)) &&
+    d_b.location == Some(Location::at(&path_b, 1, 1)));
+  assert_eq!(replayed(&pie), [false, false]);
+
+  // Diagnostics are replayed when tasks are not executed, even for tasks that are only checked.
+  let (output, replayed_diagnostics) = require_diagnostics(&mut pie, &task);
+  assert_eq!(output, Ok(3));
+  assert_eq!(replayed_diagnostics, diagnostics);
+  assert_eq!(replayed(&pie), [true, true]);
+
+  // Executing a task again replaces its diagnostics.
+  pie.file_system().write(&path_a, "Hello\nWorld")?;
+  let (output, diagnostics) = require_diagnostics(&mut pie, &task);
+  assert_eq!(output, Ok(3));
+  assert_matches!(diagnostics.as_slice(), [(b, _)] if b == &lint_b);
+  assert_eq!(replayed(&pie), [true]);
+
+  Ok(())
+}
+
+#[test]
+fn test_diagnostics_reported_once() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let path = PathBuf::from("a.txt");
+  pie.file_system().write(&path, "TODO")?;
+  let lint = Lint::File(path);
+  // Requiring the same task twice reports its diagnostics once.
+  let task = Lint::All(vec![lint.clone(), lint.clone()]);
+  pie.require_then_assert(&task, |tracker| {
+    assert_eq!(tracker.iter().filter(|e| matches!(e, Event::ReportDiagnostic(_))).count(), 1);
+  })?;
+  Ok(())
+}
@@ -0,0 +1,222 @@
+use std::io::{self, BufWriter, Stderr, Stdout, Write};
+use std::path::Path;
+
+use crate::dependency::{Dependency, FileDependency, Inconsistency, ModifiedFile, TaskDependency};
+use crate::determinism::NonDeterminism;
+use crate::diagnostic::Diagnostic;
+use crate::stamp::{FileStamper, OutputStamper};
+use crate::Task;
+use crate::tracker::{Span, Tracker};
+
+/// [`Tracker`] that writes events to a [`Write`] instance, for example [`Stdout`].
+#[derive(Clone, Debug)]
+pub struct WritingTracker<W> {
+  writer: W,
+  indentation: u32,
+}
+
+impl WritingTracker<BufWriter<Stdout>> {
+  /// Creates a [`WritingTracker`] that writes to buffered standard output.
+  pub fn with_stdout() -> Self { Self::new(BufWriter::new(io::stdout())) }
+}
+impl WritingTracker<BufWriter<Stderr>> {
+  /// Creates a [`WritingTracker`] that writes to buffered standard error.
+  pub fn with_stderr() -> Self { Self::new(BufWriter::new(io::stderr())) }
+}
+impl<W: Write> WritingTracker<W> {
+  /// Creates a [`WritingTracker`] that writes to `writer`.
+  pub fn new(writer: W) -> Self {
+    Self {
+      writer,
+      indentation: 0,
+    }
+  }
+
+  /// Gets the writer of this writing tracker.
+  pub fn writer(&self) -> &W { &self.writer }
+  /// Gets the mutable writer of this writing tracker.
+  pub fn writer_mut(&mut self) -> &mut W { &mut self.writer }
+}
+
+#[allow(dead_code)]
+impl<W: Write> WritingTracker<W> {
+  fn writeln(&mut self, args: std::fmt::Arguments) {
+    self.write_indentation();
+    let _ = writeln!(&mut self.writer, "{}", args);
+  }
+  fn write(&mut self, args: std::fmt::Arguments) {
+    let _ = write!(&mut self.writer, "{}", args);
+  }
+  fn write_nl(&mut self) {
+    let _ = write!(&mut self.writer, "\n");
+  }
+
+  fn indent(&mut self) {
+    self.indentation = self.indentation.saturating_add(1);
+  }
+  fn unindent(&mut self) {
+    self.indentation = self.indentation.saturating_sub(1);
+  }
+  fn write_indentation(&mut self) {
+    for _ in 0..self.indentation {
+      let _ = write!(&mut self.writer, " ");
+    }
+  }
+
+  fn flush(&mut self) {
+    let _ = self.writer.flush();
+  }
+}
+
+impl<W: Write, T: Task> Tracker<T> for WritingTracker<W> {
+  fn build_start(&mut self, _span: Span) {
+    self.indentation = 0;
+  }
+  fn build_end(&mut self, _span: Span) {
+    self.writeln(format_args!("🏁"));
+    self.flush();
+  }
+
+  fn require_file_end(
+    &mut self,
+    _span: Span,
+    path: &Path,
+    _stamper: &FileStamper,
+    dependency: Result<&FileDependency, &io::Error>
+  ) {
+    match dependency {
+      Ok(_) => self.writeln(format_args!("r {}", path.display())),
+      Err(e) => self.writeln(format_args!("r {} (err: {:?})", path.display(), e)),
+    }
+  }
+  fn provide_file_end(
+    &mut self,
+    _span: Span,
+    path: &Path,
+    _stamper: &FileStamper,
+    dependency: Result<&FileDependency, &io::Error>
+  ) {
+    match dependency {
+      Ok(_) => self.writeln(format_args!("p {}", path.display())),
+      Err(e) => self.writeln(format_args!("p {} (err: {:?})", path.display(), e)),
+    }
+  }
+  fn require_task_start(&mut self, _span: Span, task: &T, _stamper: &OutputStamper) {
+    self.writeln(format_args!("→ {:?}", task));
+    self.indent();
+    self.flush();
+  }
+  fn require_task_end(
+    &mut self,
+    _span: Span,
+    _dependency: &TaskDependency<T, T::Output>,
+    output: &T::Output,
+    _was_executed: bool
+  ) {
+    self.unindent();
+    self.writeln(format_args!("← {:?}", output));
+    self.flush();
+  }
+
+  fn check_dependency_start(&mut self, _span: Span, dependency: &Dependency<T, T::Output>) {
+    match dependency {
+      Dependency::RequireTask(d) => {
+        self.writeln(format_args!("? {:?}", d.task()));
+        self.indent();
+        self.flush();
+      },
+      _ => {},
+    }
+  }
+  fn check_dependency_end(
+    &mut self,
+    _span: Span,
+    dependency: &Dependency<T, T::Output>,
+    inconsistency: Result<Option<&Inconsistency<T::Output>>, &io::Error>
+  ) {
+    match dependency {
+      Dependency::RequireFile(d) | Dependency::ProvideFile(d) => {
+        match inconsistency {
+          Err(e) => self.writeln(format_args!("✗ {} (err: {:?})", d.path().display(), e)),
+          Ok(Some(Inconsistency::File(s))) =>
+            self.writeln(format_args!("✗ {} (old: {:?} ≠ new: {:?})", d.path().display(), d.stamp(), s)),
+          Ok(None) => self.writeln(format_args!("✓ {}", d.path().display())),
+          _ => {}, // Other variants cannot occur.
+        }
+      },
+      Dependency::RequireTask(d) => {
+        self.unindent();
+        match inconsistency {
+          Ok(Some(Inconsistency::Task(s))) =>
+            self.writeln(format_args!("✗ {:?} (old: {:?} ≠ new: {:?})", d.task(), d.stamp(), s)),
+          Ok(None) => self.writeln(format_args!("✓ {:?}", d.task())),
+          _ => {}, // Other variants cannot occur.
+        }
+      }
+      Dependency::ReservedRequireTask => {} // Ignore: reserved task dependencies are never checked.
+    }
+    self.flush()
+  }
+
+  fn execute_start(&mut self, _span: Span, task: &T) {
+    self.writeln(format_args!("▶ {:?}", task));
+    self.indent();
+    self.flush();
+  }
+  fn execute_end(
+    &mut self,
+    _span: Span,
+    _task: &T,
+    output: &T::Output,
+    _dependencies: &[&Dependency<T, T::Output>]
+  ) {
+    self.unindent();
+    self.writeln(format_args!("◀ {:?}", output));
+    self.flush();
+  }
+
+  fn check_determinism_start(&mut self, _span: Span, task: &T) {
+    self.writeln(format_args!("⟳ {:?}", task));
+    self.indent();
+    self.flush();
+  }
+  fn check_determinism_end(
+    &mut self,
+    _span: Span,
+    task: &T,
+    non_determinism: Option<&NonDeterminism<T, T::Output>>
+  ) {
+    self.unindent();
+    match non_determinism {
+      None => self.writeln(format_args!("✓ {:?} is deterministic", task)),
+      Some(n) if n.output_differs() =>
+        self.writeln(format_args!("✗ {:?} is non-deterministic (output: {:?} ≠ {:?})", task, n.output, n.reexecuted_output)),
+      Some(n) => self.writeln(format_args!("✗ {:?} is non-deterministic (dependencies: {:?} ≠ {:?})", task,
+        n.dependencies, n.reexecuted_dependencies)),
+    }
+    self.flush();
+  }
+
+  fn verify_files_end(&mut self, _span: Span, modified_files: &[ModifiedFile<T>]) {
+    for m in modified_files {
+      self.writeln(format_args!("! {} modified during build after {:?} required it (old: {:?} ≠ new: {:?})",
+        m.dependency.path().display(), m.task, m.dependency.stamp(), m.new_stamp));
+    }
+    self.flush();
+  }
+
+  fn report_diagnostic(&mut self, _span: Span, _task: &T, diagnostic: &Diagnostic, replayed: bool) {
+    if replayed {
+      self.writeln(format_args!("⚠ {} (replayed)", diagnostic));
+    } else {
+      self.writeln(format_args!("⚠ {}", diagnostic));
+    }
+    self.flush();
+  }
+  fn execute_log(&mut self, _span: Span, _task: &T, log: &str) {
+    for line in log.lines() {
+      self.writeln(format_args!("│ {}", line));
+    }
+    self.flush();
+  }
+}
@@ -0,0 +1,116 @@
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use assert_matches::assert_matches;
+
+use pie::{Context, Task};
+use pie::fs::FileSystem;
+use pie::tracker::event::{Event, ExecuteLog};
+use pie::tracker::writing::WritingTracker;
+
+use crate::common::{test_pie, TestPie, TestPieExt};
+
+mod common;
+
+/// Tasks that greet names, logging what they are doing.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+enum Greet {
+  /// Greets the name read from the file at given path.
+  File(PathBuf),
+  /// Greets all names, separated by newlines.
+  All(Vec<Greet>),
+}
+
+impl Task for Greet {
+  type Output = Result<String, io::ErrorKind>;
+  fn execute<C: Context<Self>>(&self, context: &mut C) -> Self::Output {
+    match self {
+      Greet::File(path) => {
+        let mut name = String::new();
+        if let Some(mut file) = context.require_file(path).map_err(|e| e.kind())? {
+          file.read_to_string(&mut name).map_err(|e| e.kind())?;
+        }
+        writeln!(context.log(), "Greeting {}", name).map_err(|e| e.kind())?;
+        Ok(format!("Hello, {}!", name))
+      }
+      Greet::All(tasks) => {
+        writeln!(context.log(), "Greeting {} names", tasks.len()).map_err(|e| e.kind())?;
+        let greetings: Result<Vec<_>, _> = tasks.iter().map(|task| context.require_task(task)).collect();
+        writeln!(context.log(), "Done").map_err(|e| e.kind())?;
+        Ok(greetings?.join("\n"))
+      }
+    }
+  }
+}
+
+/// Gets the tasks and logs of the execute log events of the last build.
+fn logs(pie: &TestPie<Greet>) -> Vec<(Greet, String)> {
+  pie.tracker().0.iter().filter_map(|e| match e {
+    Event::ExecuteLog(ExecuteLog { task, log, .. }) => Some((task.clone(), log.clone())),
+    _ => None,
+  }).collect()
+}
+
+#[test]
+fn test_log() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let path_a = PathBuf::from("a.txt");
+  pie.file_system().write(&path_a, "Alice")?;
+  let path_b = PathBuf::from("b.txt");
+  pie.file_system().write(&path_b, "Bob")?;
+  let greet_a = Greet::File(path_a.clone());
+  let greet_b = Greet::File(path_b.clone());
+  let task = Greet::All(vec![greet_a.clone(), greet_b.clone()]);
+
+  // Logs are reported per executed task, in the span of its execution, after logs of the tasks it requires.
+  pie.require_then_assert(&task, |tracker| {
+    let execute_start = tracker.find_map(|e| e.match_execute_start(&task)).unwrap();
+    assert_matches!(tracker.find_map(|e| match e {
+      Event::ExecuteLog(d) if d.task == task => Some(d),
+      _ => None,
+    }), Some(d) if d.span == execute_start.span);
+  })?;
+  assert_eq!(logs(&pie), [
+    (greet_a.clone(), "Greeting Alice\n".to_string()),
+    (greet_b.clone(), "Greeting Bob\n".to_string()),
+    (task.clone(), "Greeting 2 names\nDone\n".to_string()),
+  ]);
+
+  // Logs are not reported when tasks are not executed.
+  pie.require(&task)?;
+  assert!(logs(&pie).is_empty());
+
+  // Logs of a new execution replace the log of the previous execution.
+  pie.file_system().write(&path_a, "Anna")?;
+  pie.require(&task)?;
+  assert_eq!(logs(&pie), [
+    (greet_a.clone(), "Greeting Anna\n".to_string()),
+    (task.clone(), "Greeting 2 names\nDone\n".to_string()),
+  ]);
+
+  Ok(())
+}
+
+#[test]
+fn test_log_indented() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let path = PathBuf::from("a.txt");
+  pie.file_system().write(&path, "Alice")?;
+  let task = Greet::All(vec![Greet::File(path)]);
+  pie.require(&task)?;
+
+  // Logged lines are written indented under the execution of their task.
+  let mut writing_tracker = WritingTracker::new(Vec::new());
+  pie.tracker().0.replay(&mut writing_tracker);
+  let build_log = String::from_utf8_lossy(writing_tracker.writer());
+  let lines: Vec<_> = build_log.lines().collect();
+  let execute_line = lines.iter().position(|l| l.trim_start().starts_with("▶ File")).unwrap();
+  let indentation = lines[execute_line].len() - lines[execute_line].trim_start().len();
+  let log_line = lines.iter().position(|l| l.trim_start() == "│ Greeting Alice").unwrap();
+  assert!(log_line > execute_line);
+  assert_eq!(lines[log_line].len() - lines[log_line].trim_start().len(), indentation + 1);
+  assert!(build_log.contains("│ Greeting 1 names\n"));
+  assert!(build_log.contains("│ Done\n"));
+
+  Ok(())
+}
@@ -0,0 +1,659 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Cursor, Read, Write};
+use std::env;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// File system that tasks read from and write to, and that file stampers stamp files with.
+pub trait FileSystem {
+  /// Type of opened files, which can be read from.
+  type File: Read;
+
+  /// Gets the metadata for given `path`, returning:
+  /// - `Ok(Some(metadata))` if a file or directory exists at given path,
+  /// - `Ok(None)` if no file or directory exists at given path,
+  /// - `Err(e)` if there was an error getting the metadata for given path.
+  fn metadata(&self, path: impl AsRef<Path>) -> Result<Option<Metadata>, io::Error>;
+
+  /// Attempt to open file at given `path`, returning:
+  /// - `Ok(Some(file))` if the file exists at given path,
+  /// - `Ok(None)` if no file exists at given path (but a directory could exist at given path),
+  /// - `Err(e)` if there was an error getting the metadata for given path, or if there was an error opening the file.
+  fn open_if_file(&self, path: impl AsRef<Path>) -> Result<Option<Self::File>, io::Error>;
+
+  /// Writes `contents` to the file at given `path`, creating the file if it does not exist, and replacing its contents
+  /// if it does.
+  fn write(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), io::Error>;
+
+  /// Writes `contents` to the file at given `path` atomically, such that readers and crashes never observe a partially
+  /// written file: the file at given `path` either has its old contents, or all of `contents`.
+  fn write_atomically(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), io::Error> {
+    self.write_atomically_with(path, |writer| writer.write_all(contents.as_ref()))?;
+    Ok(())
+  }
+
+  /// Writes the file at given `path` atomically with `write`, such that readers and crashes never observe a partially
+  /// written file: the file at given `path` either has its old contents, or everything written by `write`. The file is
+  /// not changed if `write` returns an error. Returns the number of bytes in the written file.
+  ///
+  /// The default implementation buffers the data written by `write` in memory, and then calls [`write`](Self::write),
+  /// which is only correct for file systems where writes are already atomic.
+  fn write_atomically_with(
+    &self,
+    path: impl AsRef<Path>,
+    write: impl FnOnce(&mut dyn Write) -> Result<(), io::Error>,
+  ) -> Result<usize, io::Error> {
+    let mut contents = Vec::new();
+    write(&mut contents)?;
+    self.write(path, &contents)?;
+    Ok(contents.len())
+  }
+
+  /// Removes the file at given `path`.
+  fn remove_file(&self, path: impl AsRef<Path>) -> Result<(), io::Error>;
+
+  /// Reads the directory at given `path`, returning the paths of its entries in no particular order. Returns an error if
+  /// no directory exists at given `path`.
+  fn read_dir(&self, path: impl AsRef<Path>) -> Result<Vec<PathBuf>, io::Error>;
+
+  /// Gets the canonical, absolute form of `path`, with all symbolic links resolved. Returns an error if no file or
+  /// directory exists at given `path`.
+  ///
+  /// The default implementation returns `path` as is, which is only correct for file systems without symbolic links.
+  fn canonicalize(&self, path: impl AsRef<Path>) -> Result<PathBuf, io::Error> {
+    Ok(path.as_ref().to_path_buf())
+  }
+}
+
+/// Metadata of a file or directory.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Metadata {
+  /// Whether this is the metadata of a file. If not, it is the metadata of a directory.
+  pub is_file: bool,
+  /// Last modification time.
+  pub modified: SystemTime,
+}
+
+
+/// [`FileSystem`] implementation that uses the file system of the operating system, through [`std::fs`].
+#[derive(Copy, Clone, Default, Debug)]
+pub struct RealFileSystem;
+
+impl FileSystem for RealFileSystem {
+  type File = File;
+
+  fn metadata(&self, path: impl AsRef<Path>) -> Result<Option<Metadata>, io::Error> {
+    match fs::metadata(path) {
+      Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+      Err(e) => Err(e),
+      Ok(m) => Ok(Some(Metadata { is_file: m.is_file(), modified: m.modified()? }))
+    }
+  }
+
+  /// This function is necessary due to Windows returning an error when attempting to open a directory.
+  fn open_if_file(&self, path: impl AsRef<Path>) -> Result<Option<File>, io::Error> {
+    let file = match self.metadata(&path)? {
+      Some(metadata) if metadata.is_file => Some(File::open(&path)?),
+      _ => None,
+    };
+    Ok(file)
+  }
+
+  fn write(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), io::Error> {
+    fs::write(path, contents)
+  }
+
+  /// Streams the data written by `write` into a temporary sibling of the file at given `path`, flushes it to disk, and
+  /// then renames it to `path`. Renaming a file within the same directory replaces the destination atomically. The
+  /// temporary file gets the permissions of the file it replaces, if any.
+  fn write_atomically_with(
+    &self,
+    path: impl AsRef<Path>,
+    write: impl FnOnce(&mut dyn Write) -> Result<(), io::Error>,
+  ) -> Result<usize, io::Error> {
+    let path = path.as_ref();
+    let temp_path = temporary_sibling(path)?;
+    let result = (|| {
+      let file = File::options().write(true).create_new(true).open(&temp_path)?;
+      let mut writer = BufWriter::new(file);
+      write(&mut writer)?;
+      let file = writer.into_inner().map_err(|e| e.into_error())?;
+      file.sync_all()?;
+      // Set the permissions after writing, as the replaced file could be read-only.
+      if let Ok(metadata) = fs::metadata(path) {
+        file.set_permissions(metadata.permissions())?;
+      }
+      let written = file.metadata()?.len() as usize;
+      fs::rename(&temp_path, path)?;
+      // Flush the directory as well, so that the rename survives a crash.
+      #[cfg(unix)] File::open(parent_dir(path))?.sync_all()?;
+      Ok(written)
+    })();
+    if result.is_err() {
+      let _ = fs::remove_file(&temp_path);
+    }
+    result
+  }
+
+  fn remove_file(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
+    fs::remove_file(path)
+  }
+
+  fn read_dir(&self, path: impl AsRef<Path>) -> Result<Vec<PathBuf>, io::Error> {
+    fs::read_dir(path)?.map(|entry| Ok(entry?.path())).collect()
+  }
+
+  fn canonicalize(&self, path: impl AsRef<Path>) -> Result<PathBuf, io::Error> {
+    fs::canonicalize(path)
+  }
+}
+
+
+/// [`FileSystem`] implementation that stores files in memory, with a logical clock for modification times. Every
+/// write sets the modification time of the file to the current time of the logical clock, and then advances the clock.
+/// Therefore, consecutive writes always result in different modification times, making tests that depend on
+/// modification times deterministic. Like on most real file systems, creating or removing an entry in a directory also
+/// sets the modification time of the directory.
+///
+/// Cloning an in-memory file system creates a new handle to the same files.
+#[derive(Clone, Default, Debug)]
+pub struct InMemoryFileSystem {
+  state: Rc<RefCell<InMemoryState>>,
+}
+
+#[derive(Default, Debug)]
+struct InMemoryState {
+  entries: HashMap<PathBuf, InMemoryEntry>,
+  clock: u64,
+}
+
+#[derive(Debug)]
+enum InMemoryEntry {
+  File { contents: Vec<u8>, modified: u64 },
+  Directory { modified: u64 },
+}
+
+impl InMemoryFileSystem {
+  /// Gets the current time of the logical clock.
+  pub fn clock(&self) -> u64 { self.state.borrow().clock }
+  /// Sets the logical clock to `clock`. Setting the clock to an earlier time simulates file systems with imprecise
+  /// modification times, where consecutive writes can result in the same modification time.
+  pub fn set_clock(&self, clock: u64) { self.state.borrow_mut().clock = clock; }
+
+  /// Creates a directory at given `path`.
+  pub fn create_dir(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
+    let path = path.as_ref();
+    let mut state = self.state.borrow_mut();
+    let modified = state.tick();
+    if state.entries.insert(path.to_path_buf(), InMemoryEntry::Directory { modified }).is_none() {
+      state.set_parent_modified(path, modified);
+    }
+    Ok(())
+  }
+
+  /// Returns whether a file or directory exists at given `path`.
+  pub fn exists(&self, path: impl AsRef<Path>) -> bool {
+    self.state.borrow().entries.contains_key(path.as_ref())
+  }
+
+  /// Reads the file at given `path` into a string.
+  pub fn read_to_string(&self, path: impl AsRef<Path>) -> Result<String, io::Error> {
+    let Some(mut file) = self.open_if_file(&path)? else {
+      return Err(not_found(path));
+    };
+    let mut string = String::new();
+    file.read_to_string(&mut string)?;
+    Ok(string)
+  }
+}
+
+impl InMemoryState {
+  /// Returns the current time of the logical clock, then advances the clock.
+  fn tick(&mut self) -> u64 {
+    let time = self.clock;
+    self.clock += 1;
+    time
+  }
+  /// Sets the modification time of the parent directory of `path` to `modified`, if it exists.
+  fn set_parent_modified(&mut self, path: &Path, modified: u64) {
+    if let Some(InMemoryEntry::Directory { modified: parent_modified }) = path.parent()
+      .and_then(|parent| self.entries.get_mut(parent)) {
+      *parent_modified = modified;
+    }
+  }
+}
+
+impl FileSystem for InMemoryFileSystem {
+  type File = Cursor<Vec<u8>>;
+
+  fn metadata(&self, path: impl AsRef<Path>) -> Result<Option<Metadata>, io::Error> {
+    let metadata = self.state.borrow().entries.get(path.as_ref()).map(|entry| {
+      let (is_file, modified) = match entry {
+        InMemoryEntry::File { modified, .. } => (true, *modified),
+        InMemoryEntry::Directory { modified } => (false, *modified),
+      };
+      Metadata { is_file, modified: SystemTime::UNIX_EPOCH + Duration::from_secs(modified) }
+    });
+    Ok(metadata)
+  }
+
+  fn open_if_file(&self, path: impl AsRef<Path>) -> Result<Option<Self::File>, io::Error> {
+    let file = match self.state.borrow().entries.get(path.as_ref()) {
+      Some(InMemoryEntry::File { contents, .. }) => Some(Cursor::new(contents.clone())),
+      _ => None,
+    };
+    Ok(file)
+  }
+
+  fn write(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), io::Error> {
+    let path = path.as_ref();
+    let mut state = self.state.borrow_mut();
+    if let Some(InMemoryEntry::Directory { .. }) = state.entries.get(path) {
+      return Err(io::Error::other(format!("cannot write to directory '{}'", path.display())));
+    }
+    let modified = state.tick();
+    let entry = InMemoryEntry::File { contents: contents.as_ref().to_vec(), modified };
+    if state.entries.insert(path.to_path_buf(), entry).is_none() {
+      state.set_parent_modified(path, modified);
+    }
+    Ok(())
+  }
+
+  fn remove_file(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
+    let mut state = self.state.borrow_mut();
+    match state.entries.get(path.as_ref()) {
+      Some(InMemoryEntry::File { .. }) => {
+        state.entries.remove(path.as_ref());
+        let modified = state.tick();
+        state.set_parent_modified(path.as_ref(), modified);
+        Ok(())
+      }
+      _ => Err(not_found(path)),
+    }
+  }
+
+  fn read_dir(&self, path: impl AsRef<Path>) -> Result<Vec<PathBuf>, io::Error> {
+    let path = path.as_ref();
+    let state = self.state.borrow();
+    let Some(InMemoryEntry::Directory { .. }) = state.entries.get(path) else {
+      return Err(io::Error::new(io::ErrorKind::NotFound, format!("directory '{}' not found", path.display())));
+    };
+    let entries = state.entries.keys()
+      .filter(|entry| entry.parent() == Some(path))
+      .cloned()
+      .collect();
+    Ok(entries)
+  }
+}
+
+/// Policy for normalizing paths of required and provided files, such that different paths to the same file are
+/// identified as the same file. Without normalization, `a.txt`, `./a.txt`, `/project/a.txt`, and a symbolic link to
+/// `a.txt` are different files to PIE, which defeats the checks for overlapping provided files and hidden dependencies.
+/// Files are identified, stamped, and accessed through their normalized path, so different paths to the same file share
+/// their file dependencies and cached file stamps.
+///
+/// Paths are normalized in the following order:
+/// 1. If a [root](Self::with_root) is set, relative paths are resolved against the root.
+/// 2. If [symbolic link resolution](Self::with_symlink_resolution) is enabled, symbolic links in the longest existing
+///    ancestor of the path are resolved with [`FileSystem::canonicalize`], making the path absolute.
+/// 3. If [lexical normalization](Self::with_lexical) is enabled, `.` components are removed, and `..` components are
+///    removed together with their preceding component, without accessing the file system.
+///
+/// Lexical normalization of `a/../b` to `b` is incorrect when `a` is a symbolic link to a directory, so enable symbolic
+/// link resolution when paths with `..` components may go through symbolic links.
+///
+/// The default policy does not normalize paths at all, as lexical normalization may change which file a path refers to.
+/// Enable the normalization steps that are correct for the paths of your build.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PathNormalization {
+  root: Option<PathBuf>,
+  resolve_symlinks: bool,
+  lexical: bool,
+}
+
+impl Default for PathNormalization {
+  fn default() -> Self { Self::none() }
+}
+
+impl PathNormalization {
+  /// Creates a policy that does not normalize paths at all.
+  pub fn none() -> Self { Self { root: None, resolve_symlinks: false, lexical: false } }
+
+  /// Sets the project root that relative paths are resolved against to `root`, which is resolved against the current
+  /// working directory if it is relative. The root must be the directory that the file system resolves relative paths
+  /// against, which is the current working directory for the [real file system](RealFileSystem). Therefore, use `"."`
+  /// to identify relative paths with absolute paths in the current working directory.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if `root` is relative and getting the current working directory fails.
+  pub fn with_root(mut self, root: impl AsRef<Path>) -> Result<Self, io::Error> {
+    let root = root.as_ref();
+    let root = if root.is_relative() { env::current_dir()?.join(root) } else { root.to_path_buf() };
+    self.root = Some(normalize_lexically(&root));
+    Ok(self)
+  }
+  /// Sets whether symbolic links are resolved to `resolve_symlinks`.
+  pub fn with_symlink_resolution(mut self, resolve_symlinks: bool) -> Self {
+    self.resolve_symlinks = resolve_symlinks;
+    self
+  }
+  /// Sets whether paths are normalized lexically to `lexical`.
+  pub fn with_lexical(mut self, lexical: bool) -> Self {
+    self.lexical = lexical;
+    self
+  }
+
+  /// Gets the project root, or `None` if relative paths are not resolved.
+  pub fn root(&self) -> Option<&Path> { self.root.as_deref() }
+
+  /// Normalizes `path` according to this policy, using `file_system` to resolve symbolic links.
+  pub fn normalize(&self, path: impl AsRef<Path>, file_system: &impl FileSystem) -> PathBuf {
+    let path = path.as_ref();
+    let mut path = match &self.root {
+      Some(root) if path.is_relative() => root.join(path),
+      _ => path.to_path_buf(),
+    };
+    if self.resolve_symlinks {
+      path = resolve_symlinks(&path, file_system);
+    }
+    if self.lexical {
+      path = normalize_lexically(&path);
+    }
+    path
+  }
+}
+
+/// Removes `.` components from `path`, and removes `..` components together with their preceding component. Leading
+/// `..` components of relative paths are kept, and `..` components directly after the root are removed.
+fn normalize_lexically(path: &Path) -> PathBuf {
+  let mut normalized = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir => match normalized.components().next_back() {
+        Some(Component::Normal(_)) => { normalized.pop(); }
+        Some(Component::RootDir | Component::Prefix(_)) => {} // Parent of the root is the root.
+        _ => normalized.push(".."),
+      },
+      component => normalized.push(component),
+    }
+  }
+  if normalized.as_os_str().is_empty() {
+    normalized.push(".");
+  }
+  normalized
+}
+
+/// Resolves symbolic links in the longest existing ancestor of `path` with `file_system`, appending the remaining
+/// components. Returns `path` as is if no ancestor can be resolved.
+fn resolve_symlinks(path: &Path, file_system: &impl FileSystem) -> PathBuf {
+  for ancestor in path.ancestors() {
+    // Relative paths without a parent are relative to the current directory.
+    let existing = if ancestor.as_os_str().is_empty() { Path::new(".") } else { ancestor };
+    if let Ok(mut resolved) = file_system.canonicalize(existing) {
+      if let Ok(remaining) = path.strip_prefix(ancestor) {
+        if !remaining.as_os_str().is_empty() {
+          resolved.push(remaining);
+        }
+      }
+      return resolved;
+    }
+  }
+  path.to_path_buf()
+}
+
+/// Gets a unique path in the same directory as `path`, to write to before renaming to `path`.
+fn temporary_sibling(path: &Path) -> Result<PathBuf, io::Error> {
+  static COUNTER: AtomicUsize = AtomicUsize::new(0);
+  let Some(file_name) = path.file_name() else {
+    return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("'{}' is not a file path", path.display())));
+  };
+  let mut temp_name = OsString::from(".");
+  temp_name.push(file_name);
+  temp_name.push(format!(".{}.{}.tmp", std::process::id(), COUNTER.fetch_add(1, Ordering::Relaxed)));
+  Ok(parent_dir(path).join(temp_name))
+}
+
+/// Gets the parent directory of `path`, which is the current directory for relative paths without a parent.
+fn parent_dir(path: &Path) -> &Path {
+  match path.parent() {
+    Some(parent) if !parent.as_os_str().is_empty() => parent,
+    _ => Path::new("."),
+  }
+}
+
+fn not_found(path: impl AsRef<Path>) -> io::Error {
+  io::Error::new(io::ErrorKind::NotFound, format!("file '{}' not found", path.as_ref().display()))
+}
+
+#[cfg(test)]
+mod test {
+  use std::fs::remove_file;
+  use std::io;
+
+  use assert_matches::assert_matches;
+
+  use dev_shared::{create_temp_dir, create_temp_file};
+
+  use super::*;
+
+  #[test]
+  fn test_metadata_ok() -> Result<(), io::Error> {
+    let temp_file = create_temp_file()?;
+    let metadata = RealFileSystem.metadata(temp_file)?;
+    assert_matches!(metadata, Some(metadata) => {
+      assert!(metadata.is_file);
+    });
+    Ok(())
+  }
+
+  #[test]
+  fn test_metadata_none() -> Result<(), io::Error> {
+    let temp_file = create_temp_file()?;
+    remove_file(&temp_file)?;
+    let metadata = RealFileSystem.metadata(&temp_file)?;
+    assert!(metadata.is_none());
+    Ok(())
+  }
+
+  #[test]
+  fn test_open_if_file() -> Result<(), io::Error> {
+    let temp_file = create_temp_file()?;
+    let file = RealFileSystem.open_if_file(&temp_file)?;
+    assert!(file.is_some());
+    Ok(())
+  }
+
+  #[test]
+  fn test_open_if_file_non_existent() -> Result<(), io::Error> {
+    let temp_file = create_temp_file()?;
+    remove_file(&temp_file)?;
+    let file = RealFileSystem.open_if_file(&temp_file)?;
+    assert!(file.is_none());
+    Ok(())
+  }
+
+  #[test]
+  fn test_open_if_file_on_directory() -> Result<(), io::Error> {
+    let temp_dir = create_temp_dir()?;
+    let file = RealFileSystem.open_if_file(temp_dir)?;
+    assert!(file.is_none());
+    Ok(())
+  }
+
+  #[test]
+  fn test_read_dir() -> Result<(), io::Error> {
+    let temp_dir = create_temp_dir()?;
+    fs::write(temp_dir.path().join("a.txt"), "Hello")?;
+    fs::create_dir(temp_dir.path().join("b"))?;
+    let mut entries = RealFileSystem.read_dir(&temp_dir)?;
+    entries.sort();
+    assert_eq!(entries, vec![temp_dir.path().join("a.txt"), temp_dir.path().join("b")]);
+    assert!(RealFileSystem.read_dir(temp_dir.path().join("a.txt")).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_write_atomically() -> Result<(), io::Error> {
+    let temp_dir = create_temp_dir()?;
+    let path = temp_dir.path().join("out.txt");
+    RealFileSystem.write_atomically(&path, "Hello")?;
+    assert_eq!(fs::read_to_string(&path)?, "Hello");
+    RealFileSystem.write_atomically(&path, "Hello, World!")?;
+    assert_eq!(fs::read_to_string(&path)?, "Hello, World!");
+    // No temporary files are left behind.
+    assert_eq!(RealFileSystem.read_dir(&temp_dir)?, vec![path.clone()]);
+    // Writing to a directory fails and also leaves no temporary files behind.
+    fs::create_dir(temp_dir.path().join("dir"))?;
+    assert!(RealFileSystem.write_atomically(temp_dir.path().join("dir"), "Hello").is_err());
+    let mut entries = RealFileSystem.read_dir(&temp_dir)?;
+    entries.sort();
+    assert_eq!(entries, vec![temp_dir.path().join("dir"), path]);
+    Ok(())
+  }
+
+  #[test]
+  fn test_write_atomically_with() -> Result<(), io::Error> {
+    let temp_dir = create_temp_dir()?;
+    let path = temp_dir.path().join("out.txt");
+    let written = RealFileSystem.write_atomically_with(&path, |writer| {
+      writer.write_all(b"Hello")?;
+      writer.write_all(b", World!")
+    })?;
+    assert_eq!(written, 13);
+    assert_eq!(fs::read_to_string(&path)?, "Hello, World!");
+    // The file is not changed when writing fails, and no temporary files are left behind.
+    let result = RealFileSystem.write_atomically_with(&path, |writer| {
+      writer.write_all(b"Hello")?;
+      Err(io::Error::other("failed"))
+    });
+    assert!(result.is_err());
+    assert_eq!(fs::read_to_string(&path)?, "Hello, World!");
+    assert_eq!(RealFileSystem.read_dir(&temp_dir)?, vec![path.clone()]);
+    Ok(())
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_write_atomically_keeps_permissions() -> Result<(), io::Error> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = create_temp_dir()?;
+    let path = temp_dir.path().join("run.sh");
+    RealFileSystem.write_atomically(&path, "echo Hello")?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    RealFileSystem.write_atomically(&path, "echo World")?;
+    assert_eq!(fs::metadata(&path)?.permissions().mode() & 0o777, 0o755);
+    // Also when the replaced file is read-only.
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o444))?;
+    RealFileSystem.write_atomically(&path, "echo Hello, World!")?;
+    assert_eq!(fs::metadata(&path)?.permissions().mode() & 0o777, 0o444);
+    assert_eq!(fs::read_to_string(&path)?, "echo Hello, World!");
+    Ok(())
+  }
+
+  #[test]
+  fn test_path_normalization() -> Result<(), io::Error> {
+    let file_system = InMemoryFileSystem::default();
+    assert_eq!(PathNormalization::default(), PathNormalization::none());
+    let lexical = PathNormalization::none().with_lexical(true);
+    assert_eq!(lexical.normalize("./a.txt", &file_system), PathBuf::from("a.txt"));
+    assert_eq!(lexical.normalize("dir/./../a.txt", &file_system), PathBuf::from("a.txt"));
+    assert_eq!(lexical.normalize("../dir/../../a.txt", &file_system), PathBuf::from("../../a.txt"));
+    assert_eq!(lexical.normalize("/../a.txt", &file_system), PathBuf::from("/a.txt"));
+    assert_eq!(lexical.normalize("dir/..", &file_system), PathBuf::from("."));
+
+    let none = PathNormalization::none();
+    assert_eq!(none.normalize("./a.txt", &file_system), PathBuf::from("./a.txt"));
+
+    let root = lexical.clone().with_root("/project")?;
+    assert_eq!(root.normalize("./a.txt", &file_system), PathBuf::from("/project/a.txt"));
+    assert_eq!(root.normalize("/project/dir/../a.txt", &file_system), PathBuf::from("/project/a.txt"));
+    assert_eq!(root.normalize("../a.txt", &file_system), PathBuf::from("/a.txt"));
+    // Relative roots are resolved against the current working directory.
+    let current_dir = lexical.clone().with_root(".")?;
+    assert_eq!(current_dir.normalize("a.txt", &file_system), env::current_dir()?.join("a.txt"));
+    Ok(())
+  }
+
+  #[test]
+  #[cfg(unix)]
+  fn test_path_normalization_symlinks() -> Result<(), io::Error> {
+    let temp_dir = create_temp_dir()?;
+    let dir = RealFileSystem.canonicalize(temp_dir.path())?;
+    fs::create_dir(dir.join("real"))?;
+    fs::write(dir.join("real/a.txt"), "Hello")?;
+    std::os::unix::fs::symlink(dir.join("real"), dir.join("link"))?;
+    std::os::unix::fs::symlink(dir.join("real/a.txt"), dir.join("a.txt"))?;
+
+    let resolve = PathNormalization::none().with_symlink_resolution(true).with_lexical(true);
+    assert_eq!(resolve.normalize(dir.join("a.txt"), &RealFileSystem), dir.join("real/a.txt"));
+    assert_eq!(resolve.normalize(dir.join("link/a.txt"), &RealFileSystem), dir.join("real/a.txt"));
+    // Symbolic links are resolved in the longest existing ancestor of files that do not exist.
+    assert_eq!(resolve.normalize(dir.join("link/new/../b.txt"), &RealFileSystem), dir.join("real/b.txt"));
+    // Directories that are symbolic links are resolved before `..` components are removed.
+    assert_eq!(resolve.normalize(dir.join("link/../b.txt"), &RealFileSystem), dir.join("b.txt"));
+    // Without resolution, symbolic links are kept.
+    assert_eq!(PathNormalization::default().normalize(dir.join("a.txt"), &RealFileSystem), dir.join("a.txt"));
+    Ok(())
+  }
+
+  #[test]
+  fn test_in_memory() -> Result<(), io::Error> {
+    let fs = InMemoryFileSystem::default();
+    assert!(fs.metadata("in.txt")?.is_none());
+    assert!(fs.open_if_file("in.txt")?.is_none());
+
+    fs.write("in.txt", "Hello")?;
+    let metadata = assert_matches!(fs.metadata("in.txt")?, Some(m) => m);
+    assert!(metadata.is_file);
+    assert_eq!(fs.read_to_string("in.txt")?, "Hello");
+
+    // Consecutive writes result in different modification times.
+    fs.write("in.txt", "World")?;
+    let new_metadata = assert_matches!(fs.metadata("in.txt")?, Some(m) => m);
+    assert!(new_metadata.modified > metadata.modified);
+    assert_eq!(fs.read_to_string("in.txt")?, "World");
+
+    // Unless the clock is reset.
+    fs.set_clock(fs.clock() - 1);
+    fs.write("in.txt", "Hello")?;
+    assert_eq!(fs.metadata("in.txt")?, Some(new_metadata));
+
+    fs.remove_file("in.txt")?;
+    assert!(!fs.exists("in.txt"));
+    assert!(fs.remove_file("in.txt").is_err());
+    assert!(fs.read_to_string("in.txt").is_err());
+
+    fs.create_dir("dir")?;
+    assert_matches!(fs.metadata("dir")?, Some(m) if !m.is_file);
+    assert!(fs.open_if_file("dir")?.is_none());
+    assert!(fs.write("dir", "Hello").is_err());
+    assert_eq!(fs.read_dir("dir")?, Vec::<PathBuf>::new());
+    assert!(fs.read_dir("in.txt").is_err());
+
+    // Creating and removing entries in a directory changes the modification time of the directory.
+    let dir_modified = assert_matches!(fs.metadata("dir")?, Some(m) => m.modified);
+    fs.write("dir/a.txt", "Hello")?;
+    let write_modified = assert_matches!(fs.metadata("dir")?, Some(m) => m.modified);
+    assert!(write_modified > dir_modified);
+    assert_eq!(fs.read_dir("dir")?, vec![PathBuf::from("dir/a.txt")]);
+    fs.write("dir/a.txt", "World")?; // Overwriting is not creating an entry.
+    assert_eq!(fs.metadata("dir")?.map(|m| m.modified), Some(write_modified));
+    fs.remove_file("dir/a.txt")?;
+    assert!(fs.metadata("dir")?.is_some_and(|m| m.modified > write_modified));
+    assert_eq!(fs.read_dir("dir")?, Vec::<PathBuf>::new());
+
+    // Clones share files.
+    fs.clone().write("in.txt", "Hello")?;
+    assert!(fs.exists("in.txt"));
+
+    Ok(())
+  }
+}
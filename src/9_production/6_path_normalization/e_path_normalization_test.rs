@@ -0,0 +1,132 @@
+use std::io;
+use std::path::PathBuf;
+
+use pie::fs::{FileSystem, PathNormalization};
+use pie::stamp::FileStamper;
+
+use crate::common::{test_pie, TestOutput, TestPieExt};
+use crate::common::TestTask::*;
+
+mod common;
+
+#[test]
+#[should_panic(expected = "Hidden dependency")]
+fn test_current_dir_alias_hidden_dependency_panics() {
+  let mut pie = test_pie();
+  pie.set_path_normalization(PathNormalization::none().with_lexical(true));
+  let write = WriteFile(Box::new(Return("Hello")), PathBuf::from("./out.txt"), FileStamper::Modified);
+  let read = ReadFile(PathBuf::from("out.txt"), FileStamper::Modified, None);
+  // `read` requires the file provided by `write` through another path, without a dependency to `write`.
+  pie.require(&Sequence(vec![write, read])).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Overlapping provided file")]
+fn test_parent_dir_alias_overlapping_provided_file_panics() {
+  let mut pie = test_pie();
+  pie.set_path_normalization(PathNormalization::none().with_lexical(true));
+  let write_1 = WriteFile(Box::new(Return("Hello")), PathBuf::from("out.txt"), FileStamper::Modified);
+  let write_2 = WriteFile(Box::new(Return("World")), PathBuf::from("dir/../out.txt"), FileStamper::Modified);
+  pie.require(&Sequence(vec![write_1, write_2])).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Overlapping provided file")]
+fn test_absolute_alias_overlapping_provided_file_panics() {
+  let mut pie = test_pie();
+  pie.set_path_normalization(PathNormalization::none().with_lexical(true).with_root("/project").unwrap());
+  let write_1 = WriteFile(Box::new(Return("Hello")), PathBuf::from("out.txt"), FileStamper::Modified);
+  let write_2 = WriteFile(Box::new(Return("World")), PathBuf::from("/project/out.txt"), FileStamper::Modified);
+  pie.require(&Sequence(vec![write_1, write_2])).unwrap();
+}
+
+#[test]
+fn test_no_normalization() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  pie.file_system().write("in.txt", "Hello")?;
+  // Paths are not normalized by default, so different paths are different files, even if they are aliases of the same
+  // file.
+  let read_1 = ReadFile(PathBuf::from("in.txt"), FileStamper::Modified, None);
+  let read_2 = ReadFile(PathBuf::from("./in.txt"), FileStamper::Modified, None);
+  pie.require_then_assert(&Sequence(vec![read_1, read_2]), |tracker| {
+    assert!(tracker.first_require_file(&PathBuf::from("in.txt")).is_some());
+    assert!(tracker.first_require_file(&PathBuf::from("./in.txt")).is_some());
+  })?;
+  Ok(())
+}
+
+#[test]
+fn test_provided_file_alias() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  pie.set_path_normalization(PathNormalization::none().with_lexical(true));
+  pie.file_system().write("in.txt", "Hello")?;
+  let write = WriteFile(Box::new(ReadFile(PathBuf::from("in.txt"), FileStamper::Modified, None)), PathBuf::from("a.txt"),
+    FileStamper::Modified);
+  // Both tasks require the file provided by `write`, through different paths, in the same session.
+  let read_alias = ReadFile(PathBuf::from("./a.txt"), FileStamper::Modified, Some(Box::new(write.clone())));
+  let read = ReadFile(PathBuf::from("a.txt"), FileStamper::Modified, Some(Box::new(write.clone())));
+  let concat = Concat(vec![read_alias.clone(), read.clone()]);
+  assert_eq!(pie.require(&concat), Ok(TestOutput::String("HelloHello".to_string())));
+
+  // Changing the input of `write` makes it write the provided file again, which both tasks must observe.
+  pie.file_system().write("in.txt", "World")?;
+  let output = pie.require_then_assert(&concat, |tracker| {
+    assert!(tracker.one_execute_of(&write));
+    assert!(tracker.one_execute_of(&read_alias));
+    assert!(tracker.one_execute_of(&read));
+  });
+  assert_eq!(output, Ok(TestOutput::String("WorldWorld".to_string())));
+  pie.require_then_assert_no_execute(&concat)?;
+  Ok(())
+}
+
+#[cfg(unix)]
+mod symlink {
+  use std::fs::{create_dir, write};
+  use std::os::unix::fs::symlink;
+
+  use dev_shared::create_temp_dir;
+  use pie::Pie;
+
+  use crate::common::test_tracker;
+
+  use super::*;
+
+  #[test]
+  #[should_panic(expected = "Hidden dependency")]
+  fn test_symlink_alias_hidden_dependency_panics() {
+    let temp_dir = create_temp_dir().unwrap();
+    let dir = temp_dir.path();
+    create_dir(dir.join("real")).unwrap();
+    symlink(dir.join("real"), dir.join("link")).unwrap();
+
+    let mut pie = Pie::with_tracker(test_tracker());
+    pie.set_path_normalization(PathNormalization::none().with_symlink_resolution(true));
+    let write = WriteFile(Box::new(Return("Hello")), dir.join("real/out.txt"), FileStamper::Modified);
+    let read = ReadFile(dir.join("link/out.txt"), FileStamper::Modified, None);
+    pie.new_session().require(&Sequence(vec![write, read])).unwrap();
+  }
+
+  #[test]
+  fn test_symlink_alias() -> Result<(), io::Error> {
+    let temp_dir = create_temp_dir()?;
+    let dir = temp_dir.path();
+    write(dir.join("in.txt"), "Hello")?;
+    symlink(dir.join("in.txt"), dir.join("link.txt"))?;
+
+    let mut pie = Pie::with_tracker(test_tracker());
+    pie.set_path_normalization(PathNormalization::none().with_symlink_resolution(true));
+    let write = WriteFile(Box::new(Return("World")), dir.join("in.txt"), FileStamper::Modified);
+    // Reading through the symbolic link with a dependency to `write` is fine.
+    let read = ReadFile(dir.join("link.txt"), FileStamper::Modified, Some(Box::new(write)));
+    {
+      let mut session = pie.new_session();
+      assert_eq!(session.require(&read), Ok(TestOutput::String("World".to_string())));
+      assert!(session.modified_files().is_empty());
+    }
+    // Files are accessed through their normalized path, so the symbolic link is resolved.
+    assert!(pie.tracker().0.first_require_file(&dir.canonicalize()?.join("in.txt")).is_some());
+    assert!(pie.tracker().0.first_require_file(&dir.join("link.txt")).is_none());
+    Ok(())
+  }
+}
@@ -0,0 +1,585 @@
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use assert_matches::assert_matches;
+
+use pie::dependency::Dependency;
+use pie::fs::FileSystem;
+use pie::stamp::FileStamper;
+use pie::tracker::event::*;
+
+use crate::common::{test_pie, TestPieExt, TestTask::*};
+use crate::common::scenario::scenario;
+
+mod common;
+
+#[test]
+fn test_execution() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let task = Return("Hello, World!");
+  let output = pie.require_then_assert(&task, |tracker| {
+    let events = tracker.slice();
+    assert_matches!(events.get(0), Some(Event::BuildStart(_)));
+    assert_matches!(events.get(1), Some(Event::RequireTaskStart(RequireTaskStart { task: t, .. })) if t == &task);
+    assert_matches!(events.get(2), Some(Event::ExecuteStart(ExecuteStart { task: t, .. })) if t == &task);
+    assert_matches!(events.get(3), Some(Event::ExecuteEnd(ExecuteEnd { task: t, .. })) if t == &task);
+    assert_matches!(events.get(4), Some(Event::RequireTaskEnd(RequireTaskEnd { task: t, .. })) if t == &task);
+    assert_matches!(events.get(5), Some(Event::BuildEnd(_)));
+  })?;
+  assert_eq!(output.as_str(), "Hello, World!");
+  Ok(())
+}
+
+#[test]
+fn test_reuse() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let task = Return("Hello, World!");
+  // New task: execute.
+  let output = pie.require(&task)?;
+  assert_eq!(output.as_str(), "Hello, World!");
+  // Nothing changed: no execute
+  pie.require_then_assert_no_execute(&task)?;
+  Ok(())
+}
+
+#[test]
+fn test_require_file() {
+  let file = PathBuf::from("in.txt");
+  let task = ReadFile(file.clone(), FileStamper::Modified, None);
+  scenario()
+    .write(&file, "HELLO WORLD!")
+    // 1) Require task and expect that it is executed because it is new.
+    .require(&task).expect_executed([&task]).expect_string("HELLO WORLD!")
+    // 2) Require task again and expect that it is not executed because its file dependency consistent.
+    .require(&task).expect_not_executed([&task]).expect_string("HELLO WORLD!")
+    // 3) Change required file such that the file dependency of the task becomes inconsistent.
+    .write(&file, "!DLROW OLLEH")
+    // 4) Require task again and expect that it is re-executed because its file dependency is inconsistent.
+    .require(&task).expect_executed([&task]).expect_string("!DLROW OLLEH");
+
+  // Repeat the test with `FileStamper::Exists`, which results in a different outcome.
+  let task = ReadFile(file.clone(), FileStamper::Exists, None);
+  scenario()
+    .write(&file, "HELLO WORLD!")
+    // 1) Require task and expect that it is executed because it is new.
+    .require(&task).expect_executed([&task]).expect_string("HELLO WORLD!")
+    // 2) Require task again and expect that it is not executed because its file dependency is consistent.
+    .require(&task).expect_not_executed([&task]).expect_string("HELLO WORLD!")
+    // 3) Change required file, but the file dependency of the task stays consistent.
+    .write(&file, "!DLROW OLLEH")
+    // 4) Require task again and expect that it is not executed because its file dependency is still consistent.
+    .require(&task).expect_not_executed([&task]).expect_string("HELLO WORLD!");
+}
+
+#[test]
+fn test_require_provided_file() {
+  let input_file = PathBuf::from("in.txt");
+  let output_file = PathBuf::from("out.txt");
+  let read_input = ReadFile(input_file.clone(), FileStamper::Modified, None);
+  let write = WriteFile(Box::new(read_input.clone()), output_file.clone(), FileStamper::Modified);
+  let read_output = ReadFile(output_file.clone(), FileStamper::Modified, Some(Box::new(write.clone())));
+  scenario()
+    .write(&input_file, "Hello")
+    // All tasks are new, and `read_output` requires `write`, which requires `read_input`.
+    .require(&read_output)
+    .expect_executed([&read_input, &write, &read_output])
+    .expect_order(&read_input, &write)
+    .expect_order(&write, &read_output)
+    .expect_string("Hello")
+    // Nothing changed: no execute.
+    .require(&read_output)
+    .expect_not_executed([&read_input, &write, &read_output])
+    // Change the input file, propagating the change through all tasks.
+    .write(&input_file, "World")
+    .require(&read_output)
+    .expect_executed([&read_input, &write, &read_output])
+    .expect_string("World");
+}
+
+#[test]
+#[should_panic(expected = "build log of step 2")]
+fn test_scenario_failure_prints_build_log() {
+  let task = Return("Hello, World!");
+  scenario()
+    .require(&task).expect_executed([&task])
+    .require(&task).expect_executed([&task]);
+}
+
+#[test]
+fn test_require_task() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+
+  let file = PathBuf::from("in.txt");
+  pie.file_system().write(&file, "HELLO WORLD!")?;
+  let read = ReadFile(file.clone(), FileStamper::Modified, None);
+  let lower = ToLower(Box::new(read.clone()));
+
+  // 1) Require `ToLower` and assert that both tasks are executed in dependency order, because both tasks are new:
+  // → ToLower
+  //   ▶ ToLower [reason: new]
+  //     → ReadFile
+  //       ▶ ReadFile [reason: new]
+  //         - `file`
+  //       ◀ Ok(String("HELLO WORLD!"))
+  //     ← Ok(String("HELLO WORLD!"))
+  //   ◀ Ok(String("hello world!"))
+  // ← Ok(String("hello world!"))
+  // 🏁
+  let output = pie.require_then_assert(&lower, |tracker| {
+    // `ToLower` is required and executed, and its require and execute are temporally sound.
+    let lower_require = assert_matches!(tracker.first_require_task_range(&lower), Some(r) => r);
+    let lower_execute = assert_matches!(tracker.first_execute_range(&lower), Some(r) => r);
+    assert_task_temporally_sound(&lower_require, &lower_execute);
+
+    // `ReadFile` is required and executed, and its require and execute are temporally sound.
+    let read_require = assert_matches!(tracker.first_require_task_range(&read), Some(r) => r);
+    let read_execute = assert_matches!(tracker.first_execute_range(&read), Some(r) => r);
+    assert_task_temporally_sound(&read_require, &read_execute);
+
+    // Sanity check: `file` is required.
+    let file_require = assert_matches!(tracker.first_require_file_index(&file), Some(i) => i);
+
+    // `ReadFile` is required while `ToLower` is being required.
+    assert!(read_require.start() > lower_require.start());
+    assert!(lower_require.end() > read_require.end());
+
+    // Exactly `ToLower` and `ReadFile` are executed, and `ReadFile` is executed while `ToLower` is being executed.
+    tracker.assert_executed_exactly(&[&lower, &read]);
+    tracker.assert_execution_nested_in(&read, &lower);
+
+    // Sanity check: `ReadFile` requires `file` while executing.
+    assert!(file_require > read_execute.start());
+    assert!(read_execute.end() > file_require);
+  })?;
+  assert_eq!(output.as_str(), "hello world!");
+
+  // 2) Require `ToLower` again and assert that no tasks are executed because all dependencies are consistent:
+  // → ToLower
+  //   ? ReadFile
+  //     ✓ `file`
+  //   ✓ ReadFile
+  // ← Ok(String("hello world!"))
+  // 🏁
+  let output = pie.require_then_assert_no_execute(&lower)?;
+  assert_eq!(output.as_str(), "hello world!");
+
+  // Change `file` such that the file dependency of `ReadFile` becomes inconsistent.
+  pie.file_system().write(&file, "!DLROW OLLEH")?;
+
+  // 3) Require `ToLower` and assert that both tasks are re-executed in reverse dependency order:
+  // → ToLower
+  //   ? ReadFile
+  //     ✗ `file` [inconsistent: modified file stamp change]
+  //     ▶ ReadFile [reason: `file` is inconsistent due to modified file stamp change]
+  //       - `file`
+  //     ◀ Ok(String("!DLROW OLLEH")) [note: returns a different output!]
+  //   ✗ ReadFile [inconsistent: equals output stamp change]
+  //   ▶ ToLower [reason: ReadFile is inconsistent due to equals output stamp change]
+  //     → ReadFile
+  //     ← Ok(String("!DLROW OLLEH")) [note: skipped checking `read` because it is already consistent this session!]
+  //   ◀ Ok(String("!dlrow olleh"))
+  // ← Ok(String("!dlrow olleh"))
+  // 🏁
+  let output = pie.require_then_assert(&lower, |tracker| {
+    // Sanity checks: `ToLower` and `ReadFile` are required and executed, and `file` is required.
+    let lower_require = assert_matches!(tracker.first_require_task_range(&lower), Some(r) => r);
+    let lower_execute = assert_matches!(tracker.first_execute_range(&lower), Some(r) => r);
+    assert_task_temporally_sound(&lower_require, &lower_execute);
+    let read_require = assert_matches!(tracker.first_require_task_range(&read), Some(r) => r);
+    let read_execute = assert_matches!(tracker.first_execute_range(&read), Some(r) => r);
+    assert_task_temporally_sound(&read_require, &read_execute);
+    let file_require = assert_matches!(tracker.first_require_file_index(&file), Some(i) => i);
+
+    // Sanity check: `ReadFile` requires `file` while executing.
+    assert!(file_require > read_execute.start());
+    assert!(read_execute.end() > file_require);
+
+    // `ToLower` is executed after `ReadFile` has been executed, and thus after `file` has been required.
+    assert_eq!(tracker.executed_tasks(), vec![&read, &lower]);
+    assert!(lower_execute.start() > read_execute.end());
+    tracker.assert_required_before(&file, &lower);
+    // `ReadFile` is executed while `ToLower` is being required.
+    assert!(read_execute.start() > lower_require.start());
+    assert!(lower_require.end() > read_execute.end());
+  })?;
+  assert_eq!(output.as_str(), "!dlrow olleh");
+
+  // Change `file` such that the file dependency of `ReadFile` becomes inconsistent, but still has the same content.
+  pie.file_system().write(&file, "!DLROW OLLEH")?;
+
+  let output = pie.require_then_assert(&lower, |tracker| {
+    // `ReadFile` needs to be executed due to its `file` dependency being inconsistent (modified stamp changed), but
+    // `ToLower` is not executed, because its task dependency to `ReadFile` is consistent (equals stamp is the same).
+    tracker.assert_executed_exactly(&[&read]);
+  })?;
+  assert_eq!(output.as_str(), "!dlrow olleh");
+
+  Ok(())
+}
+
+#[test]
+fn test_spans() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+
+  let file = PathBuf::from("in.txt");
+  pie.file_system().write(&file, "HELLO WORLD!")?;
+  let read = ReadFile(file.clone(), FileStamper::Modified, None);
+  let lower = ToLower(Box::new(read.clone()));
+
+  // Spans are properly nested, both for a new build and for an incremental build that checks dependencies.
+  for _ in 0..2 {
+    pie.require_then_assert(&lower, |tracker| {
+      let mut stack = Vec::new();
+      for event in tracker.iter() {
+        let span = event.span();
+        if event.is_start() {
+          assert_eq!(span.parent, stack.last().copied(), "parent of {:?} is not the enclosing span", event);
+          stack.push(span.id);
+        } else {
+          assert_eq!(stack.pop(), Some(span.id), "end event {:?} does not close the innermost span", event);
+        }
+      }
+      assert!(stack.is_empty());
+    })?;
+  }
+
+  Ok(())
+}
+
+#[test]
+fn test_execute_end_dependencies() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+
+  let file = PathBuf::from("in.txt");
+  pie.file_system().write(&file, "HELLO WORLD!")?;
+  let read = ReadFile(file.clone(), FileStamper::Modified, None);
+  let lower = ToLower(Box::new(read.clone()));
+
+  pie.require_then_assert(&lower, |tracker| {
+    // `ReadFile` depends on `file` only.
+    let (_, read_end) = assert_matches!(tracker.first_execute(&read), Some(e) => e);
+    assert_matches!(read_end.dependencies.as_slice(), [Dependency::RequireFile(d)] if d.path() == &file);
+    // `ToLower` depends on `ReadFile` only.
+    let (_, lower_end) = assert_matches!(tracker.first_execute(&lower), Some(e) => e);
+    assert_matches!(lower_end.dependencies.as_slice(), [Dependency::RequireTask(d)] if d.task() == &read);
+  })?;
+
+  Ok(())
+}
+
+#[test]
+// The offending execution of `Return("Hello")` is marked in the event window.
+#[should_panic(expected = ">    4: ExecuteStart")]
+fn test_assert_executed_exactly_prints_event_window() {
+  let mut pie = test_pie();
+  let hello = Return("Hello");
+  let task = ToLower(Box::new(hello.clone()));
+  pie.require_then_assert(&task, |tracker| {
+    tracker.assert_executed_exactly(&[&task]);
+  }).unwrap();
+}
+
+/// Assert that task requires and executes are temporally sound.
+fn assert_task_temporally_sound(require: &RangeInclusive<usize>, execute: &RangeInclusive<usize>) {
+  // Require and execute ends come after require and execute starts.
+  assert!(require.end() > require.start());
+  assert!(execute.end() > execute.start());
+  // Task require ends should be later than their executes.
+  assert!(require.end() > execute.end());
+}
+
+#[test]
+fn test_no_superfluous_task_dependencies() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+
+  let file = PathBuf::from("in.txt");
+  pie.file_system().write(&file, "Hello, World!")?;
+  let read = ReadFile(file.clone(), FileStamper::Modified, None);
+  let lower = ToLower(Box::new(read.clone()));
+  let upper = ToUpper(Box::new(lower.clone()));
+
+  // Require `ToLower` and assert that `ReadFile` and `ToLower` are executed because they are new, but not `ToUpper`,
+  // because it not required by anything. `ToLower` will return `"hello, world!"`.
+  let output = pie.require_then_assert(&lower, |tracker| {
+    assert!(tracker.one_execute_of(&read));
+    assert!(tracker.one_execute_of(&lower));
+    assert!(!tracker.any_execute_of(&upper));
+  })?;
+  assert_eq!(output.as_str(), "hello, world!");
+
+  // Require `ToUpper` and assert that it is executed because it is new, but not `ReadFile` nor `ToLower` because their
+  // dependencies are consistent.
+  let output = pie.require_then_assert(&upper, |tracker| {
+    assert!(!tracker.any_execute_of(&read));
+    assert!(!tracker.any_execute_of(&lower));
+    assert!(tracker.one_execute_of(&upper));
+  })?;
+  assert_eq!(output.as_str(), "HELLO, WORLD!");
+
+  // Change `file` such that the file dependency of `ReadFile` becomes inconsistent. However, we change its contents
+  // only slightly by turning 'l' characters into capital 'L' characters. Therefore, `ToLower` will still return
+  // `"hello, world!"`.
+  pie.file_system().write(&file, "HeLLo, WorLd!")?;
+
+  // Require `ToUpper` but assert that it is _not executed_ because `ToUpper`'s task dependency to `ToLower` is still
+  // consistent, because `ToLower` still returns `"hello, world!"` which is the same as last time.
+  let output = pie.require_then_assert(&upper, |tracker| {
+    assert!(tracker.one_execute_of(&read));
+    assert!(tracker.one_execute_of(&lower));
+    assert!(!tracker.any_execute_of(&upper));
+  })?;
+  assert_eq!(output.as_str(), "HELLO, WORLD!");
+
+  Ok(())
+}
+
+
+// Overlapping provided file tests
+
+#[test]
+#[should_panic(expected = "Overlapping provided file")]
+fn test_overlapping_provided_file_panics() {
+  fn run() -> Result<(), io::Error> {
+    let mut pie = test_pie();
+
+    let output_file = PathBuf::from("out.txt");
+    let input_file = PathBuf::from("in.txt");
+    pie.file_system().write(&input_file, "Hello, World!")?;
+
+    let seq = Sequence(vec![
+      WriteFile(Box::new(Return("Hi there")), output_file.clone(), FileStamper::Modified),
+      WriteFile(Box::new(ReadFile(input_file.clone(), FileStamper::Modified, None)), output_file.clone(), FileStamper::Modified),
+    ]);
+    // Require `seq`, resulting in overlapping provided files between the two different write tasks.
+    pie.require(&seq)?;
+
+    Ok(())
+  }
+  run().unwrap()
+}
+
+#[test]
+#[should_panic(expected = "Overlapping provided file")]
+fn test_require_overlapping_provided_file_panics() {
+  fn run() -> Result<(), io::Error> {
+    let mut pie = test_pie();
+
+    let output_file = PathBuf::from("out.txt");
+
+    let write_1 = WriteFile(Box::new(Return("Hi there")), output_file.clone(), FileStamper::Modified);
+    pie.require(&write_1)?;
+
+    // `write_2` is a different task, so requiring it will cause overlap.
+    let write_2 = WriteFile(Box::new(Return("Hello, World!")), output_file.clone(), FileStamper::Modified);
+    pie.require(&write_2)?;
+
+    Ok(())
+  }
+  run().unwrap()
+}
+
+#[test]
+fn test_same_task_no_overlap() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+
+  let output_file = PathBuf::from("out.txt");
+  let input_file = PathBuf::from("in.txt");
+  pie.file_system().write(&input_file, "Hello, World!")?;
+
+  let read = ReadFile(input_file.clone(), FileStamper::Modified, None);
+  let write = WriteFile(Box::new(read), output_file.clone(), FileStamper::Modified);
+
+  pie.require_then_assert_one_execute(&write)?;
+  // Requiring and executing the same task does not cause overlap.
+  pie.file_system().write(&input_file, "World, Hello?")?;
+  pie.require_then_assert_one_execute(&write)?;
+  // Even when required indirectly.
+  pie.file_system().write(&input_file, "Hello, World!")?;
+  pie.require_then_assert_one_execute(&Sequence(vec![write]))?;
+
+  Ok(())
+}
+
+#[test]
+fn test_separate_output_files() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+
+  let ret = Return("Hi there");
+  let output_file_1 = PathBuf::from("out_1.txt");
+  let write_1 = WriteFile(Box::new(ret.clone()), output_file_1.clone(), FileStamper::Modified);
+
+  let input_file = PathBuf::from("in.txt");
+  pie.file_system().write(&input_file, "Hello, World!")?;
+  let read = ReadFile(input_file.clone(), FileStamper::Modified, None);
+  let output_file_2 = PathBuf::from("out_2.txt");
+  let write_2 = WriteFile(Box::new(read.clone()), output_file_2.clone(), FileStamper::Modified);
+
+  let seq = Sequence(vec![write_1.clone(), write_2.clone()]);
+
+  pie.require(&seq)?;
+  assert_eq!(pie.file_system().read_to_string(&output_file_1)?, "Hi there");
+  assert_eq!(pie.file_system().read_to_string(&output_file_2)?, "Hello, World!");
+
+  pie.file_system().write(&input_file, "World, Hello?")?;
+
+  // Require `write_1` to make `output_file_1` consistent.
+  pie.require_then_assert_no_execute(&write_1)?;
+  assert_eq!(pie.file_system().read_to_string(&output_file_1)?, "Hi there");
+  // Require `write_2` to make `output_file_2` consistent.
+  pie.require_then_assert_one_execute(&write_2)?;
+  assert_eq!(pie.file_system().read_to_string(&output_file_2)?, "World, Hello?");
+
+  Ok(())
+}
+
+
+// Hidden dependency tests
+
+#[test]
+#[should_panic(expected = "Hidden dependency")]
+fn test_require_hidden_dependency_panics() {
+  fn run() -> Result<(), io::Error> {
+    let mut pie = test_pie();
+
+    let file = PathBuf::from("in_out.txt");
+    pie.file_system().write(&file, "Hello, World!")?;
+
+    let read = ReadFile(file.clone(), FileStamper::Modified, None);
+    let write = WriteFile(Box::new(Return("Hi there")), file.clone(), FileStamper::Modified);
+
+    pie.require_then_assert_one_execute(&write)?;
+    pie.require_then_assert_one_execute(&read)?;
+
+    Ok(())
+  }
+  run().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Hidden dependency")]
+fn test_provide_hidden_dependency_panics() {
+  fn run() -> Result<(), io::Error> {
+    let mut pie = test_pie();
+
+    let file = PathBuf::from("in_out.txt");
+    pie.file_system().write(&file, "Hello, World!")?;
+
+    let read = ReadFile(file.clone(), FileStamper::Modified, None);
+    let write = WriteFile(Box::new(Return("Hi there")), file.clone(), FileStamper::Modified);
+
+    pie.require_then_assert_one_execute(&read)?;
+    pie.require_then_assert_one_execute(&write)?;
+
+    Ok(())
+  }
+  run().unwrap();
+}
+
+#[test]
+fn test_non_hidden_dependency() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+
+  let file = PathBuf::from("in_out.txt");
+  pie.file_system().write(&file, "Hello, World!")?;
+
+  let input_file = PathBuf::from("in.txt");
+  pie.file_system().write(&input_file, "Hi There!")?;
+  let read_input = ReadFile(input_file.clone(), FileStamper::Modified, None);
+  let write = WriteFile(Box::new(read_input.clone()), file.clone(), FileStamper::Modified);
+  let read = ReadFile(file.clone(), FileStamper::Modified, Some(Box::new(write.clone())));
+
+  // Require `read`, which requires `write` to update the provided file. All tasks are executed because they are new.
+  let output = pie.require_then_assert(&read, |tracker| {
+    assert!(tracker.one_execute_of(&read));
+    assert!(tracker.one_execute_of(&write));
+    assert!(tracker.one_execute_of(&read_input));
+  })?;
+  // `read` should output what `write` wrote, which is what `read_input` read from `input_file`.
+  assert_eq!(output.as_str(), "Hi There!");
+
+  // Remove `file`.
+  pie.file_system().remove_file(&file)?;
+  assert!(!pie.file_system().exists(&file));
+
+  // Confirm the provided file is re-generated.
+  let output = pie.require_then_assert(&read, |tracker| {
+    // `write` should execute to re-generate the provided file.
+    assert!(tracker.one_execute_of(&write));
+    // `read_input` is not executed because its file dependency to `input_file` is consistent.
+    assert!(!tracker.any_execute_of(&read_input));
+    // `read` is executed because its `file` dependency is inconsistent, due to it having a new modified date. If we use
+    // a file hash stamper, we can prevent this re-execution.
+    assert!(tracker.one_execute_of(&read));
+  })?;
+  assert!(pie.file_system().exists(&file));
+  assert_eq!(output.as_str(), "Hi There!");
+
+  // Change `read_input` and confirm the change is propagated to `read`.
+  pie.file_system().write(&input_file, "Hello There!")?;
+  let output = pie.require(&read)?;
+  assert_eq!(output.as_str(), "Hello There!");
+
+  Ok(())
+}
+
+
+// Cycle tests
+
+#[test]
+fn test_external_change_between_builds_of_session() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let path = PathBuf::from("in.txt");
+  pie.file_system().write(&path, "Hello")?;
+  let read = ReadFile(path.clone(), FileStamper::Modified, None);
+  let read_with_origin = ReadFile(path.clone(), FileStamper::Modified, Some(Box::new(Return("Hi"))));
+  pie.require(&read)?;
+  pie.require(&read_with_origin)?;
+  let file_system = pie.file_system().clone();
+
+  // Both tasks require the same file with the same stamper. The file is changed externally after `read` was checked,
+  // so the stamp that was cached while checking `read` must not be used when checking `read_with_origin`.
+  let mut session = pie.new_session();
+  assert_eq!(session.require(&read)?.as_str(), "Hello");
+  file_system.write(&path, "World")?;
+  assert_eq!(session.require(&read_with_origin)?.as_str(), "World");
+
+  Ok(())
+}
+
+#[test]
+#[should_panic(expected = "creating cycle:\n  RequireSelf\n  → RequireSelf (new dependency)")]
+fn require_self_panics() {
+  let mut pie = test_pie();
+  pie.require(&RequireSelf).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "creating cycle:\n  RequireB\n  → RequireA (new dependency)\n  → RequireB (reserved dependency)")]
+fn require_cycle_a_panics() {
+  let mut pie = test_pie();
+  pie.require(&RequireA).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "creating cycle:\n  RequireA\n  → RequireB (new dependency)\n  → RequireA (reserved dependency)")]
+fn require_cycle_b_panics() {
+  let mut pie = test_pie();
+  pie.require(&RequireB).unwrap();
+}
+
+
+// Requiring a task twice tests
+
+#[test]
+fn test_require_same_task_twice() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let hello = Return("Hello");
+  let task = Sequence(vec![hello.clone(), hello.clone()]);
+  // `hello` is required twice by the same task, but only executed once.
+  pie.require_then_assert(&task, |tracker| {
+    assert!(tracker.one_execute_of(&hello));
+  })?;
+  pie.require_then_assert_no_execute(&task)?;
+  Ok(())
+}
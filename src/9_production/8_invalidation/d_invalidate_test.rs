@@ -0,0 +1,132 @@
+use std::io;
+use std::path::PathBuf;
+
+use pie::fs::{FileSystem, PathNormalization};
+use pie::stamp::FileStamper;
+
+use crate::common::{test_pie, TestOutput, TestPieExt};
+use crate::common::TestTask::*;
+
+mod common;
+
+#[test]
+fn test_invalidate_task() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let string = Return("Hello");
+  let task = ToLower(Box::new(string.clone()));
+  pie.require_then_assert_one_execute(&task)?;
+  pie.require_then_assert_no_execute(&task)?;
+
+  // Invalidated tasks are executed, but their dependents are not when the new output is consistent.
+  assert!(pie.invalidate_task(&string));
+  pie.require_then_assert(&task, |tracker| {
+    assert!(tracker.one_execute_of(&string));
+    assert!(!tracker.any_execute_of(&task));
+  })?;
+  pie.require_then_assert_no_execute(&task)?;
+
+  // Invalidated tasks are executed even when they are not the required task.
+  assert!(pie.invalidate_task(&task));
+  pie.require_then_assert(&task, |tracker| {
+    assert!(tracker.one_execute_of(&task));
+    assert!(!tracker.any_execute_of(&string));
+  })?;
+
+  // Unknown tasks cannot be invalidated.
+  assert!(!pie.invalidate_task(&Return("World")));
+
+  Ok(())
+}
+
+#[test]
+fn test_invalidate_file() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  pie.set_path_normalization(PathNormalization::none().with_lexical(true));
+  let path = PathBuf::from("in.txt");
+  let clock = pie.file_system().clock();
+  pie.file_system().write(&path, "Hello")?;
+  let task = ReadFile(path.clone(), FileStamper::Modified, None);
+  assert_eq!(pie.require_then_assert_one_execute(&task)?, TestOutput::String("Hello".to_string()));
+
+  // Write the file while preserving its modification time, which is not detected.
+  pie.file_system().set_clock(clock);
+  pie.file_system().write(&path, "World")?;
+  assert_eq!(pie.require_then_assert_no_execute(&task)?, TestOutput::String("Hello".to_string()));
+
+  // Tasks that require an invalidated file are executed, also when invalidated through an alias of its path.
+  assert!(pie.invalidate_file("./in.txt"));
+  assert_eq!(pie.require_then_assert_one_execute(&task)?, TestOutput::String("World".to_string()));
+  pie.require_then_assert_no_execute(&task)?;
+
+  // Unknown files cannot be invalidated.
+  assert!(!pie.invalidate_file("out.txt"));
+
+  Ok(())
+}
+
+#[test]
+fn test_invalidate_provided_file() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let path = PathBuf::from("out.txt");
+  let write = WriteFile(Box::new(Return("Hello")), path.clone(), FileStamper::Modified);
+  let read = ReadFile(path.clone(), FileStamper::Modified, Some(Box::new(write.clone())));
+  pie.require(&read)?;
+
+  // The task that provides an invalidated file is executed, along with the tasks that require the file.
+  assert!(pie.invalidate_file(&path));
+  pie.require_then_assert(&read, |tracker| {
+    assert!(tracker.one_execute_of(&write));
+    assert!(tracker.one_execute_of(&read));
+  })?;
+
+  Ok(())
+}
+
+#[test]
+fn test_remove_task() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  let path = PathBuf::from("out.txt");
+  let write = WriteFile(Box::new(Return("Hello")), path.clone(), FileStamper::Modified);
+  let read = ReadFile(path.clone(), FileStamper::Modified, Some(Box::new(write.clone())));
+  pie.require(&read)?;
+
+  // Removed tasks are executed again as new tasks, and their dependents are executed.
+  assert!(pie.remove_task(&write));
+  assert!(!pie.remove_task(&write));
+  pie.require_then_assert(&read, |tracker| {
+    assert!(tracker.one_execute_of(&write));
+    assert!(tracker.one_execute_of(&read));
+  })?;
+
+  // Files provided by removed tasks are kept, and can be provided by other tasks without overlap.
+  assert!(pie.remove_task(&write));
+  assert!(pie.remove_task(&read));
+  assert!(pie.file_system().exists(&path));
+  let other_write = WriteFile(Box::new(Return("World")), path.clone(), FileStamper::Modified);
+  let other_read = ReadFile(path.clone(), FileStamper::Modified, Some(Box::new(other_write)));
+  assert_eq!(pie.require(&other_read)?, TestOutput::String("World".to_string()));
+
+  Ok(())
+}
+
+#[test]
+fn test_reset() -> Result<(), io::Error> {
+  let mut pie = test_pie();
+  pie.set_output_memory_budget(Some(1024));
+  let path = PathBuf::from("out.txt");
+  let write = WriteFile(Box::new(Return("Hello")), path.clone(), FileStamper::Modified);
+  let read = ReadFile(path.clone(), FileStamper::Modified, Some(Box::new(write.clone())));
+  pie.require(&read)?;
+
+  // All tasks are executed again after a reset, and settings are kept.
+  pie.reset();
+  assert!(!pie.invalidate_task(&read));
+  assert!(!pie.invalidate_file(&path));
+  assert_eq!(pie.output_memory_budget(), Some(1024));
+  pie.require_then_assert(&read, |tracker| {
+    assert!(tracker.one_execute_of(&write));
+    assert!(tracker.one_execute_of(&read));
+  })?;
+
+  Ok(())
+}
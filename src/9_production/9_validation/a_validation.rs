@@ -0,0 +1,42 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::path::PathBuf;
+
+/// Violation of an invariant of the dependency graph of [`Pie`](crate::Pie), found by
+/// [validating](crate::Pie::validate) it. Violations indicate a bug in PIE, or in code that changes its dependency
+/// graph.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Violation<T> {
+  /// Task `src` has a reserved task require dependency to task `dst`, while no build is executing it.
+  ReservedRequireTask { src: T, dst: T },
+  /// File `path` is provided by multiple `tasks`.
+  OverlappingProvidedFile { path: PathBuf, tasks: Vec<T> },
+  /// File `path` is required by `requiring_task` without a dependency to `providing_task` which provides the file.
+  HiddenDependency { path: PathBuf, requiring_task: T, providing_task: T },
+  /// The task to node mapping for `task` does not agree with the node data of `task`.
+  TaskNodeMismatch { task: T },
+  /// The file to node mapping for file `path` does not agree with the node data of the file.
+  FileNodeMismatch { path: PathBuf },
+  /// Consistent `task`, which is not marked for execution, does not have an output, and its output was not evicted.
+  MissingOutput { task: T },
+}
+
+impl<T: Debug> Display for Violation<T> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Violation::ReservedRequireTask { src, dst } =>
+        write!(f, "leftover reserved task require dependency from task '{:?}' to task '{:?}'", src, dst),
+      Violation::OverlappingProvidedFile { path, tasks } =>
+        write!(f, "overlapping provided file; file '{}' is provided by tasks: {:?}", path.display(), tasks),
+      Violation::HiddenDependency { path, requiring_task, providing_task } =>
+        write!(f, "hidden dependency; file '{}' is required by task '{:?}' without a dependency to providing task: {:?}",
+          path.display(), requiring_task, providing_task),
+      Violation::TaskNodeMismatch { task } =>
+        write!(f, "task to node mapping does not agree with node data of task '{:?}'", task),
+      Violation::FileNodeMismatch { path } =>
+        write!(f, "file to node mapping does not agree with node data of file '{}'", path.display()),
+      Violation::MissingOutput { task } =>
+        write!(f, "consistent task '{:?}' does not have an output", task),
+    }
+  }
+}
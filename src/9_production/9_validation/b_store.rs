@@ -0,0 +1,1269 @@
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use pie_graph::{DAG, Node};
+
+use crate::dependency::{Dependency, FileDependency, TaskDependency};
+use crate::diagnostic::Diagnostic;
+use crate::lru::OutputLru;
+use crate::reachability::ReachabilityIndex;
+use crate::Task;
+use crate::validation::Violation;
+
+/// Stores files and tasks, and their dependencies, in a DAG (directed acyclic graph). Provides operations to mutate
+/// and query this graph.
+///
+/// Tasks are interned: each task is stored once behind an [`Arc`], shared by the graph, the task to node mapping, and
+/// task dependencies. Outputs are also stored behind an [`Arc`], so that getting an output is a cheap clone.
+///
+/// When an output memory budget is set, the least recently used outputs are evicted when the total size of stored
+/// outputs exceeds the budget. Evicting an output keeps the task and its dependencies in the graph.
+pub struct Store<T, O> {
+  graph: DAG<NodeData<T, O>, Dependency<T, O>>,
+  file_to_node: HashMap<PathBuf, FileNode>,
+  task_to_node: HashMap<Arc<T>, TaskNode>,
+  reachability: ReachabilityIndex,
+  output_lru: OutputLru<TaskNode>,
+  output_memory_budget: Option<usize>,
+}
+
+#[derive(Debug)]
+enum NodeData<T, O> {
+  File(PathBuf),
+  Task {
+    task: Arc<T>,
+    output: Option<Arc<O>>,
+    /// Number of times a new output was set, used to check evicted output stamps.
+    output_generation: u64,
+    output_evicted: bool,
+    /// Whether the task must be executed the next time it is checked, even if all its dependencies are consistent.
+    marked_for_execution: bool,
+    /// Diagnostics emitted by the task during its last execution.
+    diagnostics: Vec<Diagnostic>,
+    /// Text logged by the task during its last execution.
+    log: Vec<u8>,
+  },
+}
+
+/// Newtype for file `Node`s.
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct FileNode(Node);
+
+impl Borrow<Node> for &FileNode {
+  fn borrow(&self) -> &Node { &self.0 }
+}
+
+/// Newtype for task `Node`s.
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct TaskNode(Node);
+
+/// Cycle of task require dependencies that would be created by adding a task require dependency from `src` to `dst`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TaskCycle {
+  /// Path of task require dependencies from `dst` back to `src`. Each element is a task that is required by the
+  /// previous task (by `dst` for the first element), and whether that dependency is reserved (`true`) or completed
+  /// (`false`). The last element is `src`, and the path is empty if `src` and `dst` are the same task.
+  pub path: Vec<(TaskNode, bool)>,
+}
+
+impl Borrow<Node> for &TaskNode {
+  fn borrow(&self) -> &Node { &self.0 }
+}
+
+impl<T: Task> Default for Store<T, T::Output> {
+  fn default() -> Self {
+    Self {
+      graph: DAG::default(),
+      file_to_node: HashMap::default(),
+      task_to_node: HashMap::default(),
+      reachability: ReachabilityIndex::default(),
+      output_lru: OutputLru::default(),
+      output_memory_budget: None,
+    }
+  }
+}
+
+impl<T: Task> Store<T, T::Output> {
+  /// Gets the file node for `path`, or creates a file node by adding it to the dependency graph.
+  pub fn get_or_create_file_node(&mut self, path: impl AsRef<Path>) -> FileNode {
+    let path = path.as_ref();
+    if let Some(file_node) = self.file_to_node.get(path) {
+      *file_node
+    } else {
+      let node = self.graph.add_node(NodeData::File(path.to_path_buf()));
+      let node = FileNode(node);
+      self.file_to_node.insert(path.to_path_buf(), node);
+      node
+    }
+  }
+  /// Gets the file node for `path`, or `None` if `path` was not added to the dependency graph.
+  pub fn get_file_node(&self, path: impl AsRef<Path>) -> Option<FileNode> {
+    self.file_to_node.get(path.as_ref()).copied()
+  }
+  /// Gets the path for `node`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `node` was not found in the dependency graph.
+  #[allow(dead_code)]
+  pub fn get_file_path(&self, node: &FileNode) -> &PathBuf {
+    let Some(NodeData::File(path)) = self.graph.get_node_data(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    path
+  }
+
+  /// Gets the task node for `task`, or creates a task node by adding it to the dependency graph.
+  pub fn get_or_create_task_node(&mut self, task: &T) -> TaskNode {
+    if let Some(node) = self.task_to_node.get(task) {
+      *node
+    } else {
+      let task = Arc::new(task.clone());
+      let node = self.graph.add_node(NodeData::Task {
+        task: task.clone(),
+        output: None,
+        output_generation: 0,
+        output_evicted: false,
+        marked_for_execution: false,
+        diagnostics: Vec::new(),
+        log: Vec::new(),
+      });
+      let node = TaskNode(node);
+      self.task_to_node.insert(task, node);
+      node
+    }
+  }
+  /// Gets the task node for `task`, or `None` if `task` was not added to the dependency graph.
+  pub fn get_task_node(&self, task: &T) -> Option<TaskNode> {
+    self.task_to_node.get(task).copied()
+  }
+  /// Gets the task for `node`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `node` was not found in the dependency graph.
+  pub fn get_task(&self, node: &TaskNode) -> &T {
+    self.get_task_arc(node)
+  }
+  /// Gets the interned task for `node`, which can be cheaply cloned.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `node` was not found in the dependency graph.
+  pub fn get_task_arc(&self, node: &TaskNode) -> &Arc<T> {
+    let Some(NodeData::Task { task, .. }) = self.graph.get_node_data(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    task
+  }
+}
+
+impl<T: Task> Store<T, T::Output> {
+  /// Checks whether task `node` has an output. Returns `false` if `node` does not have an output.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `node` was not found in the dependency graph.
+  pub fn task_has_output(&self, node: &TaskNode) -> bool {
+    let Some(NodeData::Task { output, .. }) = self.graph.get_node_data(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    output.is_some()
+  }
+  /// Checks whether the output of task `node` was evicted. Returns `false` if `node` has an output, or if it was never
+  /// executed.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `node` was not found in the dependency graph.
+  pub fn task_output_evicted(&self, node: &TaskNode) -> bool {
+    let Some(NodeData::Task { output_evicted, .. }) = self.graph.get_node_data(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    *output_evicted
+  }
+  /// Checks whether task `node` is marked for execution, meaning that it must be executed the next time it is checked,
+  /// even if all its dependencies are consistent.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `node` was not found in the dependency graph.
+  pub fn task_marked_for_execution(&self, node: &TaskNode) -> bool {
+    let Some(NodeData::Task { marked_for_execution, .. }) = self.graph.get_node_data(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    *marked_for_execution
+  }
+  /// Marks task `node` for execution, such that it is executed the next time it is checked. The mark is cleared when
+  /// the task is reset.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `node` was not found in the dependency graph.
+  pub fn mark_task_for_execution(&mut self, node: &TaskNode) {
+    let Some(NodeData::Task { marked_for_execution, .. }) = self.graph.get_node_data_mut(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    *marked_for_execution = true;
+  }
+  /// Gets the diagnostics emitted by task `node` during its last execution.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `node` was not found in the dependency graph.
+  pub fn get_task_diagnostics(&self, node: &TaskNode) -> &[Diagnostic] {
+    let Some(NodeData::Task { diagnostics, .. }) = self.graph.get_node_data(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    diagnostics
+  }
+  /// Adds `diagnostic` to the diagnostics of task `node`. Diagnostics are cleared when the task is reset.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `node` was not found in the dependency graph.
+  pub fn add_task_diagnostic(&mut self, node: &TaskNode, diagnostic: Diagnostic) {
+    let Some(NodeData::Task { diagnostics, .. }) = self.graph.get_node_data_mut(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    diagnostics.push(diagnostic);
+  }
+
+  /// Gets the text logged by task `node` during its last execution.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `node` was not found in the dependency graph.
+  pub fn get_task_log(&self, node: &TaskNode) -> &[u8] {
+    let Some(NodeData::Task { log, .. }) = self.graph.get_node_data(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    log
+  }
+  /// Gets the mutable text logged by task `node`, for appending text to it. The log is cleared when the task is reset.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `node` was not found in the dependency graph.
+  pub fn get_task_log_mut(&mut self, node: &TaskNode) -> &mut Vec<u8> {
+    let Some(NodeData::Task { log, .. }) = self.graph.get_node_data_mut(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    log
+  }
+  /// Gets the output generation of task `node`: the number of times a new output was set for it. Restoring an evicted
+  /// output does not change the output generation.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `node` was not found in the dependency graph.
+  pub fn get_task_output_generation(&self, node: &TaskNode) -> u64 {
+    let Some(NodeData::Task { output_generation, .. }) = self.graph.get_node_data(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    *output_generation
+  }
+  /// Gets the output for task `node`, without marking it as used. Only used in tests, as contexts get outputs with
+  /// [`use_task_output`](Self::use_task_output).
+  ///
+  /// # Panics
+  ///
+  /// Panics if `node` was not found in the dependency graph, or if the task has no output.
+  #[cfg(test)]
+  pub fn get_task_output(&self, node: &TaskNode) -> &Arc<T::Output> {
+    let Some(NodeData::Task { output: Some(output), .. }) = self.graph.get_node_data(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph, or does not have an output", node);
+    };
+    output
+  }
+  /// Gets the output for task `node` if it has one, marking it as the most recently used output.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `node` was not found in the dependency graph.
+  pub fn use_task_output(&mut self, node: &TaskNode) -> Option<Arc<T::Output>> {
+    let Some(NodeData::Task { output, .. }) = self.graph.get_node_data(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    let output = output.clone();
+    if output.is_some() {
+      self.output_lru.touch(*node);
+    }
+    output
+  }
+  /// Sets the output for task `node` to `new_output`, starting a new output generation. May evict other outputs if the
+  /// output memory budget is exceeded.
+  ///
+  /// # Panics
+  ///
+  /// Panics if task `node` was not found in the dependency graph.
+  pub fn set_task_output(&mut self, node: &TaskNode, new_output: Arc<T::Output>) {
+    self.insert_task_output(node, new_output, true);
+  }
+  /// Restores the evicted output for task `node` to `output`, which must be equal to the evicted output, keeping the
+  /// output generation. May evict other outputs if the output memory budget is exceeded.
+  ///
+  /// # Panics
+  ///
+  /// Panics if task `node` was not found in the dependency graph.
+  pub fn restore_task_output(&mut self, node: &TaskNode, output: Arc<T::Output>) {
+    self.insert_task_output(node, output, false);
+  }
+  fn insert_task_output(&mut self, node: &TaskNode, new_output: Arc<T::Output>, new_generation: bool) {
+    let Some(NodeData::Task { output, output_generation, output_evicted, .. }) = self.graph.get_node_data_mut(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    self.output_lru.insert(*node, T::output_size(&new_output));
+    output.replace(new_output);
+    if new_generation {
+      *output_generation += 1;
+    }
+    *output_evicted = false;
+    self.evict_task_outputs(Some(node));
+  }
+}
+
+impl<T: Task> Store<T, T::Output> {
+  /// Gets the output memory budget in bytes, or `None` if outputs are never evicted.
+  pub fn output_memory_budget(&self) -> Option<usize> { self.output_memory_budget }
+  /// Sets the output memory budget in bytes to `budget`, immediately evicting outputs if the budget is exceeded. `None`
+  /// disables eviction.
+  pub fn set_output_memory_budget(&mut self, budget: Option<usize>) {
+    self.output_memory_budget = budget;
+    self.evict_task_outputs(None);
+  }
+
+  /// Evicts least recently used outputs until the output memory budget is no longer exceeded, but never evicts the
+  /// output of task `keep`.
+  fn evict_task_outputs(&mut self, keep: Option<&TaskNode>) {
+    let Some(budget) = self.output_memory_budget else { return; };
+    while self.output_lru.total_size() > budget {
+      let Some(node) = self.output_lru.least_recently_used() else { break; };
+      if Some(&node) == keep {
+        break; // `keep` is the most recently used output, so all other outputs are already evicted.
+      }
+      self.evict_task_output(&node);
+    }
+  }
+  /// Evicts the output of task `node`. Equality stamps of task dependencies to `node` that stamp the evicted output are
+  /// replaced by evicted stamps, so that they no longer keep the output in memory.
+  fn evict_task_output(&mut self, node: &TaskNode) {
+    let Some(NodeData::Task { output, output_generation, output_evicted, .. }) = self.graph.get_node_data_mut(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    let Some(evicted_output) = output.take() else { return; };
+    *output_evicted = true;
+    let output_generation = *output_generation;
+    self.output_lru.remove(*node);
+
+    let dependents: Vec<_> = self.graph.get_incoming_edge_nodes(node).copied().collect();
+    for dependent in dependents {
+      if let Some(Dependency::RequireTask(dependency)) = self.graph.get_edge_data_mut(dependent, node) {
+        dependency.evict_stamp(&evicted_output, output_generation);
+      }
+    }
+  }
+}
+
+impl<T: Task> Store<T, T::Output> {
+  /// Get all dependencies of task `src`.
+  ///
+  /// # Panics
+  ///
+  /// Panics in development builds if `src` was not found in the dependency graph.
+  pub fn get_dependencies_of_task<'a>(&'a self, src: &'a TaskNode) -> impl Iterator<Item=&'a Dependency<T, T::Output>> + 'a {
+    debug_assert!(self.graph.contains_node(src), "BUG: node {:?} was not found in the dependency graph", src);
+    self.graph.get_outgoing_edge_data(src)
+  }
+
+  /// Get the task node that provides file `dst`, or `None` if there is none.
+  ///
+  /// # Panics
+  ///
+  /// Panics in development builds if `dst` was not found in the dependency graph.
+  pub fn get_task_providing_file(&self, dst: &FileNode) -> Option<TaskNode> {
+    debug_assert!(self.graph.contains_node(dst), "BUG: node {:?} was not found in the dependency graph", dst);
+    self.graph.get_incoming_edges(dst).filter_map(|(n, d)|
+      if matches!(d, Dependency::ProvideFile(_)) {
+        Some(TaskNode(*n))
+      } else {
+        None
+      }
+    ).next()
+  }
+  /// Get all task nodes for tasks that require file `dst`.
+  ///
+  /// # Panics
+  ///
+  /// Panics in development builds if `dst` was not found in the dependency graph.
+  pub fn get_tasks_requiring_file<'a>(&'a self, dst: &'a FileNode) -> impl Iterator<Item=TaskNode> + '_ {
+    debug_assert!(self.graph.contains_node(dst), "BUG: node {:?} was not found in the dependency graph", dst);
+    self.graph.get_incoming_edges(dst).filter_map(|(n, d)|
+      if matches!(d, Dependency::RequireFile(_)) {
+        Some(TaskNode(*n))
+      } else {
+        None
+      }
+    )
+  }
+  /// Get all task nodes for tasks that require (or have reserved a require of) task `dst`.
+  ///
+  /// # Panics
+  ///
+  /// Panics in development builds if `dst` was not found in the dependency graph.
+  pub fn get_tasks_requiring_task<'a>(&'a self, dst: &'a TaskNode) -> impl Iterator<Item=TaskNode> + 'a {
+    debug_assert!(self.graph.contains_node(dst), "BUG: node {:?} was not found in the dependency graph", dst);
+    self.graph.get_incoming_edges(dst).filter_map(|(n, d)|
+      if matches!(d, Dependency::RequireTask(_) | Dependency::ReservedRequireTask) {
+        Some(TaskNode(*n))
+      } else {
+        None
+      }
+    )
+  }
+  /// Checks whether there is a direct or indirect (transitive) dependency from task `src` to task `dst`.
+  ///
+  /// # Panics
+  ///
+  /// Panics in development builds if `src` or `dst` were not found in the dependency graph.
+  pub fn contains_transitive_task_dependency(&self, src: &TaskNode, dst: &TaskNode) -> bool {
+    debug_assert!(self.graph.contains_node(src), "BUG: src node {:?} was not found in the dependency graph", src);
+    debug_assert!(self.graph.contains_node(dst), "BUG: dst node {:?} was not found in the dependency graph", dst);
+    self.reachability.contains_path(&self.graph, src.0, dst.0)
+  }
+
+  /// Add a file require `dependency` from task `src` to file `dst`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `src` or `dst` were not found in the dependency graph, or if a cycle is created by adding this dependency.
+  pub fn add_file_require_dependency(&mut self, src: &TaskNode, dst: &FileNode, dependency: FileDependency) {
+    match self.graph.add_edge(src, dst, Dependency::RequireFile(dependency)) {
+      Err(pie_graph::Error::NodeMissing) => panic!("BUG: source node {:?} or destination node {:?} was not found in the dependency graph", src, dst),
+      Err(pie_graph::Error::CycleDetected) => panic!("BUG: cycle detected when adding file dependency from {:?} to {:?}", src, dst),
+      _ => self.reachability.edge_added(src.0, dst.0),
+    }
+  }
+  /// Add a file provide `dependency` from task `src` to file `dst`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `src` or `dst` were not found in the dependency graph, or if a cycle is created by adding this dependency.
+  pub fn add_file_provide_dependency(&mut self, src: &TaskNode, dst: &FileNode, dependency: FileDependency) {
+    match self.graph.add_edge(src, dst, Dependency::ProvideFile(dependency)) {
+      Err(pie_graph::Error::NodeMissing) => panic!("BUG: source node {:?} or destination node {:?} was not found in the dependency graph", src, dst),
+      Err(pie_graph::Error::CycleDetected) => panic!("BUG: cycle detected when adding file dependency from {:?} to {:?}", src, dst),
+      _ => self.reachability.edge_added(src.0, dst.0),
+    }
+  }
+  /// Reserves a task require dependency from task `src` to task `dst`.
+  ///
+  /// # Errors
+  ///
+  /// Returns `Err(cycle)` if adding this dependency to the graph creates a cycle, where `cycle` is the path of task
+  /// require dependencies from `dst` back to `src`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `src` or `dst` were not found in the dependency graph.
+  pub fn reserve_task_require_dependency(&mut self, src: &TaskNode, dst: &TaskNode) -> Result<(), TaskCycle> {
+    match self.graph.add_edge(src, dst, Dependency::ReservedRequireTask) {
+      Err(pie_graph::Error::NodeMissing) => panic!("BUG: source node {:?} or destination node {:?} was not found in the dependency graph", src, dst),
+      Err(pie_graph::Error::CycleDetected) => Err(self.find_task_cycle(src, dst)),
+      _ => {
+        self.reachability.edge_added(src.0, dst.0);
+        Ok(())
+      }
+    }
+  }
+  /// Finds the shortest path of task require dependencies from task `dst` back to task `src`, which forms a cycle with
+  /// a task require dependency from `src` to `dst`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if there is no path of task require dependencies from `dst` to `src`.
+  fn find_task_cycle(&self, src: &TaskNode, dst: &TaskNode) -> TaskCycle {
+    // Breadth-first search from `dst` to `src`, storing the previous node of each visited node.
+    let mut previous = HashMap::new();
+    let mut queue = VecDeque::from([dst.0]);
+    while let Some(node) = queue.pop_front() {
+      if node == src.0 {
+        break;
+      }
+      for (next, dependency) in self.graph.get_outgoing_edges(node) {
+        let is_task_dependency = matches!(dependency, Dependency::RequireTask(_) | Dependency::ReservedRequireTask);
+        if is_task_dependency && *next != dst.0 && !previous.contains_key(next) {
+          previous.insert(*next, node);
+          queue.push_back(*next);
+        }
+      }
+    }
+
+    // Walk back from `src` to `dst` to get the path.
+    let mut path = Vec::new();
+    let mut node = src.0;
+    while node != dst.0 {
+      let Some(previous_node) = previous.get(&node).copied() else {
+        panic!("BUG: no path of task require dependencies from {:?} to {:?} was found", dst, src);
+      };
+      let reserved = matches!(self.graph.get_edge_data(previous_node, node), Some(Dependency::ReservedRequireTask));
+      path.push((TaskNode(node), reserved));
+      node = previous_node;
+    }
+    path.reverse();
+    TaskCycle { path }
+  }
+  /// Updates a reserved task require dependency from task `src` to task `dst`, to `dependency`. When `src` requires
+  /// `dst` multiple times, the dependency is already a task require dependency, which is then replaced.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `src` or `dst` were not found in the dependency graph, or if the dependency between `src` and `dst` is
+  /// not a (reserved) task dependency.
+  pub fn update_task_require_dependency(&mut self, src: &TaskNode, dst: &TaskNode, dependency: TaskDependency<T, T::Output>) {
+    let Some(d @ (Dependency::ReservedRequireTask | Dependency::RequireTask(_))) = self.graph.get_edge_data_mut(src, dst) else {
+      panic!("BUG: no reserved task dependency was found between source node {:?} and destination node {:?}", src, dst)
+    };
+    *d = Dependency::RequireTask(dependency);
+  }
+}
+
+impl<T: Task> Store<T, T::Output> {
+  /// Reset task `src`, removing its output, clearing its mark for execution, its diagnostics, and its log, and removing
+  /// all its outgoing dependencies.
+  ///
+  /// # Panics
+  ///
+  /// Panics if task `src` was not found in the dependency graph.
+  pub fn reset_task(&mut self, src: &TaskNode) {
+    if let Some(NodeData::Task { output, output_evicted, marked_for_execution, diagnostics, log, .. }) =
+      self.graph.get_node_data_mut(src) {
+      *output = None;
+      *output_evicted = false;
+      *marked_for_execution = false;
+      diagnostics.clear();
+      log.clear();
+    } else {
+      panic!("BUG: node {:?} was not found in the dependency graph", src);
+    }
+    self.graph.remove_outgoing_edges_of_node(src);
+    self.reachability.outgoing_edges_removed(src.0);
+    self.output_lru.remove(*src);
+  }
+  /// Removes task `node` from the dependency graph, together with its output and all its incoming and outgoing
+  /// dependencies. Files provided or required by the task stay in the dependency graph.
+  ///
+  /// # Panics
+  ///
+  /// Panics if task `node` was not found in the dependency graph.
+  pub fn remove_task(&mut self, node: &TaskNode) {
+    let Some(NodeData::Task { task, .. }) = self.graph.get_node_data(node) else {
+      panic!("BUG: node {:?} was not found in the dependency graph", node);
+    };
+    self.task_to_node.remove(task.as_ref());
+    self.graph.remove_node(node.0);
+    self.reachability.node_removed(node.0);
+    self.output_lru.remove(*node);
+  }
+  /// Removes all files and tasks, and their dependencies, from the dependency graph. The output memory budget is kept.
+  pub fn clear(&mut self) {
+    *self = Self { output_memory_budget: self.output_memory_budget, ..Self::default() };
+  }
+}
+
+impl<T: Task> Store<T, T::Output> {
+  /// Validates the invariants of this store that contexts rely on, returning all violations. Reserved task require
+  /// dependencies are violations, so this must not be called while a build is executing tasks.
+  ///
+  /// Hidden dependencies are checked by searching the dependency graph, instead of using the reachability index, such
+  /// that validation does not rely on the index being up-to-date.
+  pub fn validate(&self) -> Vec<Violation<T>> {
+    let mut violations = Vec::new();
+    let mut mismatched_tasks = HashSet::new();
+    let mut mismatched_files = HashSet::new();
+
+    for (_, node) in self.graph.iter_unsorted() {
+      match self.graph.get_node_data(node) {
+        Some(NodeData::File(path)) => {
+          let file_node = FileNode(node);
+          if self.file_to_node.get(path) != Some(&file_node) {
+            mismatched_files.insert(path);
+          }
+          let providing_task_nodes: Vec<_> = self.graph.get_incoming_edges(node).filter_map(|(n, d)|
+            if matches!(d, Dependency::ProvideFile(_)) {
+              Some(TaskNode(*n))
+            } else {
+              None
+            }
+          ).collect();
+          if providing_task_nodes.len() > 1 {
+            let tasks = providing_task_nodes.iter().map(|n| self.get_task(n).clone()).collect();
+            violations.push(Violation::OverlappingProvidedFile { path: path.clone(), tasks });
+          }
+          for providing_task_node in &providing_task_nodes {
+            for requiring_task_node in self.get_tasks_requiring_file(&file_node) {
+              // Dependencies of tasks marked for execution are outdated, and are removed when the task is executed.
+              if self.task_marked_for_execution(&requiring_task_node) {
+                continue;
+              }
+              if !self.graph.contains_transitive_edge(requiring_task_node.0, providing_task_node.0) {
+                violations.push(Violation::HiddenDependency {
+                  path: path.clone(),
+                  requiring_task: self.get_task(&requiring_task_node).clone(),
+                  providing_task: self.get_task(providing_task_node).clone(),
+                });
+              }
+            }
+          }
+        }
+        Some(NodeData::Task { task, output, output_evicted, marked_for_execution, .. }) => {
+          if self.task_to_node.get(task) != Some(&TaskNode(node)) {
+            mismatched_tasks.insert(task);
+          }
+          if output.is_none() && !output_evicted && !marked_for_execution {
+            violations.push(Violation::MissingOutput { task: task.as_ref().clone() });
+          }
+          for (dst, dependency) in self.graph.get_outgoing_edges(node) {
+            if matches!(dependency, Dependency::ReservedRequireTask) {
+              let dst = self.get_task(&TaskNode(*dst)).clone();
+              violations.push(Violation::ReservedRequireTask { src: task.as_ref().clone(), dst });
+            }
+          }
+        }
+        None => {}
+      }
+    }
+
+    // Mappings must point to nodes that are in the graph, and have data for the same task or file.
+    for (task, node) in &self.task_to_node {
+      if !matches!(self.graph.get_node_data(node), Some(NodeData::Task { task: t, .. }) if t == task) {
+        mismatched_tasks.insert(task);
+      }
+    }
+    for (path, node) in &self.file_to_node {
+      if !matches!(self.graph.get_node_data(node), Some(NodeData::File(p)) if p == path) {
+        mismatched_files.insert(path);
+      }
+    }
+    violations.extend(mismatched_tasks.into_iter().map(|t| Violation::TaskNodeMismatch { task: t.as_ref().clone() }));
+    violations.extend(mismatched_files.into_iter().map(|p| Violation::FileNodeMismatch { path: p.clone() }));
+
+    violations
+  }
+}
+
+
+#[cfg(test)]
+mod test {
+  use assert_matches::assert_matches;
+
+  use crate::Context;
+  use crate::diagnostic::Severity;
+  use crate::fs::RealFileSystem;
+  use crate::stamp::{FileStamper, OutputStamp, OutputStamper};
+
+  use super::*;
+
+  /// Task that returns its owned string. Never executed, just used for testing the store.
+  #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+  struct StringConstant(String);
+
+  impl StringConstant {
+    pub fn new(string: impl Into<String>) -> Self { Self(string.into()) }
+  }
+
+  impl Task for StringConstant {
+    type Output = String;
+    fn execute<C: Context<Self>>(&self, _context: &mut C) -> Self::Output {
+      self.0.clone()
+    }
+  }
+
+  #[test]
+  fn test_file_mapping() {
+    let mut store: Store<StringConstant, String> = Store::default();
+
+    let path_a = PathBuf::from("hello.txt");
+    let node_a = store.get_or_create_file_node(&path_a);
+    assert_eq!(node_a, store.get_or_create_file_node(&path_a)); // Same node
+    assert_eq!(&path_a, store.get_file_path(&node_a)); // Same file path
+
+    let path_b = PathBuf::from("world.txt");
+    let node_b = store.get_or_create_file_node(&path_b);
+    assert_eq!(node_b, store.get_or_create_file_node(&path_b));
+    assert_eq!(&path_b, store.get_file_path(&node_b));
+
+    assert_ne!(node_a, node_b); // Different nodes
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_file_mapping_panics() {
+    let mut fake_store: Store<StringConstant, String> = Store::default();
+    let fake_node = fake_store.get_or_create_file_node("hello.txt");
+    let store: Store<StringConstant, String> = Store::default();
+    store.get_file_path(&fake_node);
+  }
+
+
+  #[test]
+  fn test_task_mapping() {
+    let mut store = Store::default();
+
+    let task_a = StringConstant::new("Hello");
+    let node_a = store.get_or_create_task_node(&task_a);
+    assert_eq!(node_a, store.get_or_create_task_node(&task_a)); // Same node
+    assert_eq!(&task_a, store.get_task(&node_a)); // Same task
+    let (interned_task_a, _) = store.task_to_node.get_key_value(&task_a).unwrap();
+    assert!(Arc::ptr_eq(interned_task_a, store.get_task_arc(&node_a))); // Same interned task
+
+    let task_b = StringConstant::new("World");
+    let node_b = store.get_or_create_task_node(&task_b);
+    assert_eq!(node_b, store.get_or_create_task_node(&task_b));
+    assert_eq!(&task_b, store.get_task(&node_b));
+
+    assert_ne!(node_a, node_b); // Different nodes
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_task_mapping_panics() {
+    let mut fake_store = Store::default();
+    let fake_node = fake_store.get_or_create_task_node(&StringConstant::new("Hello"));
+    let store: Store<StringConstant, String> = Store::default();
+    store.get_task(&fake_node);
+  }
+
+
+  #[test]
+  fn test_task_outputs() {
+    let mut store = Store::default();
+    let output_a = "Hello".to_string();
+    let task_a = StringConstant::new(&output_a);
+    let node_a = store.get_or_create_task_node(&task_a);
+
+    let output_b = "World".to_string();
+    let task_b = StringConstant::new(&output_b);
+    let node_b = store.get_or_create_task_node(&task_b);
+
+    // Assert that tasks have no output by default.
+    assert!(!store.task_has_output(&node_a));
+    assert!(!store.task_has_output(&node_b));
+
+    // Set output for task A, assert that A has that output but B is unchanged.
+    store.set_task_output(&node_a, Arc::new(output_a.clone()));
+    assert!(store.task_has_output(&node_a));
+    assert_eq!(store.get_task_output(&node_a).as_ref(), &output_a);
+    assert!(!store.task_has_output(&node_b));
+
+    // Set output for task B, assert that B has that output but A is unchanged.
+    store.set_task_output(&node_b, Arc::new(output_b.clone()));
+    assert!(store.task_has_output(&node_a));
+    assert_eq!(store.get_task_output(&node_a).as_ref(), &output_a);
+    assert!(store.task_has_output(&node_b));
+    assert_eq!(store.get_task_output(&node_b).as_ref(), &output_b);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_task_has_output_panics() {
+    let mut fake_store = Store::default();
+    let fake_node = fake_store.get_or_create_task_node(&StringConstant::new("Hello"));
+    let store: Store<StringConstant, String> = Store::default();
+    store.task_has_output(&fake_node);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_get_task_output_panics() {
+    let mut store = Store::default();
+    let node = store.get_or_create_task_node(&StringConstant::new("Hello"));
+    store.get_task_output(&node);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_set_task_output_panics() {
+    let mut fake_store = Store::default();
+    let fake_node = fake_store.get_or_create_task_node(&StringConstant::new("Hello"));
+    let mut store: Store<StringConstant, String> = Store::default();
+    store.set_task_output(&fake_node, Arc::new("Hello".to_string()));
+  }
+
+  #[test]
+  fn test_evict_task_outputs() {
+    let mut store = Store::default();
+    let output_size = StringConstant::output_size(&String::new());
+    let output_a = Arc::new("Hello".to_string());
+    let task_a = StringConstant::new(output_a.as_ref());
+    let node_a = store.get_or_create_task_node(&task_a);
+    let task_b = StringConstant::new("World");
+    let node_b = store.get_or_create_task_node(&task_b);
+    let task_c = StringConstant::new("!");
+    let node_c = store.get_or_create_task_node(&task_c);
+
+    // Task B requires task A, stamping the output of A.
+    store.set_task_output(&node_a, output_a.clone());
+    assert_eq!(store.get_task_output_generation(&node_a), 1);
+    assert!(store.reserve_task_require_dependency(&node_b, &node_a).is_ok());
+    store.update_task_require_dependency(&node_b, &node_a,
+      TaskDependency::new(task_a.clone(), OutputStamper::Equals, output_a.clone()));
+    store.set_task_output(&node_b, Arc::new("World".to_string()));
+
+    // Budget for two outputs: setting the output of C evicts the least recently used output, which is the output of A.
+    store.set_output_memory_budget(Some(2 * output_size));
+    store.set_task_output(&node_c, Arc::new("!".to_string()));
+    assert!(!store.task_has_output(&node_a));
+    assert!(store.task_output_evicted(&node_a));
+    assert!(store.task_has_output(&node_b));
+    assert!(store.task_has_output(&node_c));
+    // The dependency from B to A is kept, but its stamp no longer shares the output of A.
+    let dependencies: Vec<_> = store.get_dependencies_of_task(&node_b).cloned().collect();
+    assert_eq!(dependencies.len(), 1);
+    assert_matches!(&dependencies[0], Dependency::RequireTask(d) => {
+      assert_eq!(d.stamp(), &OutputStamp::Evicted(1));
+    });
+    assert_eq!(Arc::strong_count(&output_a), 1);
+
+    // Using the output of B makes C the least recently used output, so restoring the output of A evicts C.
+    assert_eq!(store.use_task_output(&node_b).as_deref(), Some(&"World".to_string()));
+    store.restore_task_output(&node_a, output_a.clone());
+    assert_eq!(store.get_task_output_generation(&node_a), 1);
+    assert!(!store.task_output_evicted(&node_a));
+    assert!(store.task_has_output(&node_b));
+    assert!(store.task_output_evicted(&node_c));
+
+    // Reset task C: it has no output, and its output is no longer evicted.
+    store.reset_task(&node_c);
+    assert!(!store.task_has_output(&node_c));
+    assert!(!store.task_output_evicted(&node_c));
+
+    // Removing the budget stops evicting outputs.
+    store.set_output_memory_budget(None);
+    store.set_task_output(&node_c, Arc::new("!".to_string()));
+    assert!(store.task_has_output(&node_a));
+    assert!(store.task_has_output(&node_b));
+    assert!(store.task_has_output(&node_c));
+  }
+
+
+  #[test]
+  fn test_dependencies() {
+    let mut store = Store::default();
+    let output_a = "Hello".to_string();
+    let task_a = StringConstant::new(output_a.clone());
+    let node_a = store.get_or_create_task_node(&task_a);
+    let output_b = "World".to_string();
+    let task_b = StringConstant::new(output_b.clone());
+    let node_b = store.get_or_create_task_node(&task_b);
+    let path_c = PathBuf::from("hello.txt");
+    let node_c = store.get_or_create_file_node(&path_c);
+
+    assert_eq!(store.get_dependencies_of_task(&node_a).next(), None);
+    assert_eq!(store.get_dependencies_of_task(&node_b).next(), None);
+    assert_eq!(store.get_task_providing_file(&node_c), None);
+    assert_eq!(store.get_tasks_requiring_file(&node_c).next(), None);
+    assert!(!store.contains_transitive_task_dependency(&node_a, &node_a));
+    assert!(!store.contains_transitive_task_dependency(&node_b, &node_b));
+    assert!(!store.contains_transitive_task_dependency(&node_a, &node_b));
+    assert!(!store.contains_transitive_task_dependency(&node_b, &node_a));
+
+    // Add file dependency from task A to file C.
+    let file_dependency_a2c = FileDependency::new(&RealFileSystem, &path_c, FileStamper::Exists).unwrap();
+    store.add_file_require_dependency(&node_a, &node_c, file_dependency_a2c.clone());
+    let deps_of_a: Vec<_> = store.get_dependencies_of_task(&node_a).cloned().collect();
+    assert_eq!(deps_of_a.get(0), Some(&Dependency::RequireFile(file_dependency_a2c.clone())));
+    assert_eq!(deps_of_a.get(1), None);
+    assert_eq!(store.get_dependencies_of_task(&node_b).next(), None);
+    assert_eq!(store.get_task_providing_file(&node_c), None);
+    let reqs_to_c: Vec<_> = store.get_tasks_requiring_file(&node_c).collect();
+    assert_eq!(reqs_to_c.get(0), Some(&node_a));
+    assert_eq!(reqs_to_c.get(1), None);
+    assert!(!store.contains_transitive_task_dependency(&node_a, &node_b));
+    assert!(!store.contains_transitive_task_dependency(&node_b, &node_a));
+
+    // Reserve task dependency from task B to task A.
+    let task_dependency_b2a = TaskDependency::new(task_a.clone(), OutputStamper::Equals, output_a.clone());
+    let result = store.reserve_task_require_dependency(&node_b, &node_a);
+    assert_eq!(result, Ok(()));
+    let deps_of_b: Vec<_> = store.get_dependencies_of_task(&node_b).cloned().collect();
+    assert_eq!(deps_of_b.get(0), Some(&Dependency::ReservedRequireTask));
+    assert_eq!(deps_of_b.get(1), None);
+
+    // Update task dependency from task B to task A.
+    store.update_task_require_dependency(&node_b, &node_a, task_dependency_b2a.clone());
+    let deps_of_a: Vec<_> = store.get_dependencies_of_task(&node_a).cloned().collect();
+    assert_eq!(deps_of_a.get(0), Some(&Dependency::RequireFile(file_dependency_a2c.clone())));
+    assert_eq!(deps_of_a.get(1), None);
+    let deps_of_b: Vec<_> = store.get_dependencies_of_task(&node_b).cloned().collect();
+    assert_eq!(deps_of_b.get(0), Some(&Dependency::RequireTask(task_dependency_b2a.clone())));
+    assert_eq!(deps_of_b.get(1), None);
+    assert_eq!(store.get_task_providing_file(&node_c), None);
+    let reqs_to_c: Vec<_> = store.get_tasks_requiring_file(&node_c).collect();
+    assert_eq!(reqs_to_c.get(0), Some(&node_a));
+    assert_eq!(reqs_to_c.get(1), None);
+    assert!(!store.contains_transitive_task_dependency(&node_a, &node_b));
+    assert!(store.contains_transitive_task_dependency(&node_b, &node_a));
+
+    // Add file dependency from task B to file C.
+    let file_dependency_b2c = FileDependency::new(&RealFileSystem, &path_c, FileStamper::Exists).unwrap();
+    store.add_file_provide_dependency(&node_b, &node_c, file_dependency_b2c.clone());
+    let deps_of_a: Vec<_> = store.get_dependencies_of_task(&node_a).cloned().collect();
+    assert_eq!(deps_of_a.get(0), Some(&Dependency::RequireFile(file_dependency_a2c.clone())));
+    assert_eq!(deps_of_a.get(1), None);
+    let deps_of_b: Vec<_> = store.get_dependencies_of_task(&node_b).cloned().collect();
+    assert_eq!(deps_of_b.get(0), Some(&Dependency::RequireTask(task_dependency_b2a.clone())));
+    assert_eq!(deps_of_b.get(1), Some(&Dependency::ProvideFile(file_dependency_b2c.clone())));
+    assert_eq!(deps_of_b.get(2), None);
+    assert_eq!(store.get_task_providing_file(&node_c), Some(node_b));
+    let reqs_to_c: Vec<_> = store.get_tasks_requiring_file(&node_c).collect();
+    assert_eq!(reqs_to_c.get(0), Some(&node_a));
+    assert_eq!(reqs_to_c.get(1), None);
+    assert!(!store.contains_transitive_task_dependency(&node_a, &node_b));
+    assert!(store.contains_transitive_task_dependency(&node_b, &node_a));
+
+    // Reserve task dependency from task A to task B, creating a cycle.
+    let result = store.reserve_task_require_dependency(&node_a, &node_b);
+    // Creates a cycle: error with the path from task B back to task A, through the completed dependency from B to A.
+    assert_eq!(result, Err(TaskCycle { path: vec![(node_a, false)] }));
+  }
+
+  #[test]
+  fn test_task_cycle() {
+    let mut store = Store::default();
+    let task_a = StringConstant::new("A");
+    let node_a = store.get_or_create_task_node(&task_a);
+    let task_b = StringConstant::new("B");
+    let node_b = store.get_or_create_task_node(&task_b);
+    let task_c = StringConstant::new("C");
+    let node_c = store.get_or_create_task_node(&task_c);
+    let task_d = StringConstant::new("D");
+    let node_d = store.get_or_create_task_node(&task_d);
+
+    // Task A requires task B (reserved) and task D (completed), task B requires task C (completed), and task D requires
+    // task C (reserved).
+    assert!(store.reserve_task_require_dependency(&node_a, &node_b).is_ok());
+    assert!(store.reserve_task_require_dependency(&node_a, &node_d).is_ok());
+    store.update_task_require_dependency(&node_a, &node_d,
+      TaskDependency::new(task_d.clone(), OutputStamper::Equals, Arc::new("D".to_string())));
+    assert!(store.reserve_task_require_dependency(&node_b, &node_c).is_ok());
+    store.update_task_require_dependency(&node_b, &node_c,
+      TaskDependency::new(task_c.clone(), OutputStamper::Equals, Arc::new("C".to_string())));
+    assert!(store.reserve_task_require_dependency(&node_d, &node_c).is_ok());
+
+    // Task C requiring task A creates a cycle: the path from A back to C is one of the shortest paths.
+    let Err(cycle) = store.reserve_task_require_dependency(&node_c, &node_a) else {
+      panic!("expected a cycle");
+    };
+    assert!(cycle == TaskCycle { path: vec![(node_b, true), (node_c, false)] } ||
+      cycle == TaskCycle { path: vec![(node_d, false), (node_c, true)] });
+    // Task B requiring task A creates a cycle through the reserved dependency from A to B.
+    assert_eq!(store.reserve_task_require_dependency(&node_b, &node_a), Err(TaskCycle { path: vec![(node_b, true)] }));
+    // Task A requiring itself creates a cycle with an empty path.
+    assert_eq!(store.reserve_task_require_dependency(&node_a, &node_a), Err(TaskCycle { path: vec![] }));
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_get_dependencies_of_task_panics() {
+    let mut fake_store = Store::default();
+    let fake_node = fake_store.get_or_create_task_node(&StringConstant::new("Hello"));
+    let store: Store<StringConstant, String> = Store::default();
+    let _ = store.get_dependencies_of_task(&fake_node);
+  }
+
+  #[test]
+  #[should_panic]
+  fn get_task_providing_file_panics() {
+    let mut fake_store: Store<StringConstant, String> = Store::default();
+    let fake_node = fake_store.get_or_create_file_node("hello.txt");
+    let store: Store<StringConstant, String> = Store::default();
+    let _ = store.get_task_providing_file(&fake_node);
+  }
+
+  #[test]
+  #[should_panic]
+  fn get_tasks_requiring_file_panics() {
+    let mut fake_store: Store<StringConstant, String> = Store::default();
+    let fake_node = fake_store.get_or_create_file_node("hello.txt");
+    let store: Store<StringConstant, String> = Store::default();
+    let _ = store.get_tasks_requiring_file(&fake_node);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_contains_transitive_task_dependency_panics() {
+    let mut fake_store = Store::default();
+    let fake_node = fake_store.get_or_create_task_node(&StringConstant::new("Hello"));
+    let store: Store<StringConstant, String> = Store::default();
+    let _ = store.contains_transitive_task_dependency(&fake_node, &fake_node);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_add_file_require_dependency_panics() {
+    let mut fake_store = Store::default();
+    let fake_file_node = fake_store.get_or_create_file_node("hello.txt");
+    let fake_task_node = fake_store.get_or_create_task_node(&StringConstant::new("Hello"));
+    let mut store: Store<StringConstant, String> = Store::default();
+    let dependency = FileDependency::new(&RealFileSystem, "hello.txt", FileStamper::Exists).unwrap();
+    store.add_file_require_dependency(&fake_task_node, &fake_file_node, dependency);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_add_file_provide_dependency_panics() {
+    let mut fake_store = Store::default();
+    let fake_file_node = fake_store.get_or_create_file_node("hello.txt");
+    let fake_task_node = fake_store.get_or_create_task_node(&StringConstant::new("Hello"));
+    let mut store: Store<StringConstant, String> = Store::default();
+    let dependency = FileDependency::new(&RealFileSystem, "hello.txt", FileStamper::Exists).unwrap();
+    store.add_file_provide_dependency(&fake_task_node, &fake_file_node, dependency);
+  }
+
+  #[test]
+  fn test_require_task_twice() {
+    let mut store = Store::default();
+    let output_a = "Hello".to_string();
+    let task_a = StringConstant::new(&output_a);
+    let node_a = store.get_or_create_task_node(&task_a);
+    let task_b = StringConstant::new("World");
+    let node_b = store.get_or_create_task_node(&task_b);
+
+    // Task B requires task A twice, resulting in one task dependency.
+    let task_dependency_b2a = TaskDependency::new(task_a.clone(), OutputStamper::Equals, output_a.clone());
+    for _ in 0..2 {
+      assert_eq!(store.reserve_task_require_dependency(&node_b, &node_a), Ok(()));
+      store.update_task_require_dependency(&node_b, &node_a, task_dependency_b2a.clone());
+    }
+    let deps_of_b: Vec<_> = store.get_dependencies_of_task(&node_b).cloned().collect();
+    assert_eq!(deps_of_b.first(), Some(&Dependency::RequireTask(task_dependency_b2a)));
+    assert_eq!(deps_of_b.get(1), None);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_reserve_task_require_dependency_panics() {
+    let mut fake_store = Store::default();
+    let output = "Hello".to_string();
+    let task = StringConstant::new(&output);
+    let fake_task_node = fake_store.get_or_create_task_node(&task);
+    let mut store: Store<StringConstant, String> = Store::default();
+    let _ = store.reserve_task_require_dependency(&fake_task_node, &fake_task_node);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_update_task_require_dependency_panics_node() {
+    let mut fake_store = Store::default();
+    let output = "Hello".to_string();
+    let task = StringConstant::new(&output);
+    let fake_task_node = fake_store.get_or_create_task_node(&task);
+    let mut store: Store<StringConstant, String> = Store::default();
+    let dependency = TaskDependency::new(task, OutputStamper::Equals, output);
+    store.update_task_require_dependency(&fake_task_node, &fake_task_node, dependency);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_update_task_require_dependency_panics_dependency() {
+    let mut store: Store<StringConstant, String> = Store::default();
+    let output_a = "Hello".to_string();
+    let task_a = StringConstant::new(&output_a);
+    let task_node_a = store.get_or_create_task_node(&task_a);
+    let output_b = "World".to_string();
+    let task_b = StringConstant::new(&output_b);
+    let task_node_b = store.get_or_create_task_node(&task_b);
+    let dependency = TaskDependency::new(task_b, OutputStamper::Equals, output_b);
+    store.update_task_require_dependency(&task_node_a, &task_node_b, dependency);
+  }
+
+
+  #[test]
+  fn test_reset() {
+    let mut store = Store::default();
+    let output_a = "Hello".to_string();
+    let task_a = StringConstant::new(output_a.clone());
+    let task_a_node = store.get_or_create_task_node(&task_a);
+    let output_b = "World".to_string();
+    let task_b = StringConstant::new(output_b.clone());
+    let task_b_node = store.get_or_create_task_node(&task_b);
+    let path = PathBuf::from("hello.txt");
+    let file_node = store.get_or_create_file_node(&path);
+
+    // Set outputs for task A and B.
+    store.set_task_output(&task_a_node, Arc::new(output_a.clone()));
+    assert!(store.task_has_output(&task_a_node));
+    assert_eq!(store.get_task_output(&task_a_node).as_ref(), &output_a);
+    store.set_task_output(&task_b_node, Arc::new(output_b.clone()));
+    assert!(store.task_has_output(&task_b_node));
+    assert_eq!(store.get_task_output(&task_b_node).as_ref(), &output_b);
+
+    // Add file dependency for task A and B.
+    let file_dependency = FileDependency::new(&RealFileSystem, &path, FileStamper::Exists).unwrap();
+    store.add_file_require_dependency(&task_a_node, &file_node, file_dependency.clone());
+    let deps_of_a: Vec<_> = store.get_dependencies_of_task(&task_a_node).cloned().collect();
+    assert_eq!(deps_of_a.get(0), Some(&Dependency::RequireFile(file_dependency.clone())));
+    assert_eq!(deps_of_a.get(1), None);
+    store.add_file_require_dependency(&task_b_node, &file_node, file_dependency.clone());
+    let deps_of_b: Vec<_> = store.get_dependencies_of_task(&task_b_node).cloned().collect();
+    assert_eq!(deps_of_b.get(0), Some(&Dependency::RequireFile(file_dependency.clone())));
+    assert_eq!(deps_of_b.get(1), None);
+
+    // Mark task A and B for execution.
+    assert!(!store.task_marked_for_execution(&task_a_node));
+    store.mark_task_for_execution(&task_a_node);
+    assert!(store.task_marked_for_execution(&task_a_node));
+    store.mark_task_for_execution(&task_b_node);
+    // Add a diagnostic to task A and B.
+    let diagnostic = Diagnostic { severity: Severity::Warning, message: "Hello".to_string(), location: None };
+    store.add_task_diagnostic(&task_a_node, diagnostic.clone());
+    assert_eq!(store.get_task_diagnostics(&task_a_node).len(), 1);
+    store.add_task_diagnostic(&task_b_node, diagnostic.clone());
+    // Log to task A and B.
+    store.get_task_log_mut(&task_a_node).extend_from_slice(b"Hello");
+    assert_eq!(store.get_task_log(&task_a_node), b"Hello");
+    store.get_task_log_mut(&task_b_node).extend_from_slice(b"World");
+
+    // Reset only task A.
+    store.reset_task(&task_a_node);
+    // Assert that task A is reset.
+    assert!(!store.task_has_output(&task_a_node));
+    assert!(!store.task_marked_for_execution(&task_a_node));
+    assert!(store.get_task_diagnostics(&task_a_node).is_empty());
+    assert!(store.get_task_log(&task_a_node).is_empty());
+    assert_eq!(store.get_dependencies_of_task(&task_a_node).next(), None);
+    // Assert that task B is unchanged.
+    assert!(store.task_has_output(&task_b_node));
+    assert!(store.task_marked_for_execution(&task_b_node));
+    assert_eq!(store.get_task_diagnostics(&task_b_node), &[diagnostic]);
+    assert_eq!(store.get_task_log(&task_b_node), b"World");
+    assert_eq!(store.get_task_output(&task_b_node).as_ref(), &output_b);
+    let deps_of_b: Vec<_> = store.get_dependencies_of_task(&task_b_node).cloned().collect();
+    assert_eq!(deps_of_b.get(0), Some(&Dependency::RequireFile(file_dependency.clone())));
+    assert_eq!(deps_of_b.get(1), None);
+  }
+
+  #[test]
+  fn test_remove_task() {
+    let mut store = Store::default();
+    let task_a = StringConstant::new("Hello");
+    let task_a_node = store.get_or_create_task_node(&task_a);
+    let task_b = StringConstant::new("World");
+    let task_b_node = store.get_or_create_task_node(&task_b);
+    let path = PathBuf::from("hello.txt");
+    let file_node = store.get_or_create_file_node(&path);
+
+    // Task A requires task B, and task B provides a file.
+    store.set_task_output(&task_b_node, Arc::new("World".to_string()));
+    assert_eq!(store.reserve_task_require_dependency(&task_a_node, &task_b_node), Ok(()));
+    let file_dependency = FileDependency::new(&RealFileSystem, &path, FileStamper::Exists).unwrap();
+    store.add_file_provide_dependency(&task_b_node, &file_node, file_dependency);
+    assert_eq!(store.get_tasks_requiring_task(&task_b_node).collect::<Vec<_>>(), vec![task_a_node]);
+    assert_eq!(store.get_task_providing_file(&file_node), Some(task_b_node));
+    assert!(store.contains_transitive_task_dependency(&task_a_node, &task_b_node));
+
+    // Remove task B.
+    store.remove_task(&task_b_node);
+    assert_eq!(store.get_task_node(&task_b), None);
+    assert_eq!(store.get_task_node(&task_a), Some(task_a_node));
+    assert_eq!(store.get_dependencies_of_task(&task_a_node).next(), None);
+    // Assert that the file stays, but is no longer provided.
+    assert_eq!(store.get_file_node(&path), Some(file_node));
+    assert_eq!(store.get_task_providing_file(&file_node), None);
+
+    // Task B is a new task with a new node when added again.
+    let task_b_node = store.get_or_create_task_node(&task_b);
+    assert!(!store.task_has_output(&task_b_node));
+    assert!(!store.contains_transitive_task_dependency(&task_a_node, &task_b_node));
+
+    // Clear the store, keeping the output memory budget.
+    store.set_output_memory_budget(Some(1024));
+    store.clear();
+    assert_eq!(store.get_task_node(&task_a), None);
+    assert_eq!(store.get_file_node(&path), None);
+    assert_eq!(store.output_memory_budget(), Some(1024));
+  }
+
+  #[test]
+  fn test_validate() {
+    let mut store = Store::default();
+    let task_a = StringConstant::new("A");
+    let node_a = store.get_or_create_task_node(&task_a);
+    let task_b = StringConstant::new("B");
+    let node_b = store.get_or_create_task_node(&task_b);
+    let task_c = StringConstant::new("C");
+    let node_c = store.get_or_create_task_node(&task_c);
+    let path = PathBuf::from("hello.txt");
+    let file_node = store.get_or_create_file_node(&path);
+    let file_dependency = FileDependency::new(&RealFileSystem, &path, FileStamper::Exists).unwrap();
+
+    // Tasks without an output are only valid when they are marked for execution.
+    let violations = store.validate();
+    assert_eq!(violations.len(), 3);
+    assert!(violations.contains(&Violation::MissingOutput { task: task_a.clone() }));
+    store.set_task_output(&node_a, Arc::new("A".to_string()));
+    store.set_task_output(&node_b, Arc::new("B".to_string()));
+    store.mark_task_for_execution(&node_c);
+    assert_eq!(store.validate(), vec![]);
+
+    // Reserved task require dependencies are only valid while building.
+    assert_eq!(store.reserve_task_require_dependency(&node_a, &node_b), Ok(()));
+    assert_eq!(store.validate(), vec![Violation::ReservedRequireTask { src: task_a.clone(), dst: task_b.clone() }]);
+    store.update_task_require_dependency(&node_a, &node_b,
+      TaskDependency::new(task_b.clone(), OutputStamper::Equals, Arc::new("B".to_string())));
+    assert_eq!(store.validate(), vec![]);
+
+    // Requiring a provided file is only valid with a dependency to the providing task.
+    store.add_file_provide_dependency(&node_c, &file_node, file_dependency.clone());
+    store.add_file_require_dependency(&node_a, &file_node, file_dependency.clone());
+    assert_eq!(store.validate(), vec![Violation::HiddenDependency {
+      path: path.clone(),
+      requiring_task: task_a.clone(),
+      providing_task: task_c.clone(),
+    }]);
+    assert_eq!(store.reserve_task_require_dependency(&node_b, &node_c), Ok(()));
+    store.update_task_require_dependency(&node_b, &node_c,
+      TaskDependency::new(task_c.clone(), OutputStamper::Equals, Arc::new("C".to_string())));
+    assert_eq!(store.validate(), vec![]);
+
+    // Files are only valid when provided by at most one task.
+    store.add_file_provide_dependency(&node_b, &file_node, file_dependency.clone());
+    assert_matches!(store.validate().as_slice(), [Violation::OverlappingProvidedFile { path: p, tasks }]
+      if p == &path && tasks.len() == 2 && tasks.contains(&task_b) && tasks.contains(&task_c));
+    store.graph.remove_edge(&node_b, &file_node);
+    assert_eq!(store.validate(), vec![]);
+
+    // Task and file mappings must agree with node data.
+    store.task_to_node.remove(&task_b);
+    assert_eq!(store.validate(), vec![Violation::TaskNodeMismatch { task: task_b.clone() }]);
+    store.task_to_node.insert(Arc::new(task_b.clone()), node_a);
+    assert_eq!(store.validate(), vec![Violation::TaskNodeMismatch { task: task_b.clone() }]);
+    store.task_to_node.insert(Arc::new(task_b.clone()), node_b);
+    let other_path = PathBuf::from("world.txt");
+    store.file_to_node.insert(other_path.clone(), file_node);
+    assert_eq!(store.validate(), vec![Violation::FileNodeMismatch { path: other_path }]);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_remove_task_panics() {
+    let mut fake_store = Store::default();
+    let fake_node = fake_store.get_or_create_task_node(&StringConstant::new("Hello"));
+    let mut store: Store<StringConstant, String> = Store::default();
+    store.remove_task(&fake_node);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_reset_task_panics() {
+    let mut fake_store = Store::default();
+    let fake_node = fake_store.get_or_create_task_node(&StringConstant::new("Hello"));
+    let mut store: Store<StringConstant, String> = Store::default();
+    store.reset_task(&fake_node);
+  }
+}
@@ -0,0 +1,419 @@
+use std::collections::HashSet;
+use std::collections::hash_map::RandomState;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use stamp::{FileStampCache, FileStamper, OutputStamper};
+
+use crate::context::top_down::TopDownContext;
+use crate::context::checking::DifferentialCheckFailure;
+use crate::dependency::{FileDependency, ModifiedFile};
+use crate::determinism::{DeterminismCheck, NonDeterminism};
+use crate::diagnostic::{Diagnostic, Location, Severity};
+use crate::fs::{FileSystem, PathNormalization, RealFileSystem};
+use crate::store::{FileNode, Store, TaskNode};
+use crate::tracker::{NoopTracker, SpanId, Tracker};
+use crate::validation::Violation;
+
+pub mod stamp;
+pub mod dependency;
+pub mod tracker;
+pub mod context;
+pub mod fs;
+pub mod determinism;
+pub mod diagnostic;
+pub mod command;
+pub mod tasks;
+pub mod validation;
+mod store;
+mod reachability;
+mod lru;
+
+/// A unit of computation in a programmatic incremental build system.
+pub trait Task: Clone + Eq + Hash + Debug {
+  /// Type of output this task returns when executed.
+  type Output: Clone + Eq + Debug;
+  /// Execute the task, using `context` to specify dynamic dependencies, returning `Self::Output`.
+  fn execute<C: Context<Self>>(&self, context: &mut C) -> Self::Output;
+  /// Estimates the size of `output` in bytes, used to evict outputs when exceeding an
+  /// [output memory budget](Pie::set_output_memory_budget). Defaults to the size of `Self::Output` itself, excluding
+  /// any memory it owns on the heap. Override this for outputs that own large allocations.
+  fn output_size(output: &Self::Output) -> usize { std::mem::size_of_val(output) }
+  /// Returns the [`OutputPolicy`] for `output`, determining whether `output` is cached. Defaults to
+  /// [`OutputPolicy::Cache`]. Override this for tasks that can fail transiently, such as tasks that read files or
+  /// access the network, so that transient failures do not stick until a dependency of the task changes.
+  #[allow(unused_variables)]
+  fn output_policy(output: &Self::Output) -> OutputPolicy { OutputPolicy::Cache }
+}
+
+/// Policy for an output of a task, determining whether the output is cached. See [`Task::output_policy`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum OutputPolicy {
+  /// Cache the output: the task is executed again only when one of its dependencies is inconsistent.
+  Cache,
+  /// Do not cache the output, because it is the result of a transient failure. The output is returned, but the task is
+  /// executed again in the next session, even when all its dependencies are consistent.
+  Transient,
+  /// Execute the task again right away, up to the [maximum number of retries](Pie::set_max_retries), because the output
+  /// is the result of a failure that is likely to go away when retrying. When no retries are left, the output is
+  /// treated as [transient](Self::Transient).
+  Retry,
+}
+
+impl OutputPolicy {
+  /// Gets the policy for an output that is an I/O error of `kind`: [retry](Self::Retry) for
+  /// [interrupted](io::ErrorKind::Interrupted), [would block](io::ErrorKind::WouldBlock), and
+  /// [timed out](io::ErrorKind::TimedOut) errors, and [cache](Self::Cache) for other errors. Errors such as a file not
+  /// being found are cached, as requiring the file creates a dependency that executes the task again once the file is
+  /// created.
+  pub fn from_io_error_kind(kind: io::ErrorKind) -> Self {
+    match kind {
+      io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Self::Retry,
+      _ => Self::Cache,
+    }
+  }
+}
+
+/// Programmatic incremental build context, enabling tasks to create dynamic dependencies that context implementations
+/// use for incremental execution.
+pub trait Context<T: Task> {
+  /// Type of file system that files are required from and provided to.
+  type FileSystem: FileSystem;
+  /// Gets the file system that files are required from and provided to. Tasks should read and write files through this
+  /// file system.
+  fn file_system(&self) -> &Self::FileSystem;
+
+  /// Requires file at given `path`, recording a dependency to it (using the default require file stamper). Call this
+  /// method *just before reading from the file*, so that the dependency corresponds to the data that you are reading.
+  /// Returns:
+  /// - `Ok(Some(file))` if a file exists at given `path`,
+  /// - `Ok(None)` if no file exists at given `path` (but a directory could exist at given `path`),
+  /// - `Err(e)` if there was an error getting the metadata for given `path`, if there was an error opening the file, or
+  ///   if there was an error stamping the file.
+  fn require_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Option<<Self::FileSystem as FileSystem>::File>, io::Error> {
+    self.require_file_with_stamper(path, self.default_require_file_stamper())
+  }
+  /// Requires file at given `path`, recording a dependency to it (using given `stamper`). Call this method
+  /// *just before reading from the file*, so that the dependency corresponds to the data that you are reading. Returns:
+  /// - `Ok(Some(file))` if a file exists at given `path`,
+  /// - `Ok(None)` if no file exists at given `path` (but a directory could exist at given `path`),
+  /// - `Err(e)` if there was an error getting the metadata for given `path`, if there was an error opening the file, or
+  ///   if there was an error stamping the file.
+  fn require_file_with_stamper<P: AsRef<Path>>(
+    &mut self,
+    path: P,
+    stamper: FileStamper
+  ) -> Result<Option<<Self::FileSystem as FileSystem>::File>, io::Error>;
+  /// Returns the default require file stamper.
+  fn default_require_file_stamper(&self) -> FileStamper { FileStamper::Modified }
+
+  /// Provides file at given `path`, recording a dependency to it (using the default provide file stamper). Call this
+  /// method *just after writing to the file*, so that the dependency corresponds to your written data. Returns an
+  /// `Err(e)` if there was an error getting the metadata for given `path`, or if there was an error stamping the file.
+  fn provide_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), io::Error> {
+    self.provide_file_with_stamper(path, self.default_provide_file_stamper())
+  }
+  /// Provides file at given `path`, recording a dependency to it (using given `stamper`). Call this method
+  /// *just after writing to the file*, so that the dependency corresponds to you written data.  Returns an `Err(e)` if
+  /// there was an error getting the metadata for given `path`, or if there was an error stamping the file.
+  fn provide_file_with_stamper<P: AsRef<Path>>(&mut self, path: P, stamper: FileStamper) -> Result<(), io::Error>;
+  /// Returns the default provide file stamper.
+  fn default_provide_file_stamper(&self) -> FileStamper { FileStamper::Modified }
+
+  /// Writes the file at given `path` atomically with `write`, and then provides it, recording a dependency to it (using
+  /// the default provide file stamper). Data written by `write` is streamed into a temporary file, which only replaces
+  /// the file if `write` succeeds. Readers and crashes never observe a partially written file, and the dependency is only
+  /// recorded after the file is complete. Returns the number of bytes written, or an `Err(e)` if `write` failed, if
+  /// there was an error writing the file, or if there was an error stamping the file.
+  fn write_provided_file<P: AsRef<Path>, W: FnOnce(&mut dyn Write) -> Result<(), io::Error>>(
+    &mut self,
+    path: P,
+    write: W,
+  ) -> Result<usize, io::Error> {
+    self.write_provided_file_with_stamper(path, self.default_provide_file_stamper(), write)
+  }
+  /// Writes the file at given `path` atomically with `write`, and then provides it, recording a dependency to it (using
+  /// given `stamper`). See [`write_provided_file`](Self::write_provided_file) for more info.
+  fn write_provided_file_with_stamper<P: AsRef<Path>, W: FnOnce(&mut dyn Write) -> Result<(), io::Error>>(
+    &mut self,
+    path: P,
+    stamper: FileStamper,
+    write: W,
+  ) -> Result<usize, io::Error> {
+    let written = self.file_system().write_atomically_with(&path, write)?;
+    self.provide_file_with_stamper(path, stamper)?;
+    Ok(written)
+  }
+
+  /// Requires given `task`, recording a dependency (using the default output stamper) and selectively executing it.
+  /// Returns its up-to-date output.
+  fn require_task(&mut self, task: &T) -> T::Output {
+    self.require_task_with_stamper(task, self.default_output_stamper())
+  }
+  /// Requires given `task`, recording a dependency (using given `stamper`) and selectively executing it. Returns its
+  /// up-to-date output.
+  fn require_task_with_stamper(&mut self, task: &T, stamper: OutputStamper) -> T::Output {
+    T::Output::clone(&self.require_task_shared_with_stamper(task, stamper))
+  }
+  /// Requires given `task`, recording a dependency (using the default output stamper) and selectively executing it.
+  /// Returns its up-to-date output, shared with the context, which is cheaper than cloning large outputs.
+  fn require_task_shared(&mut self, task: &T) -> Arc<T::Output> {
+    self.require_task_shared_with_stamper(task, self.default_output_stamper())
+  }
+  /// Requires given `task`, recording a dependency (using given `stamper`) and selectively executing it. Returns its
+  /// up-to-date output, shared with the context, which is cheaper than cloning large outputs.
+  fn require_task_shared_with_stamper(&mut self, task: &T, stamper: OutputStamper) -> Arc<T::Output>;
+  /// Returns the default output stamper.
+  fn default_output_stamper(&self) -> OutputStamper { OutputStamper::Equals }
+
+  /// Emits a diagnostic with `severity` and `message`, optionally about `location`, for the current executing task.
+  /// Diagnostics are stored with the output of the task, and are reported again in later sessions when the task is not
+  /// executed because it is consistent.
+  fn emit_diagnostic<M: Into<String>>(&mut self, severity: Severity, message: M, location: Option<Location>);
+
+  /// Gets a writer that captures text logged by the current executing task. Use this instead of printing to standard
+  /// output, which interleaves with the output of trackers. Captured text is stored with the task, replacing the text of
+  /// its previous execution, and is reported to the tracker when the task is done executing.
+  fn log(&mut self) -> &mut dyn Write;
+}
+
+/// Main entry point into PIE, a sound and incremental programmatic build system.
+pub struct Pie<T, O, A = NoopTracker, F = RealFileSystem> {
+  store: Store<T, O>,
+  tracker: A,
+  file_system: F,
+  determinism_check: DeterminismCheck,
+  file_stamp_caching: bool,
+  differential_checking: bool,
+  max_retries: usize,
+  path_normalization: PathNormalization,
+}
+
+impl<T: Task> Default for Pie<T, T::Output> {
+  fn default() -> Self { Self::with_tracker(NoopTracker) }
+}
+
+impl<T: Task, A: Tracker<T>> Pie<T, T::Output, A> {
+  /// Creates a new [`Pie`] instance with given `tracker`, using the [real file system](RealFileSystem).
+  pub fn with_tracker(tracker: A) -> Self { Self::with_tracker_and_file_system(tracker, RealFileSystem) }
+}
+
+impl<T: Task, A: Tracker<T>, F: FileSystem> Pie<T, T::Output, A, F> {
+  /// Creates a new [`Pie`] instance with given `tracker` and `file_system`.
+  pub fn with_tracker_and_file_system(tracker: A, file_system: F) -> Self {
+    Self { store: Store::default(), tracker, file_system, determinism_check: DeterminismCheck::default(),
+      file_stamp_caching: true, differential_checking: false, max_retries: 0,
+      path_normalization: PathNormalization::default() }
+  }
+
+  /// Creates a new build session. Only one session may be active at once, enforced via mutable (exclusive) borrow.
+  pub fn new_session(&mut self) -> Session<T, T::Output, A, F> { Session::new(self) }
+  /// Runs `f` inside a new build session.
+  pub fn run_in_session<R>(&mut self, f: impl FnOnce(Session<T, T::Output, A, F>) -> R) -> R {
+    let session = self.new_session();
+    f(session)
+  }
+
+  /// Gets the [`Tracker`] instance.
+  pub fn tracker(&self) -> &A { &self.tracker }
+  /// Gets the mutable [`Tracker`] instance.
+  pub fn tracker_mut(&mut self) -> &mut A { &mut self.tracker }
+
+  /// Gets the [`FileSystem`] instance.
+  pub fn file_system(&self) -> &F { &self.file_system }
+
+  /// Gets the [`DeterminismCheck`] mode that sessions use.
+  pub fn determinism_check(&self) -> DeterminismCheck { self.determinism_check }
+  /// Sets the [`DeterminismCheck`] mode that sessions use. Non-deterministic tasks are reported to the tracker and
+  /// stored in the session, accessible via [`Session::non_deterministic_tasks`].
+  pub fn set_determinism_check(&mut self, determinism_check: DeterminismCheck) {
+    self.determinism_check = determinism_check;
+  }
+
+  /// Gets whether sessions use differential checking.
+  pub fn differential_checking(&self) -> bool { self.differential_checking }
+  /// Sets whether sessions use differential checking. When enabled, tasks that are not executed because they are
+  /// consistent are run anyway, and their output is compared with their incremental output. Mismatched outputs, and
+  /// files read through the file system before being required or provided, are stored in the session, accessible via
+  /// [`Session::differential_check_failures`]. Files read without going through the file system of the context, such
+  /// as with `std::fs` or by spawned processes, are not detected as undeclared reads, and files written that way are
+  /// changed on the real file system.
+  ///
+  /// Differential checking runs every consistent task, which defeats the purpose of incremental builds. Enable it in
+  /// tests, and in continuous integration builds of real projects to find their missing dependencies, but not in
+  /// builds where incrementality matters.
+  pub fn set_differential_checking(&mut self, differential_checking: bool) {
+    self.differential_checking = differential_checking;
+  }
+
+  /// Gets whether sessions cache file stamps.
+  pub fn file_stamp_caching(&self) -> bool { self.file_stamp_caching }
+  /// Sets whether sessions cache file stamps to `file_stamp_caching`. When enabled, which is the default, file
+  /// dependencies are checked with stamps that are cached during each [build](Session::require), such that a file
+  /// required by many tasks is stamped once per build. When disabled, the file of every file dependency is stamped.
+  pub fn set_file_stamp_caching(&mut self, file_stamp_caching: bool) {
+    self.file_stamp_caching = file_stamp_caching;
+  }
+
+  /// Gets the maximum number of times that sessions execute a task again when its output has the
+  /// [retry policy](OutputPolicy::Retry).
+  pub fn max_retries(&self) -> usize { self.max_retries }
+  /// Sets the maximum number of times that sessions execute a task again when its output has the
+  /// [retry policy](OutputPolicy::Retry) to `max_retries`. Defaults to 0, treating outputs with the retry policy as
+  /// [transient](OutputPolicy::Transient) right away.
+  pub fn set_max_retries(&mut self, max_retries: usize) {
+    self.max_retries = max_retries;
+  }
+
+  /// Gets the policy for normalizing paths of required and provided files.
+  pub fn path_normalization(&self) -> &PathNormalization { &self.path_normalization }
+  /// Sets the policy for normalizing paths of required and provided files to `path_normalization`. Defaults to no
+  /// normalization. Files that were required or provided in previous sessions are not normalized again, so set the
+  /// policy before the first session.
+  pub fn set_path_normalization(&mut self, path_normalization: PathNormalization) {
+    self.path_normalization = path_normalization;
+  }
+
+  /// Gets the output memory budget in bytes, or `None` if outputs are never evicted.
+  pub fn output_memory_budget(&self) -> Option<usize> { self.store.output_memory_budget() }
+  /// Sets the output memory budget in bytes to `budget`. When the total size of stored outputs, as estimated by
+  /// [`Task::output_size`], exceeds the budget, the least recently used outputs are evicted. Evicted tasks keep their
+  /// dependencies, and are executed again when their output is needed. `None` (the default) disables eviction.
+  pub fn set_output_memory_budget(&mut self, budget: Option<usize>) {
+    self.store.set_output_memory_budget(budget);
+  }
+
+  /// Invalidates `task`, so that it is executed the next time it is required, even if all its dependencies are
+  /// consistent. Tasks that depend on `task` are only executed again if its new output is inconsistent with their
+  /// dependency. Returns `false` if `task` is unknown, and `true` otherwise.
+  pub fn invalidate_task(&mut self, task: &T) -> bool {
+    let Some(node) = self.store.get_task_node(task) else { return false; };
+    self.store.mark_task_for_execution(&node);
+    true
+  }
+  /// Invalidates the file at `path` as if its stamp changed, for example when an external tool modified the file
+  /// while preserving its modification time. All tasks that require the file, and the task that provides the file,
+  /// are executed the next time they are required. Returns `false` if the file is unknown, and `true` otherwise.
+  pub fn invalidate_file(&mut self, path: impl AsRef<Path>) -> bool {
+    let path = self.path_normalization.normalize(path, &self.file_system);
+    let Some(node) = self.store.get_file_node(path) else { return false; };
+    let tasks: Vec<_> = self.store.get_tasks_requiring_file(&node)
+      .chain(self.store.get_task_providing_file(&node))
+      .collect();
+    for task_node in tasks {
+      self.store.mark_task_for_execution(&task_node);
+    }
+    true
+  }
+  /// Removes `task`, together with its output and all its dependencies. Tasks that depend on `task` are executed the
+  /// next time they are required. Files provided by `task` are not deleted, but are no longer provided by any task,
+  /// so that other tasks can provide them. Returns `false` if `task` is unknown, and `true` otherwise.
+  pub fn remove_task(&mut self, task: &T) -> bool {
+    let Some(node) = self.store.get_task_node(task) else { return false; };
+    let requiring_tasks: Vec<_> = self.store.get_tasks_requiring_task(&node).collect();
+    for task_node in requiring_tasks {
+      self.store.mark_task_for_execution(&task_node);
+    }
+    self.store.remove_task(&node);
+    true
+  }
+  /// Resets this build system by removing all tasks and files, and their outputs and dependencies, so that all tasks
+  /// are executed the next time they are required. Files provided by tasks are not deleted. Settings are kept.
+  pub fn reset(&mut self) {
+    self.store.clear();
+  }
+
+  /// Validates the invariants of the dependency graph that builds rely on, returning all violations, which indicate a
+  /// bug. Useful for debugging and fuzzing. In development builds, this is also done at the end of every build, panicking
+  /// when there are violations.
+  pub fn validate(&self) -> Vec<Violation<T>> {
+    self.store.validate()
+  }
+}
+
+/// A session in which builds are executed.
+pub struct Session<'p, T, O, A, F> {
+  store: &'p mut Store<T, O>,
+  tracker: &'p mut A,
+  file_system: &'p F,
+  current_executing_task: Option<TaskNode>,
+  consistent: HashSet<TaskNode>,
+  file_stamp_cache: FileStampCache,
+  file_stamp_caching: bool,
+  dependency_check_errors: Vec<io::Error>,
+  determinism_check: DeterminismCheck,
+  determinism_check_random_state: RandomState,
+  non_deterministic_tasks: Vec<NonDeterminism<T, O>>,
+  differential_checking: bool,
+  differential_check_failures: Vec<DifferentialCheckFailure<T, O>>,
+  max_retries: usize,
+  path_normalization: &'p PathNormalization,
+  required_files: Vec<(TaskNode, FileNode, FileDependency)>,
+  provided_files: HashSet<FileNode>,
+  modified_files: Vec<ModifiedFile<T>>,
+  diagnostics: Vec<(T, Diagnostic)>,
+  next_span_id: u64,
+  span_stack: Vec<SpanId>,
+}
+
+impl<'p, T: Task, A: Tracker<T>, F: FileSystem> Session<'p, T, T::Output, A, F> {
+  fn new(pie: &'p mut Pie<T, T::Output, A, F>) -> Self {
+    Self {
+      store: &mut pie.store,
+      tracker: &mut pie.tracker,
+      file_system: &pie.file_system,
+      current_executing_task: None,
+      consistent: HashSet::default(),
+      file_stamp_cache: FileStampCache::default(),
+      file_stamp_caching: pie.file_stamp_caching,
+      dependency_check_errors: Vec::default(),
+      determinism_check: pie.determinism_check,
+      determinism_check_random_state: RandomState::new(),
+      non_deterministic_tasks: Vec::default(),
+      differential_checking: pie.differential_checking,
+      differential_check_failures: Vec::default(),
+      max_retries: pie.max_retries,
+      path_normalization: &pie.path_normalization,
+      required_files: Vec::default(),
+      provided_files: HashSet::default(),
+      modified_files: Vec::default(),
+      diagnostics: Vec::default(),
+      next_span_id: 0,
+      span_stack: Vec::default(),
+    }
+  }
+
+  /// Requires `task`, returning its up-to-date output.
+  ///
+  /// Tasks that were already made consistent in this session are not checked again, so files that are changed
+  /// between builds of the same session are only observed by tasks that are checked for the first time.
+  pub fn require(&mut self, task: &T) -> T::Output {
+    self.current_executing_task = None;
+    // Files may have been changed externally since the previous build of this session, making cached stamps stale.
+    self.file_stamp_cache = FileStampCache::default();
+    TopDownContext::new(self).require_initial(task)
+  }
+
+  /// Gets the [`Tracker`] instance.
+  pub fn tracker(&self) -> &A { &self.tracker }
+  /// Gets the mutable [`Tracker`] instance.
+  pub fn tracker_mut(&mut self) -> &mut A { &mut self.tracker }
+
+  /// Gets all errors produced during dependency checks.
+  pub fn dependency_check_errors(&self) -> &[io::Error] { &self.dependency_check_errors }
+  /// Gets all non-deterministic tasks found by [determinism checks](Pie::set_determinism_check).
+  pub fn non_deterministic_tasks(&self) -> &[NonDeterminism<T, T::Output>] { &self.non_deterministic_tasks }
+  /// Gets all failures found by [differential checking](Pie::set_differential_checking).
+  pub fn differential_check_failures(&self) -> &[DifferentialCheckFailure<T, T::Output>] {
+    &self.differential_check_failures
+  }
+  /// Gets all files that were modified during a build after a task required them. The tasks that required them are
+  /// executed again in the next session.
+  pub fn modified_files(&self) -> &[ModifiedFile<T>] { &self.modified_files }
+  /// Gets all diagnostics of tasks that were executed or checked in this session, together with the task that emitted
+  /// them. Includes diagnostics stored with the outputs of tasks that were not executed because they were consistent.
+  pub fn diagnostics(&self) -> &[(T, Diagnostic)] { &self.diagnostics }
+}
@@ -0,0 +1,419 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::{Context, OutputPolicy, Session, Task};
+use crate::context::checking::{CheckingContext, DifferentialCheckFailure};
+use crate::dependency::{Dependency, FileDependency, Inconsistency, MakeConsistent, ModifiedFile, TaskDependency};
+use crate::determinism::NonDeterminism;
+use crate::diagnostic::{Diagnostic, Location, Severity};
+use crate::fs::FileSystem;
+use crate::stamp::{FileStampCache, FileStamper, OutputStamp, OutputStamper};
+use crate::store::TaskNode;
+use crate::tracker::{Span, SpanId, Tracker};
+
+pub struct TopDownContext<'p, 's, T, O, A, F> {
+  session: &'s mut Session<'p, T, O, A, F>,
+}
+
+impl<'p, 's, T: Task, A: Tracker<T>, F: FileSystem> TopDownContext<'p, 's, T, T::Output, A, F> {
+  pub fn new(session: &'s mut Session<'p, T, T::Output, A, F>) -> Self { Self { session } }
+
+  pub fn require_initial(&mut self, task: &T) -> T::Output {
+    let span = self.start_span();
+    self.session.tracker.build_start(span);
+    let output = self.require_task(task);
+    self.verify_required_files();
+    if cfg!(debug_assertions) {
+      let violations = self.session.store.validate();
+      if !violations.is_empty() {
+        let violations: Vec<_> = violations.iter().map(|v| format!("  {}", v)).collect();
+        panic!("BUG: dependency graph is invalid at the end of a build:\n{}", violations.join("\n"));
+      }
+    }
+    self.session.tracker.build_end(span);
+    self.end_span(span);
+    output
+  }
+
+  /// Verifies that files required during this build were not modified after they were required, by stamping them
+  /// again. A file that is modified between stamping and reading it results in a stamp that does not correspond to the
+  /// data that was read. Tasks that required modified files are marked for execution, so that they are executed again
+  /// in the next session, and the modified files are reported to the tracker and stored in the session.
+  ///
+  /// Files provided during this build are not verified, as tasks are expected to modify the files they provide, and
+  /// hidden dependency checks already ensure that tasks requiring those files depend on the providing task.
+  fn verify_required_files(&mut self) {
+    let provided_files = std::mem::take(&mut self.session.provided_files);
+    if self.session.required_files.is_empty() {
+      return;
+    }
+    let span = self.start_span();
+    self.session.tracker.verify_files_start(span);
+    // Use a new stamp cache, as the stamp cache of the session may contain stamps from before the files were modified.
+    let mut file_stamp_cache = FileStampCache::default();
+    let mut verified = HashSet::new();
+    let mut modified_files = Vec::new();
+    for (node, file_node, dependency) in std::mem::take(&mut self.session.required_files) {
+      // Skip provided files, and files that a task required multiple times, for example when it was executed again.
+      if provided_files.contains(&file_node) || !verified.insert((node, file_node)) {
+        continue;
+      }
+      match dependency.is_inconsistent_cached(self.session.file_system, &mut file_stamp_cache) {
+        Ok(None) => {}
+        Ok(Some(new_stamp)) => {
+          self.session.store.mark_task_for_execution(&node);
+          let task = self.session.store.get_task(&node).clone();
+          modified_files.push(ModifiedFile { task, dependency, new_stamp });
+        }
+        Err(e) => { // Error while verifying: store error and assume modified.
+          self.session.store.mark_task_for_execution(&node);
+          self.session.dependency_check_errors.push(e);
+        }
+      }
+    }
+    self.session.tracker.verify_files_end(span, &modified_files);
+    self.end_span(span);
+    self.session.modified_files.extend(modified_files);
+  }
+
+  /// Starts a new span nested in the current span (if any), making it the current span.
+  fn start_span(&mut self) -> Span {
+    let id = SpanId(self.session.next_span_id);
+    self.session.next_span_id += 1;
+    let span = Span { id, parent: self.session.span_stack.last().copied() };
+    self.session.span_stack.push(id);
+    span
+  }
+  /// Normalizes `path` with the path normalization policy of the session, such that different paths to the same file
+  /// get the same file node.
+  fn normalize_path(&self, path: &Path) -> PathBuf {
+    self.session.path_normalization.normalize(path, self.session.file_system)
+  }
+  /// Gets the current span.
+  fn current_span(&self) -> Span {
+    let stack = &self.session.span_stack;
+    let Some(id) = stack.last().copied() else {
+      panic!("BUG: getting current span while no span is active");
+    };
+    Span { id, parent: stack.len().checked_sub(2).map(|i| stack[i]) }
+  }
+  /// Ends `span`, making its parent the current span.
+  fn end_span(&mut self, span: Span) {
+    let id = self.session.span_stack.pop();
+    debug_assert_eq!(id, Some(span.id), "BUG: ending span {:?} which is not the current span", span);
+  }
+}
+
+impl<'p, 's, T: Task, A: Tracker<T>, F: FileSystem> Context<T> for TopDownContext<'p, 's, T, T::Output, A, F> {
+  type FileSystem = F;
+  fn file_system(&self) -> &F { self.session.file_system }
+
+  fn require_file_with_stamper<P: AsRef<Path>>(&mut self, path: P, stamper: FileStamper) -> Result<Option<F::File>, io::Error> {
+    let Some(current_executing_task_node) = self.session.current_executing_task else {
+      // No current executing task, so no dependency needs to be made.
+      return self.session.file_system.open_if_file(path);
+    };
+    // Normalize once, so that the file node, cached file stamps, and file dependency all use the same path.
+    let path = &self.normalize_path(path.as_ref());
+    let node = self.session.store.get_or_create_file_node(path);
+
+    if let Some(providing_task_node) = self.session.store.get_task_providing_file(&node) {
+      if !self.session.store.contains_transitive_task_dependency(&current_executing_task_node, &providing_task_node) {
+        let current_executing_task = self.session.store.get_task(&current_executing_task_node);
+        let providing_task = self.session.store.get_task(&providing_task_node);
+        panic!("Hidden dependency; file '{}' is required by the current executing task '{:?}' without a dependency to \
+                providing task: {:?}", path.display(), current_executing_task, providing_task);
+      }
+    }
+
+    let span = self.start_span();
+    self.session.tracker.require_file_start(span, path, &stamper);
+    let result = FileDependency::new_with_file(self.session.file_system, path, stamper);
+    self.session.tracker.require_file_end(span, path, &stamper, result.as_ref().map(|(d, _)| d));
+    self.end_span(span);
+    let (dependency, file) = result?;
+    self.session.required_files.push((current_executing_task_node, node, dependency.clone()));
+    self.session.store.add_file_require_dependency(&current_executing_task_node, &node, dependency);
+    Ok(file)
+  }
+
+  fn provide_file_with_stamper<P: AsRef<Path>>(&mut self, path: P, stamper: FileStamper) -> Result<(), io::Error> {
+    let Some(current_executing_task_node) = self.session.current_executing_task else {
+      return Ok(()); // No current executing task, so no dependency needs to be made.
+    };
+    // Normalize once, so that the file node, cached file stamps, and file dependency all use the same path.
+    let path = &self.normalize_path(path.as_ref());
+    let node = self.session.store.get_or_create_file_node(path);
+
+    if let Some(previous_providing_task_node) = self.session.store.get_task_providing_file(&node) {
+      let current_executing_task = self.session.store.get_task(&current_executing_task_node);
+      let previous_providing_task = self.session.store.get_task(&previous_providing_task_node);
+      panic!("Overlapping provided file; file '{}' is provided by the current executing task '{:?}' that was \
+              previously provided by task: {:?}", path.display(), current_executing_task, previous_providing_task);
+    }
+
+    for requiring_task_node in self.session.store.get_tasks_requiring_file(&node) {
+      if !self.session.store.contains_transitive_task_dependency(&requiring_task_node, &current_executing_task_node) {
+        let current_executing_task = self.session.store.get_task(&current_executing_task_node);
+        let requiring_task = self.session.store.get_task(&requiring_task_node);
+        panic!("Hidden dependency; file '{}' is provided by the current executing task '{:?}' without a dependency \
+                from requiring task '{:?}' to the current executing task", path.display(), current_executing_task, requiring_task);
+      }
+    }
+
+    // The current executing task may have changed the provided file, so its cached stamps are no longer valid.
+    self.session.file_stamp_cache.invalidate(path);
+    self.session.provided_files.insert(node);
+
+    let span = self.start_span();
+    self.session.tracker.provide_file_start(span, path, &stamper);
+    let result = FileDependency::new(self.session.file_system, path, stamper);
+    self.session.tracker.provide_file_end(span, path, &stamper, result.as_ref());
+    self.end_span(span);
+    let dependency = result?;
+    self.session.store.add_file_provide_dependency(&current_executing_task_node, &node, dependency);
+    Ok(())
+  }
+
+  fn require_task_shared_with_stamper(&mut self, task: &T, stamper: OutputStamper) -> Arc<T::Output> {
+    let span = self.start_span();
+    self.session.tracker.require_task_start(span, task, &stamper);
+
+    let node = self.session.store.get_or_create_task_node(task);
+    if let Some(current_executing_task_node) = &self.session.current_executing_task {
+      // First reserve a task require dependency to catch cycles before (potentially) executing the task, and to have
+      // the dependency edge in the graph for catching future cycles.
+      if let Err(cycle) = self.session.store.reserve_task_require_dependency(current_executing_task_node, &node) {
+        let current_executing_task = self.session.store.get_task(current_executing_task_node);
+        let mut lines = vec![format!("{:?}", current_executing_task), format!("→ {:?} (new dependency)", task)];
+        for (node, reserved) in cycle.path {
+          let kind = if reserved { "reserved" } else { "completed" };
+          lines.push(format!("→ {:?} ({} dependency)", self.session.store.get_task(&node), kind));
+        }
+        panic!("Cyclic task dependency; current executing task '{:?}' is requiring task '{:?}' which was already \
+                required, creating cycle:\n  {}", current_executing_task, task, lines.join("\n  "));
+      }
+    }
+    let (output, was_executed) = self.make_task_consistent(task, node);
+
+    let dependency = TaskDependency::new(self.session.store.get_task_arc(&node).clone(), stamper, output.clone());
+    self.session.tracker.require_task_end(span, &dependency, output.as_ref(), was_executed);
+    self.end_span(span);
+
+    if let Some(current_executing_task_node) = &self.session.current_executing_task {
+      // Update the reserved task require dependency to a real task require dependency.
+      self.session.store.update_task_require_dependency(current_executing_task_node, &node, dependency)
+    }
+
+    output
+  }
+
+  fn emit_diagnostic<M: Into<String>>(&mut self, severity: Severity, message: M, location: Option<Location>) {
+    let Some(current_executing_task_node) = self.session.current_executing_task else {
+      return; // No current executing task, so there is no output to store the diagnostic with.
+    };
+    let diagnostic = Diagnostic { severity, message: message.into(), location };
+    self.session.store.add_task_diagnostic(&current_executing_task_node, diagnostic);
+  }
+
+  fn log(&mut self) -> &mut dyn Write {
+    let Some(current_executing_task_node) = self.session.current_executing_task else {
+      panic!("BUG: logging while no task is executing");
+    };
+    self.session.store.get_task_log_mut(&current_executing_task_node)
+  }
+}
+
+impl<'p, 's, T: Task, A: Tracker<T>, F: FileSystem> MakeConsistent<T> for TopDownContext<'p, 's, T, T::Output, A, F> {
+  fn make_task_consistent(&mut self, task: &T) -> Arc<T::Output> {
+    let node = self.session.store.get_or_create_task_node(task);
+    let (output, _) = self.make_task_consistent(task, node);
+    output
+  }
+}
+
+impl<'p, 's, T: Task, A: Tracker<T>, F: FileSystem> TopDownContext<'p, 's, T, T::Output, A, F> {
+  // Get required task output by executing it if needed, or by getting the output from the store if not needed. If the
+  // output was evicted from the store, the task is executed again to recompute it.
+  fn make_task_consistent(&mut self, task: &T, node: TaskNode) -> (Arc<T::Output>, bool) {
+    match self.make_task_consistent_lazily(task, node) {
+      (Some(output), was_executed) => (output, was_executed),
+      (None, _) => (self.execute_task(task, node, true), true),
+    }
+  }
+
+  /// Makes `task` consistent, executing it if needed, but does not execute it to recompute its output if its output was
+  /// evicted. Returns its output, or `None` if its output was evicted, and whether it was executed.
+  fn make_task_consistent_lazily(&mut self, task: &T, node: TaskNode) -> (Option<Arc<T::Output>>, bool) {
+    let already_consistent = self.session.consistent.contains(&node);
+    let should_execute = !already_consistent && self.should_execute_task(&node);
+    let output = if should_execute {
+      Some(self.execute_task(task, node, false))
+    } else {
+      // Correctness: when `should_execute_task` returns `true`, the above block is executed. Otherwise this block is
+      // executed and `should_execute_task` ensures that the task has an output, or that its output was evicted.
+      let output = self.session.store.use_task_output(&node);
+      if let Some(output) = &output {
+        if !already_consistent && self.session.differential_checking {
+          self.check_differential(task, output);
+        }
+      }
+      output
+    };
+
+    if !already_consistent {
+      self.report_diagnostics(task, &node, !should_execute);
+    }
+    self.session.consistent.insert(node);
+    (output, should_execute)
+  }
+
+  /// Reports the diagnostics stored with the output of `task` to the tracker and the session. `replayed` indicates
+  /// whether the diagnostics are replayed because `task` was not executed. Diagnostics are reported after executing
+  /// `task` instead of when they are emitted, as checking determinism executes `task` twice.
+  fn report_diagnostics(&mut self, task: &T, node: &TaskNode, replayed: bool) {
+    let span = self.current_span();
+    for diagnostic in self.session.store.get_task_diagnostics(node) {
+      self.session.tracker.report_diagnostic(span, task, diagnostic, replayed);
+      self.session.diagnostics.push((task.clone(), diagnostic.clone()));
+    }
+  }
+
+  /// Executes `task`, storing and returning its output. If `recompute` is `true`, `task` is executed to recompute its
+  /// evicted output, which is equal to the evicted output because tasks are deterministic, keeping its output generation.
+  ///
+  /// The task is executed again while its output has the [retry policy](OutputPolicy::Retry), up to the maximum number
+  /// of retries. If its final output is not cached, it is marked for execution in the next session.
+  fn execute_task(&mut self, task: &T, node: TaskNode, recompute: bool) -> Arc<T::Output> {
+    let span = self.start_span();
+    self.session.tracker.execute_start(span, task);
+    self.session.store.reset_task(&node);
+    let previous_executing_task = self.session.current_executing_task.replace(node);
+    let mut output = task.execute(self);
+    let mut retries = 0;
+    while T::output_policy(&output) == OutputPolicy::Retry && retries < self.session.max_retries {
+      retries += 1;
+      self.session.store.reset_task(&node);
+      output = task.execute(self);
+    }
+    if self.session.determinism_check.should_check(task, &self.session.determinism_check_random_state) {
+      output = self.check_determinism(task, node, output);
+    }
+    self.session.current_executing_task = previous_executing_task;
+    let output = Arc::new(output);
+    if recompute {
+      self.session.store.restore_task_output(&node, output.clone());
+    } else {
+      self.session.store.set_task_output(&node, output.clone());
+    }
+    if T::output_policy(&output) != OutputPolicy::Cache {
+      // Do not cache the output: execute the task again in the next session.
+      self.session.store.mark_task_for_execution(&node);
+    }
+    let log = self.session.store.get_task_log(&node);
+    if !log.is_empty() {
+      self.session.tracker.execute_log(span, task, &String::from_utf8_lossy(log));
+    }
+    let dependencies: Vec<_> = self.session.store.get_dependencies_of_task(&node).collect();
+    self.session.tracker.execute_end(span, task, output.as_ref(), &dependencies);
+    self.end_span(span);
+    output
+  }
+
+  /// Checks whether `task` is deterministic by executing it again, comparing the `output` and dependencies of its first
+  /// execution with those of the second execution. Returns the output of the second execution, as the dependencies of
+  /// the second execution are now stored.
+  fn check_determinism(&mut self, task: &T, node: TaskNode, output: T::Output) -> T::Output {
+    let span = self.start_span();
+    self.session.tracker.check_determinism_start(span, task);
+    let dependencies: Vec<_> = self.session.store.get_dependencies_of_task(&node).cloned().collect();
+    self.session.store.reset_task(&node);
+    let reexecuted_output = task.execute(self);
+    let reexecuted_dependencies: Vec<_> = self.session.store.get_dependencies_of_task(&node).cloned().collect();
+    let non_determinism = NonDeterminism::compare(task, output, dependencies, reexecuted_output.clone(),
+      reexecuted_dependencies);
+    self.session.tracker.check_determinism_end(span, task, non_determinism.as_ref());
+    self.end_span(span);
+    if let Some(non_determinism) = non_determinism {
+      self.session.non_deterministic_tasks.push(non_determinism);
+    }
+    reexecuted_output
+  }
+
+  /// Checks `task`, which was not executed because it is consistent, by running it anyway in a [`CheckingContext`],
+  /// storing a failure if the output of running it differs from its incremental `output`, and a failure for each file
+  /// it read without requiring or providing it.
+  fn check_differential(&mut self, task: &T, output: &T::Output) {
+    let file_system = self.session.file_system;
+    let previous_executing_task = self.session.current_executing_task.take();
+    let mut context = CheckingContext::new(self, file_system);
+    let rerun_output = task.execute(&mut context);
+    let undeclared_reads = context.into_undeclared_reads();
+    self.session.current_executing_task = previous_executing_task;
+
+    if &rerun_output != output {
+      let failure = DifferentialCheckFailure::OutputMismatch { task: task.clone(), output: output.clone(), rerun_output };
+      self.session.differential_check_failures.push(failure);
+    }
+    for path in undeclared_reads {
+      self.session.differential_check_failures.push(DifferentialCheckFailure::UndeclaredRead { task: task.clone(), path });
+    }
+  }
+
+  /// Checks whether given task should be executed, returning `true` if it should be executed. A task should be executed
+  /// if it is marked for execution, if any of its dependencies are inconsistent, or when it has no output.
+  fn should_execute_task(&mut self, node: &TaskNode) -> bool {
+    if self.session.store.task_marked_for_execution(node) {
+      return true;
+    }
+    // Borrow: because we pass `self` (which is `&mut self`) to `is_inconsistent` for recursive consistency checking,
+    //         we need to clone and collect dependencies into a `Vec`. Otherwise we have an immutable borrow of `self`
+    //         through `self.store` while we create a mutable borrow of `self`, which is not allowed.
+    let dependencies: Vec<_> = self.session.store.get_dependencies_of_task(node).cloned().collect();
+    for dependency in dependencies {
+      let span = self.start_span();
+      self.session.tracker.check_dependency_start(span, &dependency);
+      let inconsistency = match &dependency {
+        // Stamp files through the session's file stamp cache, so that a file required by many tasks is stamped once.
+        Dependency::RequireFile(d) | Dependency::ProvideFile(d) if self.session.file_stamp_caching => d
+          .is_inconsistent_cached(self.session.file_system, &mut self.session.file_stamp_cache)
+          .map(|s| s.map(Inconsistency::File)),
+        // Check evicted stamps without recomputing the evicted output, which this dependency does not need.
+        Dependency::RequireTask(d) if matches!(d.stamp(), OutputStamp::Evicted(_)) =>
+          Ok(self.is_evicted_task_dependency_inconsistent(d)),
+        _ => dependency.is_inconsistent(self),
+      };
+      self.session.tracker.check_dependency_end(span, &dependency, inconsistency.as_ref().map(|o| o.as_ref()));
+      self.end_span(span);
+      match inconsistency {
+        Ok(Some(_)) => return true,
+        Err(e) => { // Error while checking: store error and assume inconsistent
+          self.session.dependency_check_errors.push(e);
+          return true;
+        }
+        _ => {} // Consistent: continue checking
+      }
+    }
+    // Task has no dependencies or all dependencies are consistent. Should only execute if it has no output and its
+    // output was not evicted, meaning that it has never been executed before.
+    return !self.session.store.task_has_output(node) && !self.session.store.task_output_evicted(node);
+  }
+
+  /// Checks whether task dependency `dependency` with an [evicted stamp](OutputStamp::Evicted) is inconsistent, by
+  /// making its task consistent without recomputing its output, and then comparing output generations. The dependency
+  /// is consistent if its task was not executed since its output was stamped, because the output is then unchanged.
+  fn is_evicted_task_dependency_inconsistent(
+    &mut self,
+    dependency: &TaskDependency<T, T::Output>
+  ) -> Option<Inconsistency<T::Output>> {
+    let OutputStamp::Evicted(generation) = dependency.stamp() else {
+      panic!("BUG: checking task dependency without evicted stamp as evicted");
+    };
+    let node = self.session.store.get_or_create_task_node(dependency.task());
+    self.make_task_consistent_lazily(dependency.task(), node);
+    let new_generation = self.session.store.get_task_output_generation(&node);
+    (new_generation != *generation).then_some(Inconsistency::Task(OutputStamp::Evicted(new_generation)))
+  }
+}
@@ -403,6 +403,16 @@ pub fn step_all(
         SourceArchive::new("source.zip")
       );
     });
+    stepper.with_path("8_require_twice", |stepper| {
+      stepper.apply_failure([
+        add("a_test.rs", "pie/tests/top_down.rs"),
+      ]);
+      stepper.apply([
+        create_diff_from_destination_file("b_store.rs", "pie/src/store.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
   });
 
   stepper.with_path("4_example", |stepper| {
@@ -472,4 +482,321 @@ pub fn step_all(
       );
     });
   });
+
+  stepper.with_path("5_tracking", |stepper| {
+    stepper.set_cargo_args(["test", "--all-features"]);
+
+    stepper.with_path("1_event_log", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_Cargo.toml", "pie/Cargo.toml"),
+        create_diff_from_destination_file("b_stamp.rs", "pie/src/stamp.rs"),
+        create_diff_from_destination_file("c_dependency.rs", "pie/src/dependency.rs"),
+        create_diff_from_destination_file("d_event.rs", "pie/src/tracker/event.rs"),
+        add("e_test.rs", "pie/tests/event_log.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+
+    stepper.with_path("2_multi_tracker", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_multi_tracker.rs", "pie/src/tracker/mod.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("3_event_model", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_tracker.rs", "pie/src/tracker/mod.rs"),
+        create_diff_from_destination_file("b_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("c_top_down.rs", "pie/src/context/top_down.rs"),
+        create_diff_from_destination_file("d_writing.rs", "pie/src/tracker/writing.rs"),
+        create_diff_from_destination_file("e_dependency.rs", "pie/src/dependency.rs"),
+        create_diff_from_destination_file("f_event.rs", "pie/src/tracker/event.rs"),
+        create_diff_from_destination_file("g_top_down_test.rs", "pie/tests/top_down.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("4_progress", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_tracker.rs", "pie/src/tracker/mod.rs"),
+        add("b_progress.rs", "pie/src/tracker/progress.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+  });
+
+  stepper.with_path("6_testing", |stepper| {
+    stepper.set_cargo_args(["test", "--all-features"]);
+    stepper.with_path("1_soundness", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_Cargo.toml", "pie/Cargo.toml"),
+        create_diff_from_destination_file("b_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("c_context_mod.rs", "pie/src/context/mod.rs"),
+        create_diff_from_destination_file("d_non_incremental.rs", "pie/src/context/non_incremental.rs"),
+        create_diff_from_destination_file("e_common.rs", "pie/tests/common/mod.rs"),
+        add("f_soundness.rs", "pie/tests/soundness.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("2_file_system", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_fs.rs", "pie/src/fs.rs"),
+        create_diff_from_destination_file("b_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("c_stamp.rs", "pie/src/stamp.rs"),
+        create_diff_from_destination_file("d_dependency.rs", "pie/src/dependency.rs"),
+        create_diff_from_destination_file("e_top_down.rs", "pie/src/context/top_down.rs"),
+        create_diff_from_destination_file("f_non_incremental.rs", "pie/src/context/non_incremental.rs"),
+        create_diff_from_destination_file("g_store.rs", "pie/src/store.rs"),
+        create_diff_from_destination_file("h_common.rs", "pie/tests/common/mod.rs"),
+        create_diff_from_destination_file("i_top_down_test.rs", "pie/tests/top_down.rs"),
+        create_diff_from_destination_file("j_soundness.rs", "pie/tests/soundness.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("3_scenario", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_common.rs", "pie/tests/common/mod.rs"),
+        add("b_scenario.rs", "pie/tests/common/scenario.rs"),
+        create_diff_from_destination_file("c_top_down_test.rs", "pie/tests/top_down.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("4_event_queries", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_event.rs", "pie/src/tracker/event.rs"),
+        create_diff_from_destination_file("b_top_down_test.rs", "pie/tests/top_down.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("5_determinism", |stepper| {
+      stepper.apply([
+        add("a_determinism.rs", "pie/src/determinism.rs"),
+        create_diff_from_destination_file("b_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("c_top_down.rs", "pie/src/context/top_down.rs"),
+        create_diff_from_destination_file("d_tracker.rs", "pie/src/tracker/mod.rs"),
+        create_diff_from_destination_file("e_event.rs", "pie/src/tracker/event.rs"),
+        create_diff_from_destination_file("f_writing.rs", "pie/src/tracker/writing.rs"),
+        create_diff_from_destination_file("g_common.rs", "pie/tests/common/mod.rs"),
+        create_diff_from_destination_file("h_scenario.rs", "pie/tests/common/scenario.rs"),
+        create_diff_from_destination_file("i_soundness.rs", "pie/tests/soundness.rs"),
+        add("j_determinism_test.rs", "pie/tests/determinism.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("6_checking", |stepper| {
+      stepper.apply([
+        add("a_checking.rs", "pie/src/context/checking.rs"),
+        create_diff_from_destination_file("b_context_mod.rs", "pie/src/context/mod.rs"),
+        create_diff_from_destination_file("c_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("d_top_down.rs", "pie/src/context/top_down.rs"),
+        create_diff_from_destination_file("e_common.rs", "pie/tests/common/mod.rs"),
+        create_diff_from_destination_file("f_scenario.rs", "pie/tests/common/scenario.rs"),
+        create_diff_from_destination_file("g_soundness.rs", "pie/tests/soundness.rs"),
+        add("h_checking_test.rs", "pie/tests/checking.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+  });
+
+  stepper.with_path("7_performance", |stepper| {
+    stepper.set_cargo_args(["test", "--all-features"]);
+    stepper.with_path("1_stamp_cache", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_Cargo.toml", "pie/Cargo.toml"),
+        create_diff_from_destination_file("b_stamp.rs", "pie/src/stamp.rs"),
+        create_diff_from_destination_file("c_dependency.rs", "pie/src/dependency.rs"),
+        create_diff_from_destination_file("d_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("e_top_down.rs", "pie/src/context/top_down.rs"),
+        add("f_stamp_cache_bench.rs", "pie/benches/stamp_cache.rs"),
+        create_diff_from_destination_file("g_top_down_test.rs", "pie/tests/top_down.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("2_reachability", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_Cargo.toml", "pie/Cargo.toml"),
+        add("b_reachability.rs", "pie/src/reachability.rs"),
+        create_diff_from_destination_file("c_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("d_store.rs", "pie/src/store.rs"),
+        add("e_hidden_dependency_bench.rs", "pie/benches/hidden_dependency.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("3_shared", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_Cargo.toml", "pie/Cargo.toml"),
+        create_diff_from_destination_file("b_store.rs", "pie/src/store.rs"),
+        create_diff_from_destination_file("c_stamp.rs", "pie/src/stamp.rs"),
+        create_diff_from_destination_file("d_dependency.rs", "pie/src/dependency.rs"),
+        create_diff_from_destination_file("e_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("f_top_down.rs", "pie/src/context/top_down.rs"),
+        create_diff_from_destination_file("g_non_incremental.rs", "pie/src/context/non_incremental.rs"),
+        create_diff_from_destination_file("h_checking.rs", "pie/src/context/checking.rs"),
+        add("i_shared_output_bench.rs", "pie/benches/shared_output.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("4_eviction", |stepper| {
+      stepper.apply([
+        add("a_lru.rs", "pie/src/lru.rs"),
+        create_diff_from_destination_file("b_stamp.rs", "pie/src/stamp.rs"),
+        create_diff_from_destination_file("c_dependency.rs", "pie/src/dependency.rs"),
+        create_diff_from_destination_file("d_store.rs", "pie/src/store.rs"),
+        create_diff_from_destination_file("e_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("f_top_down.rs", "pie/src/context/top_down.rs"),
+        create_diff_from_destination_file("g_determinism.rs", "pie/src/determinism.rs"),
+        add("h_eviction_test.rs", "pie/tests/eviction.rs"),
+        create_diff_from_destination_file("i_soundness.rs", "pie/tests/soundness.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+  });
+
+  stepper.with_path("8_library", |stepper| {
+    stepper.set_cargo_args(["test", "--all-features"]);
+    stepper.with_path("1_command", |stepper| {
+      stepper.apply([
+        add("a_command.rs", "pie/src/command.rs"),
+        create_diff_from_destination_file("b_lib.rs", "pie/src/lib.rs"),
+        add("c_command_test.rs", "pie/tests/command.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("2_file_tasks", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_fs.rs", "pie/src/fs.rs"),
+        create_diff_from_destination_file("b_checking.rs", "pie/src/context/checking.rs"),
+        create_diff_from_destination_file("c_stamp_cache_bench.rs", "pie/benches/stamp_cache.rs"),
+        add("d_tasks.rs", "pie/src/tasks.rs"),
+        create_diff_from_destination_file("e_lib.rs", "pie/src/lib.rs"),
+        add("f_tasks_test.rs", "pie/tests/tasks.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+  });
+
+  stepper.with_path("9_production", |stepper| {
+    stepper.set_cargo_args(["test", "--all-features"]);
+    stepper.with_path("1_atomic_writes", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_fs.rs", "pie/src/fs.rs"),
+        create_diff_from_destination_file("b_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("c_tasks.rs", "pie/src/tasks.rs"),
+        create_diff_from_destination_file("d_common.rs", "pie/tests/common/mod.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("2_modified_files", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_store.rs", "pie/src/store.rs"),
+        create_diff_from_destination_file("b_dependency.rs", "pie/src/dependency.rs"),
+        create_diff_from_destination_file("c_tracker.rs", "pie/src/tracker/mod.rs"),
+        create_diff_from_destination_file("d_event.rs", "pie/src/tracker/event.rs"),
+        create_diff_from_destination_file("e_writing.rs", "pie/src/tracker/writing.rs"),
+        create_diff_from_destination_file("f_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("g_top_down.rs", "pie/src/context/top_down.rs"),
+        create_diff_from_destination_file("h_common.rs", "pie/tests/common/mod.rs"),
+        add("i_modified_files_test.rs", "pie/tests/modified_files.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("3_output_policy", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("b_top_down.rs", "pie/src/context/top_down.rs"),
+        add("c_output_policy_test.rs", "pie/tests/output_policy.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("4_diagnostics", |stepper| {
+      stepper.apply([
+        add("a_diagnostic.rs", "pie/src/diagnostic.rs"),
+        create_diff_from_destination_file("b_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("c_non_incremental.rs", "pie/src/context/non_incremental.rs"),
+        create_diff_from_destination_file("d_checking.rs", "pie/src/context/checking.rs"),
+        create_diff_from_destination_file("e_store.rs", "pie/src/store.rs"),
+        create_diff_from_destination_file("f_tracker.rs", "pie/src/tracker/mod.rs"),
+        create_diff_from_destination_file("g_event.rs", "pie/src/tracker/event.rs"),
+        create_diff_from_destination_file("h_writing.rs", "pie/src/tracker/writing.rs"),
+        create_diff_from_destination_file("i_top_down.rs", "pie/src/context/top_down.rs"),
+        add("j_diagnostics_test.rs", "pie/tests/diagnostics.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("5_log", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("b_non_incremental.rs", "pie/src/context/non_incremental.rs"),
+        create_diff_from_destination_file("c_checking.rs", "pie/src/context/checking.rs"),
+        create_diff_from_destination_file("d_store.rs", "pie/src/store.rs"),
+        create_diff_from_destination_file("e_tracker.rs", "pie/src/tracker/mod.rs"),
+        create_diff_from_destination_file("f_event.rs", "pie/src/tracker/event.rs"),
+        create_diff_from_destination_file("g_writing.rs", "pie/src/tracker/writing.rs"),
+        create_diff_from_destination_file("h_top_down.rs", "pie/src/context/top_down.rs"),
+        add("i_log_test.rs", "pie/tests/log.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("6_path_normalization", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_fs.rs", "pie/src/fs.rs"),
+        create_diff_from_destination_file("b_checking.rs", "pie/src/context/checking.rs"),
+        create_diff_from_destination_file("c_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("d_top_down.rs", "pie/src/context/top_down.rs"),
+        add("e_path_normalization_test.rs", "pie/tests/path_normalization.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("7_cycles", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_store.rs", "pie/src/store.rs"),
+        create_diff_from_destination_file("b_top_down.rs", "pie/src/context/top_down.rs"),
+        create_diff_from_destination_file("c_top_down_test.rs", "pie/tests/top_down.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("8_invalidation", |stepper| {
+      stepper.apply([
+        create_diff_from_destination_file("a_reachability.rs", "pie/src/reachability.rs"),
+        create_diff_from_destination_file("b_store.rs", "pie/src/store.rs"),
+        create_diff_from_destination_file("c_lib.rs", "pie/src/lib.rs"),
+        add("d_invalidate_test.rs", "pie/tests/invalidate.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+    stepper.with_path("9_validation", |stepper| {
+      stepper.apply([
+        add("a_validation.rs", "pie/src/validation.rs"),
+        create_diff_from_destination_file("b_store.rs", "pie/src/store.rs"),
+        create_diff_from_destination_file("c_lib.rs", "pie/src/lib.rs"),
+        create_diff_from_destination_file("d_top_down.rs", "pie/src/context/top_down.rs"),
+        create_diff_from_destination_file("e_invalidate_test.rs", "pie/tests/invalidate.rs"),
+      ]).output(
+        SourceArchive::new("source.zip")
+      );
+    });
+  });
 }